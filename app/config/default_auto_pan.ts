@@ -0,0 +1,10 @@
+import type { AutoPan } from "~/types/auto_pan";
+import { LfoWaveform } from "~/sound/synth_api_service";
+
+export const default_auto_pan_config: AutoPan = {
+  id: 0,
+  rate: 26,
+  width: 100,
+  waveform: LfoWaveform.SINE,
+  phase_offset: 0,
+};
@@ -0,0 +1,12 @@
+import type { Chorus } from "~/types/chorus";
+
+export const default_chorus_config: Chorus = {
+  id: 0,
+  rate: 20,
+  depth: 30,
+  base_delay: 30,
+  voices: 67,
+  spread: 50,
+  dry: 100,
+  wet: 50,
+};
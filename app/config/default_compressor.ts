@@ -0,0 +1,14 @@
+import type { Compressor } from "~/types/compressor";
+
+export const default_compressor_config: Compressor = {
+  id: 0,
+  threshold: 70,
+  ratio: 40,
+  knee: 25,
+  attack: 67,
+  release: 54,
+  makeup: 0,
+  link: 100,
+  dry: 0,
+  wet: 100,
+};
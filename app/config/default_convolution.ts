@@ -0,0 +1,11 @@
+import type { Convolution } from "~/types/convolution";
+
+export const default_convolution_config: Convolution = {
+  id: 0,
+  ir_sample: null,
+  trim_start: 0,
+  length: 100,
+  pre_delay: 0,
+  dry: 100,
+  wet: 30,
+};
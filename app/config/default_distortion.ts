@@ -0,0 +1,14 @@
+import type { Distortion } from "~/types/distortion";
+
+export const default_distortion_config: Distortion = {
+  id: 0,
+  curve: 0,
+  drive: 25,
+  output: 70,
+  tone: 80,
+  oversampling: 50,
+  bits: 47,
+  downsample: 0,
+  dry: 0,
+  wet: 100,
+};
@@ -0,0 +1,10 @@
+import type { Ducker } from "~/types/ducker";
+
+export const default_ducker_config: Ducker = {
+  id: 0,
+  threshold: 69,
+  duck: 13,
+  attack: 59,
+  hold: 10,
+  release: 57,
+};
@@ -8,4 +8,8 @@ export const default_echo_config: Echo = {
   r_delay_offset: 50,
   dry: 0.7,
   wet: 1,
+  ping_pong: false,
+  low_cut: 0,
+  high_cut: 100,
+  saturation: 0,
 };
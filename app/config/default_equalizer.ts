@@ -0,0 +1,24 @@
+import { FilterType } from "~/sound/synth_api_service";
+import type { EqBand, Equalizer } from "~/types/equalizer";
+
+// fréquences des bandes du moteur (40 Hz à 16 kHz) sur l'échelle log du knob
+const frequencies = [10, 23, 37, 49, 62, 75, 87, 97];
+
+export const default_equalizer_config: Equalizer = {
+  id: 0,
+  bands: frequencies.map(
+    (frequency, index): EqBand => ({
+      type:
+        index === 0
+          ? FilterType.LOW_SHELF
+          : index === frequencies.length - 1
+            ? FilterType.HIGH_SHELF
+            : FilterType.BELL,
+      frequency,
+      gain: 50,
+      q: 36,
+      enabled: true,
+    })
+  ),
+  output: 50,
+};
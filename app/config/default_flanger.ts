@@ -0,0 +1,12 @@
+import type { Flanger } from "~/types/flanger";
+
+export const default_flanger_config: Flanger = {
+  id: 0,
+  rate: 5,
+  depth: 20,
+  manual: 10,
+  feedback: 75,
+  spread: 25,
+  dry: 100,
+  wet: 70,
+};
@@ -0,0 +1,12 @@
+import type { Gate } from "~/types/gate";
+
+export const default_gate_config: Gate = {
+  id: 0,
+  threshold: 48,
+  ratio: 44,
+  knee: 25,
+  attack: 33,
+  release: 59,
+  range: 83,
+  link: 100,
+};
@@ -0,0 +1,11 @@
+import type { Ladder } from "~/types/ladder";
+
+export const default_ladder_config: Ladder = {
+  id: 0,
+  cutoff: 57,
+  resonance: 27,
+  drive: 0,
+  poles: 100,
+  dry: 0,
+  wet: 100,
+};
@@ -0,0 +1,10 @@
+import type { Limiter } from "~/types/limiter";
+
+export const default_limiter_config: Limiter = {
+  id: 0,
+  ceiling: 99,
+  release: 57,
+  lookahead: 14,
+  input_gain: 0,
+  link: 100,
+};
@@ -0,0 +1,13 @@
+import type { Phaser } from "~/types/phaser";
+
+export const default_phaser_config: Phaser = {
+  id: 0,
+  rate: 10,
+  depth: 80,
+  center: 40,
+  feedback: 70,
+  stages: 40,
+  spread: 25,
+  dry: 100,
+  wet: 100,
+};
@@ -0,0 +1,19 @@
+import { SHIFTER_VOICES } from "~/sound/synth_api_service";
+import type { PitchShifter, ShifterVoice } from "~/types/pitch_shifter";
+
+// seule la première voix est active, une octave au-dessus
+export const default_pitch_shifter_config: PitchShifter = {
+  id: 0,
+  harmony: Array.from(
+    { length: SHIFTER_VOICES },
+    (_, index): ShifterVoice => ({
+      semitones: index === 0 ? 75 : 50,
+      cents: 50,
+      level: index === 0 ? 100 : 0,
+      pan: 50,
+    })
+  ),
+  window: 44,
+  dry: 100,
+  wet: 70,
+};
@@ -0,0 +1,13 @@
+import type { Reverb } from "~/types/reverb";
+
+export const default_reverb_config: Reverb = {
+  id: 0,
+  size: 50,
+  decay: 10,
+  pre_delay: 2,
+  damping: 40,
+  diffusion: 70,
+  width: 100,
+  dry: 100,
+  wet: 30,
+};
@@ -0,0 +1,10 @@
+import type { RingModulator } from "~/types/ring_modulator";
+import { LfoWaveform } from "~/sound/synth_api_service";
+
+export const default_ring_modulator_config: RingModulator = {
+  id: 0,
+  carrier: 70,
+  depth: 100,
+  waveform: LfoWaveform.SINE,
+  phase_offset: 0,
+};
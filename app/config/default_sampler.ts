@@ -1,3 +1,4 @@
+import { FX_BUSES, Interpolation } from "~/sound/synth_api_service";
 import type { Sampler } from "~/types/sampler";
 
 export const default_sampler_config: Sampler = {
@@ -6,6 +7,8 @@ export const default_sampler_config: Sampler = {
   sample_id: 0,
   id: 0,
   shift: 50,
+  interpolation: Interpolation.LINEAR,
+  sends: Array.from({ length: FX_BUSES }, () => ({ level: 0, pre_fader: false })),
   adsr: {
     attack: 10,
     decay: 10,
@@ -0,0 +1,12 @@
+import type { StereoTool } from "~/types/stereo_tool";
+
+export const default_stereo_tool_config: StereoTool = {
+  id: 0,
+  width: 50,
+  haas: 50,
+  bass_mono: 0,
+  swap: false,
+  invert_left: false,
+  invert_right: false,
+  mono: false,
+};
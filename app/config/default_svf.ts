@@ -0,0 +1,12 @@
+import type { Svf } from "~/types/svf";
+
+export const default_svf_config: Svf = {
+  id: 0,
+  cutoff: 57,
+  resonance: 8,
+  low: 100,
+  band: 50,
+  high: 50,
+  dry: 0,
+  wet: 100,
+};
@@ -0,0 +1,10 @@
+import type { Tremolo } from "~/types/tremolo";
+import { LfoWaveform } from "~/sound/synth_api_service";
+
+export const default_tremolo_config: Tremolo = {
+  id: 0,
+  rate: 50,
+  intensity: 50,
+  waveform: LfoWaveform.SINE,
+  phase_offset: 0,
+};
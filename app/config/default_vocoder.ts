@@ -0,0 +1,18 @@
+import type { Vocoder } from "~/types/vocoder";
+import { BandSpacing, ModulatorSource } from "~/sound/synth_api_service";
+
+export const default_vocoder_config: Vocoder = {
+  id: 0,
+  modulator: ModulatorSource.SAMPLE,
+  modulator_sample: null,
+  band_count: 43,
+  spacing: BandSpacing.LOGARITHMIC,
+  low: 10,
+  high: 40,
+  attack: 1,
+  release: 10,
+  noise: 5,
+  output: 75,
+  dry: 0,
+  wet: 100,
+};
@@ -39,6 +39,10 @@ export class MidiController {
       SynthApi.playNote({ value: note });
     } else if (status === 137 || (status === 153 && velocity === 0)) {
       SynthApi.stopNote(note);
+    } else if ((status & 0xf0) === 0xb0) {
+      SynthApi.controlChange(note, velocity);
+    } else if ((status & 0xf0) === 0xc0) {
+      SynthApi.programChange(note);
     }
   }
 }
@@ -11,14 +11,19 @@ export class AudioEngineOrchestrator {
   private audioCtx!: AudioContext;
   private workletNode!: AudioWorkletNode;
   private rustWorker!: Worker;
+  private inputSource: MediaStreamAudioSourceNode | null = null;
   public shared_sound_buffer: SharedArrayBuffer;
 
   private constructor() {
     const float32ringBufferBytes = RING_BUFFER_SIZE * Float32Array.BYTES_PER_ELEMENT;
 
     const indexesBytes = Int32Array.BYTES_PER_ELEMENT * 3;
+    // entrée audio après la sortie : [write, read] puis un ring buffer de même taille
+    const inputIndexesBytes = Int32Array.BYTES_PER_ELEMENT * 2;
 
-    this.shared_sound_buffer = new SharedArrayBuffer(indexesBytes + float32ringBufferBytes);
+    this.shared_sound_buffer = new SharedArrayBuffer(
+      indexesBytes + float32ringBufferBytes + inputIndexesBytes + float32ringBufferBytes
+    );
 
     const indexes = new Int32Array(this.shared_sound_buffer, 0, 3);
     const flag = indexes.subarray(0, 1);
@@ -37,8 +42,8 @@ export class AudioEngineOrchestrator {
     midi_queue_buffer: SharedArrayBuffer,
     osc_queue_buffer: SharedArrayBuffer,
     fx_queue_buffer: SharedArrayBuffer,
-    sample_event_buffer: SharedArrayBuffer,
-    sample_buffer: SharedArrayBuffer
+    sample_upload_buffer: SharedArrayBuffer,
+    sample_transfer_buffer: SharedArrayBuffer
   ): Promise<Worker | null> {
     try {
       if (this.audioCtx) return null;
@@ -61,8 +66,8 @@ export class AudioEngineOrchestrator {
             midi_queue_buffer: midi_queue_buffer,
             osc_queue_buffer: osc_queue_buffer,
             fx_queue_buffer: fx_queue_buffer,
-            sample_event_buffer: sample_event_buffer,
-            sample_buffer: sample_buffer,
+            sample_upload_buffer: sample_upload_buffer,
+            sample_transfer_buffer: sample_transfer_buffer,
           });
         }
       };
@@ -115,11 +120,37 @@ export class AudioEngineOrchestrator {
     }
   }
 
+  /**
+   * Branche le micro (ou l'entrée de la carte son) sur le worklet : le moteur le reçoit
+   * comme entrée audio, utilisée par exemple comme modulateur du vocodeur.
+   */
+  public async enable_audio_input(): Promise<boolean> {
+    if (!this.audioCtx || !this.workletNode) return false;
+    if (this.inputSource) return true;
+
+    try {
+      const stream = await navigator.mediaDevices.getUserMedia({
+        audio: { echoCancellation: false, noiseSuppression: false, autoGainControl: false },
+      });
+      this.inputSource = this.audioCtx.createMediaStreamSource(stream);
+      this.inputSource.connect(this.workletNode);
+      return true;
+    } catch (error) {
+      console.error("[AUDIO ENGINE] audio input unavailable", error);
+      return false;
+    }
+  }
+
   release() {
     this.refCount--;
     if (this.refCount <= 0) {
       console.log("stop to audio context");
       if (this.workletNode) this.workletNode.disconnect();
+      if (this.inputSource) {
+        this.inputSource.mediaStream.getTracks().forEach((track) => track.stop());
+        this.inputSource.disconnect();
+        this.inputSource = null;
+      }
 
       this.audioCtx.close();
       this.rustWorker.terminate();
@@ -10,6 +10,10 @@ class AudioWorkletManager extends AudioWorkletProcessor {
 
   private flag: Int32Array;
 
+  private inputRingBuffer: Float32Array<any>;
+  private inputWriteIndex: Int32Array<any>;
+  private inputReadIndex: Int32Array<any>;
+
   private previous_input_left = 0;
   private previous_input_right = 0;
 
@@ -28,9 +32,42 @@ class AudioWorkletManager extends AudioWorkletProcessor {
 
     const indexesBytes = Int32Array.BYTES_PER_ELEMENT * 3;
     this.ringBuffer = new Float32Array(sharedBuffer, indexesBytes, this.ringBufferSize);
+
+    // entrée audio : [write, read] puis un ring buffer de même taille, après la sortie
+    const inputIndexesOffset = indexesBytes + this.ringBufferSize * Float32Array.BYTES_PER_ELEMENT;
+    const inputIndexes = new Int32Array(sharedBuffer, inputIndexesOffset, 2);
+    this.inputWriteIndex = inputIndexes.subarray(0, 1);
+    this.inputReadIndex = inputIndexes.subarray(1, 2);
+    this.inputRingBuffer = new Float32Array(
+      sharedBuffer,
+      inputIndexesOffset + Int32Array.BYTES_PER_ELEMENT * 2,
+      this.ringBufferSize
+    );
+  }
+
+  // copie l'entrée entrelacée pour le moteur, les frames sont perdues si le ring est plein
+  private writeInput(input: Float32Array[]) {
+    if (!input || input.length === 0) return;
+    const left = input[0];
+    const right = input[1] ?? input[0];
+
+    let wIndex = Atomics.load(this.inputWriteIndex, 0);
+    const rIndex = Atomics.load(this.inputReadIndex, 0);
+
+    for (let i = 0; i < left.length; i++) {
+      const next = (wIndex + 2) % this.ringBufferSize;
+      if (next === rIndex) break;
+      this.inputRingBuffer[wIndex] = left[i];
+      this.inputRingBuffer[wIndex + 1] = right[i];
+      wIndex = next;
+    }
+
+    Atomics.store(this.inputWriteIndex, 0, wIndex);
   }
 
   process(inputs: any, outputs: any, parameters: any) {
+    this.writeInput(inputs[0]);
+
     const output = outputs[0];
     const left = output[0];
     const right = output[1] ?? output[0];
@@ -15,10 +15,10 @@ fn sinc(x: f32) -> f32 {
 }
 
 /// Fenêtre de Hamming
-fn hamming_window(n: usize, N: usize) -> f32 {
+fn hamming_window(n: usize, size: usize) -> f32 {
     let nf = n as f32;
-    let Nf = N as f32;
-    0.54 - 0.46 * ((2.0 * PI * nf) / (Nf - 1.0)).cos()
+    let size_f = size as f32;
+    0.54 - 0.46 * ((2.0 * PI * nf) / (size_f - 1.0)).cos()
 }
 
 /// Interpolation sinc
@@ -0,0 +1,109 @@
+//! Coût du mixer par bloc selon sa taille : `cargo bench --bench mixer`
+//!
+//! Un bloc d'une frame donne le coût du verrou du `Mutex` et de l'appel virtuel de chaque
+//! effet payés à chaque frame ; ce n'est pas l'ancien moteur (lissage, envois et tranches
+//! sont ceux d'aujourd'hui), seulement la part que le traitement par bloc amortit.
+
+use std::{hint::black_box, sync::Mutex, time::Instant};
+
+use rust_synth::{
+    sound_engine::{
+        dsp::{fx::EffectsEnum, mixer::Mixer, smoothing::SmoothedParam},
+        synthetizer::sampler::Sampler,
+    },
+    utils::{
+        constants::{CHANNEL_CHAIN_BASE, MAX_FX_BUSES, SAMPLE_RATE},
+        types::{Interpolation, SamplerSend},
+    },
+};
+
+const FRAMES: usize = 44_100 * 5;
+const SAMPLERS: u8 = 4;
+
+fn sampler(id: u8) -> Sampler {
+    let mut sends = [SamplerSend::default(); MAX_FX_BUSES];
+    sends[0].level = SmoothedParam::new(0.3);
+    Sampler {
+        id,
+        sample_id: 0,
+        instrument_id: None,
+        attack_length: 0,
+        hold_length: 0,
+        decay_length: 0,
+        sustain_gain: 1.0,
+        release_length: 0,
+        frequency_shift: 0.0,
+        phase_shift: 0.0,
+        delay_length: 0,
+        gain: SmoothedParam::new(0.8),
+        gain_l: SmoothedParam::new(1.0),
+        gain_r: SmoothedParam::new(1.0),
+        interpolation: Interpolation::Linear,
+        sends,
+    }
+}
+
+/// Session type : un compresseur par tranche, une reverb sur le bus 1 et cinq effets
+/// sur le master
+fn session() -> Mutex<Mixer> {
+    let mut mixer = Mixer::new();
+    let mut id = 0;
+    for sampler_id in 0..SAMPLERS {
+        mixer.add_channel(sampler_id);
+        mixer.create_effect(id, EffectsEnum::Compressor);
+        mixer.move_fx(id, 0, CHANNEL_CHAIN_BASE + sampler_id as usize);
+        id += 1;
+    }
+    mixer.create_effect(id, EffectsEnum::Reverb);
+    mixer.move_fx(id, 0, 1);
+    id += 1;
+    mixer.create_effect(id, EffectsEnum::Filter);
+    mixer.create_effect(id + 1, EffectsEnum::Equalizer);
+    mixer.create_effect(id + 2, EffectsEnum::Echo);
+    mixer.create_effect(id + 3, EffectsEnum::Chorus);
+    mixer.create_effect(id + 4, EffectsEnum::Compressor);
+    Mutex::new(mixer)
+}
+
+fn main() {
+    let mut samplers = (0..SAMPLERS).map(sampler).collect::<Vec<_>>();
+    // sortie avant fader des samplers, frame par frame
+    let channel_frames = (0..FRAMES * SAMPLERS as usize)
+        .map(|i| {
+            let phase = (i / SAMPLERS as usize) as f32 / SAMPLE_RATE * (110.0 + i as f32 % 4.0);
+            [(phase * std::f32::consts::TAU).sin(); 2]
+        })
+        .collect::<Vec<_>>();
+    let input = vec![[0.0; 2]; FRAMES];
+    let mut output = vec![[0.0; 2]; FRAMES];
+
+    // budget temps réel d'une frame de sortie
+    let budget_ns = 1e9 / SAMPLE_RATE as f64;
+
+    println!("bloc (frames)   ns/frame   ns/bloc    % d'un coeur");
+    for block_size in [1, 32, 128, 1024] {
+        let mixer = session();
+
+        let start = Instant::now();
+        for (block_index, block) in output.chunks_mut(block_size).enumerate() {
+            let first = block_index * block_size;
+            let channels = &channel_frames
+                [first * SAMPLERS as usize..(first + block.len()) * SAMPLERS as usize];
+            let input = &input[first..first + block.len()];
+            mixer
+                .lock()
+                .unwrap()
+                .render_block(block, black_box(channels), &mut samplers, input);
+        }
+        black_box(&output);
+        let ns_per_frame = start.elapsed().as_nanos() as f64 / FRAMES as f64;
+
+        println!(
+            "{:>13}   {:>8.1}   {:>7.0}   {:>6.2} %",
+            block_size,
+            ns_per_frame,
+            ns_per_frame * block_size as f64,
+            ns_per_frame / budget_ns * 100.0
+        );
+    }
+}
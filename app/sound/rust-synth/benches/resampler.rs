@@ -0,0 +1,65 @@
+//! Coût CPU d'une voix selon l'interpolation : `cargo bench --bench resampler`
+
+use std::{hint::black_box, time::Instant};
+
+use rust_synth::{
+    sound_engine::synthetizer::sample_manager::SampleManager,
+    utils::{
+        constants::SAMPLE_RATE,
+        toolkit::ToolKit,
+        types::{Interpolation, Sample},
+    },
+};
+
+const FRAMES: usize = 44_100 * 5;
+
+fn main() {
+    // sinus stéréo d'une seconde, bouclé
+    let length = SAMPLE_RATE as usize;
+    let mut values = Vec::with_capacity(length * 2);
+    for channel in 0..2 {
+        values.extend((0..length).map(|i| {
+            let phase = i as f32 / SAMPLE_RATE * 440.0 + channel as f32 * 0.25;
+            (phase * std::f32::consts::TAU).sin()
+        }));
+    }
+
+    let mut sample_manager = SampleManager::new();
+    sample_manager.samples.push(Sample::new(1, values, 2, 0));
+
+    // budget temps réel d'une frame de sortie
+    let budget_ns = 1e9 / SAMPLE_RATE as f64;
+
+    println!("interpolation   transposition   ns/frame   % d'un coeur par voix");
+    for interpolation in [
+        Interpolation::Linear,
+        Interpolation::Cubic,
+        Interpolation::Sinc8,
+        Interpolation::Sinc32,
+    ] {
+        for note in [55, 67, 84] {
+            let frequency = ToolKit::midi_to_freq(note);
+            let mut position = 0.0;
+
+            let start = Instant::now();
+            for _ in 0..FRAMES {
+                black_box(sample_manager.read(
+                    1,
+                    &mut position,
+                    black_box(frequency),
+                    false,
+                    interpolation,
+                ));
+            }
+            let ns_per_frame = start.elapsed().as_nanos() as f64 / FRAMES as f64;
+
+            println!(
+                "{:<15} {:>+4} demi-tons   {:>8.1}   {:>6.2} %",
+                format!("{:?}", interpolation),
+                note as i32 - 60,
+                ns_per_frame,
+                ns_per_frame / budget_ns * 100.0
+            );
+        }
+    }
+}
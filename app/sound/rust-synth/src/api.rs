@@ -1,17 +1,31 @@
-use js_sys::{Atomics, Float32Array, Int32Array, SharedArrayBuffer, Uint8Array};
-use wasm_bindgen::prelude::wasm_bindgen;
+use js_sys::{
+    Array, Atomics, Float32Array, Int32Array, JsString, Object, Reflect, SharedArrayBuffer,
+    Uint8Array,
+};
+use wasm_bindgen::{JsValue, prelude::wasm_bindgen};
 use web_sys::console;
 
 use crate::{
-    global::{AUDIO_PROCESSOR, SHARED_BUFFERS},
+    global::{AUDIO_PROCESSOR, MIXER, SHARED_BUFFERS},
     shared_memory::{
         ring_buffer_manager::RingBufferManager,
-        shared_buffers::{AudioBuffers, FxBuffers, MidiBuffers, SamplerBuffers, SharedBuffers},
+        shared_buffers::{
+            AudioBuffers, FxBuffers, MidiBuffers, SampleUploadBuffers, SamplerBuffers,
+            SharedBuffers,
+        },
+    },
+    sound_engine::{
+        dsp::{fx::EffectsEnum, resampler},
+        event_handler::EventHandler,
+        processor::AudioProcessor,
     },
-    sound_engine::{event_handler::EventHandler, processor::AudioProcessor},
     utils::constants::{
-        FLAG_INDEX, FX_QUEUE_CAPACITY, HEADERS_SIZE_BYTES, MIDI_READ_INDEX, MIDI_WRITE_INDEX,
-        READ_INDEX, WRITE_INDEX,
+        FLAG_INDEX, FX_METER_CAPACITY, FX_METER_SIZE, FX_QUEUE_CAPACITY, FX_READ_INDEX,
+        FX_RESPONSE_POINTS, FX_WRITE_INDEX, HEADERS_SIZE_BYTES, INPUT_HEADERS_SIZE_BYTES,
+        INPUT_READ_INDEX, INPUT_WRITE_INDEX, MIDI_READ_INDEX, MIDI_WRITE_INDEX, READ_INDEX,
+        SAMPLE_TRANSFER_SLOTS, SAMPLE_UPLOAD_EVENT_SIZE, SAMPLE_UPLOAD_QUEUE_CAPACITY,
+        SAMPLE_UPLOAD_READ_INDEX, SAMPLE_UPLOAD_STATUS_CAPACITY, SAMPLE_UPLOAD_STATUS_SIZE,
+        SAMPLE_UPLOAD_WRITE_INDEX, WRITE_INDEX,
     },
 };
 
@@ -22,8 +36,8 @@ pub fn init_audio_thread(
     midi_buffer: SharedArrayBuffer,
     osc_buffer: SharedArrayBuffer,
     fx_buffer: SharedArrayBuffer,
-    sample_event_buffer: SharedArrayBuffer,
-    sample_buffer: SharedArrayBuffer,
+    sample_upload_buffer: SharedArrayBuffer,
+    sample_transfer_buffer: SharedArrayBuffer,
 ) {
     init_shared_buffers(
         &shared_audio_buffer,
@@ -31,14 +45,15 @@ pub fn init_audio_thread(
         midi_buffer,
         osc_buffer,
         fx_buffer,
-        sample_event_buffer,
-        sample_buffer,
+        sample_upload_buffer,
+        sample_transfer_buffer,
     );
     init_audio_processor();
     console::log_1(&"Buffers et processeur audio initialisés".into());
 }
 
 fn init_audio_processor() {
+    resampler::init_kernels();
     let audio_processor = AudioProcessor::new();
 
     AUDIO_PROCESSOR.with(|p| *p.borrow_mut() = Some(audio_processor));
@@ -50,20 +65,35 @@ fn init_shared_buffers(
     midi_buffer: SharedArrayBuffer,
     osc_buffer: SharedArrayBuffer,
     fx_buffer: SharedArrayBuffer,
-    sample_event_buffer: SharedArrayBuffer,
-    sample_buffer: SharedArrayBuffer,
+    sample_upload_buffer: SharedArrayBuffer,
+    sample_transfer_buffer: SharedArrayBuffer,
 ) {
     // -------- Audio --------
-    let control_arr = Int32Array::new(&shared_audio_buffer);
+    let control_arr = Int32Array::new(shared_audio_buffer);
     let flag = control_arr.subarray(FLAG_INDEX, FLAG_INDEX + 1);
     let read_idx = control_arr.subarray(READ_INDEX, READ_INDEX + 1);
     let write_idx = control_arr.subarray(WRITE_INDEX, WRITE_INDEX + 1);
 
-    let audio_data_start_elem = (HEADERS_SIZE_BYTES / 4) as u32;
+    let audio_data_start_elem = HEADERS_SIZE_BYTES / 4;
     let ring_buffer_end_elem = audio_data_start_elem + ring_buffer_size;
-    let ring_buffer = Float32Array::new(&shared_audio_buffer)
+    let ring_buffer = Float32Array::new(shared_audio_buffer)
         .subarray(audio_data_start_elem, ring_buffer_end_elem);
 
+    let input_control_start = ring_buffer_end_elem;
+    let input_write_idx = control_arr.subarray(
+        input_control_start + INPUT_WRITE_INDEX,
+        input_control_start + INPUT_WRITE_INDEX + 1,
+    );
+    let input_read_idx = control_arr.subarray(
+        input_control_start + INPUT_READ_INDEX,
+        input_control_start + INPUT_READ_INDEX + 1,
+    );
+    let input_data_start_elem = input_control_start + INPUT_HEADERS_SIZE_BYTES / 4;
+    let input_ring_buffer = Float32Array::new(shared_audio_buffer).subarray(
+        input_data_start_elem,
+        input_data_start_elem + ring_buffer_size,
+    );
+
     // -------- MIDI --------
     let midi_control_arr = Int32Array::new(&midi_buffer);
     let midi_write_idx = midi_control_arr.subarray(MIDI_WRITE_INDEX, MIDI_WRITE_INDEX + 1);
@@ -76,16 +106,30 @@ fn init_shared_buffers(
     let osc_read_idx = osc_control_arr.subarray(1, 2);
     let osc_queue = Uint8Array::new(&osc_buffer).subarray(8, osc_buffer.byte_length());
 
-    // samples event
+    // -------- Samples --------
+    // write_idx, read_idx, états des slots, file d'événements, table de statuts
+    let upload_control_arr = Int32Array::new(&sample_upload_buffer);
+    let upload_write_idx =
+        upload_control_arr.subarray(SAMPLE_UPLOAD_WRITE_INDEX, SAMPLE_UPLOAD_WRITE_INDEX + 1);
+    let upload_read_idx =
+        upload_control_arr.subarray(SAMPLE_UPLOAD_READ_INDEX, SAMPLE_UPLOAD_READ_INDEX + 1);
 
-    let sample_event_view = Int32Array::new(&sample_event_buffer);
-    let sample_buffer_view = Float32Array::new(&sample_buffer);
+    let slots_start = 2;
+    let queue_start = slots_start + SAMPLE_TRANSFER_SLOTS;
+    let status_start = queue_start + SAMPLE_UPLOAD_QUEUE_CAPACITY * SAMPLE_UPLOAD_EVENT_SIZE;
+    let status_end = status_start + SAMPLE_UPLOAD_STATUS_CAPACITY * SAMPLE_UPLOAD_STATUS_SIZE;
+
+    let upload_slot_states = upload_control_arr.subarray(slots_start, queue_start);
+    let upload_queue = upload_control_arr.subarray(queue_start, status_start);
+    let upload_status = upload_control_arr.subarray(status_start, status_end);
+    let transfer_view = Float32Array::new(&sample_transfer_buffer);
+    let transfer_bytes_view = Uint8Array::new(&sample_transfer_buffer);
 
     // -------- FX ------------------
 
     let fx_control_arr = Int32Array::new(&fx_buffer);
-    let fx_write_idx = fx_control_arr.subarray(0, 1);
-    let fx_read_idx = fx_control_arr.subarray(1, 2);
+    let fx_write_idx = fx_control_arr.subarray(FX_WRITE_INDEX, FX_WRITE_INDEX + 1);
+    let fx_read_idx = fx_control_arr.subarray(FX_READ_INDEX, FX_READ_INDEX + 1);
 
     // 2 Int32 pour write_idx + read_idx
     let fx_int_offset = 2 * 4; // 2 Int32 * 4 octets
@@ -104,6 +148,20 @@ fn init_shared_buffers(
         fx_float_offset / 4 + FX_QUEUE_CAPACITY,
     );
 
+    let fx_meter_offset = fx_float_offset + FX_QUEUE_CAPACITY * 4;
+    let fx_meters = fx_queue_int_full.subarray(
+        fx_meter_offset / 4,
+        fx_meter_offset / 4 + 1 + FX_METER_CAPACITY * FX_METER_SIZE,
+    );
+
+    let fx_response_offset = fx_meter_offset + (1 + FX_METER_CAPACITY * FX_METER_SIZE) * 4;
+    let fx_response_header =
+        fx_queue_int_full.subarray(fx_response_offset / 4, fx_response_offset / 4 + 2);
+    let fx_response = fx_queue_float_full.subarray(
+        fx_response_offset / 4 + 2,
+        fx_response_offset / 4 + 2 + FX_RESPONSE_POINTS,
+    );
+
     // -------- SharedBuffers --------
     let shared_buffers = SharedBuffers {
         audio: AudioBuffers {
@@ -111,6 +169,9 @@ fn init_shared_buffers(
             read_idx,
             write_idx,
             ring_buffer,
+            input_write_idx,
+            input_read_idx,
+            input_ring_buffer,
         },
         midi: MidiBuffers {
             write_idx: midi_write_idx,
@@ -127,15 +188,59 @@ fn init_shared_buffers(
             read_idx: fx_read_idx,
             queue_int: fx_queue_int,
             queue_float: fx_queue_float,
+            meters: fx_meters,
+            response_header: fx_response_header,
+            response: fx_response,
+        },
+        sample_upload: SampleUploadBuffers {
+            write_idx: upload_write_idx,
+            read_idx: upload_read_idx,
+            slot_states: upload_slot_states,
+            queue: upload_queue,
+            status: upload_status,
+            transfer: transfer_view,
+            transfer_bytes: transfer_bytes_view,
         },
-        sample_event: sample_event_view,
-        sample_buffer: sample_buffer_view,
     };
 
     _ = SHARED_BUFFERS.with(|cell| cell.set(shared_buffers));
     // Initialisation du processeur audio avec les samplers de test
 }
 
+/// Paramètres d'un type d'effet : `{ name, unit, min, max, default, curve, options }` dans
+/// l'ordre de leurs index, tableau vide pour un type inconnu. Ne dépend pas du thread audio.
+#[wasm_bindgen]
+pub fn describe_effect(effect_type: u32) -> Array {
+    let Ok(effect) = EffectsEnum::try_from(effect_type) else {
+        return Array::new();
+    };
+    effect
+        .params()
+        .iter()
+        .map(|info| {
+            let object = Object::new();
+            let options = info
+                .options
+                .iter()
+                .map(|&option| JsString::from(option))
+                .collect::<Array>();
+            let fields: [(&str, JsValue); 7] = [
+                ("name", info.name.into()),
+                ("unit", info.unit.into()),
+                ("min", info.min.into()),
+                ("max", info.max.into()),
+                ("default", info.default.into()),
+                ("curve", (info.curve as u32).into()),
+                ("options", options.into()),
+            ];
+            for (key, value) in fields {
+                _ = Reflect::set(&object, &key.into(), &value);
+            }
+            object
+        })
+        .collect()
+}
+
 #[wasm_bindgen]
 pub fn start_audio_processing_loop() {
     SHARED_BUFFERS.with(|cell| {
@@ -149,7 +254,6 @@ fn main_loop(buffers: &SharedBuffers) {
     let read_idx = &buffers.audio.read_idx;
     let write_idx = &buffers.audio.write_idx;
     let ring_buffer = &buffers.audio.ring_buffer;
-    let midi = &buffers.midi;
 
     console::log_1(&"Démarrage de la boucle audio (infinie)".into());
 
@@ -171,9 +275,24 @@ fn main_loop(buffers: &SharedBuffers) {
                 if sample_count_frames > 0 {
                     // Si au moins une frame est disponible
                     let ring_buffer_manager = RingBufferManager::new(ring_buffer, write_idx);
+                    let input_manager = RingBufferManager::new(
+                        &buffers.audio.input_ring_buffer,
+                        &buffers.audio.input_write_idx,
+                    );
+                    processor.capture_audio_input(
+                        sample_count_frames,
+                        &input_manager,
+                        &buffers.audio.input_read_idx,
+                    );
                     processor
                         .process_and_fill_audio_buffer(sample_count_frames, &ring_buffer_manager);
+                    publish_gain_reductions(&buffers.fx);
                 }
+
+                processor
+                    .event_handler
+                    .finish_completed_upload(&buffers.sample_upload);
+                rebuild_impulse_response();
             }
         });
 
@@ -189,5 +308,29 @@ fn update_input_event_buffers(event_handler: &mut EventHandler, buffers: &Shared
 
     event_handler.process_fx_events(&buffers.fx);
 
-    event_handler.process_sample_event(&buffers.sample_event);
+    event_handler.process_sample_uploads(&buffers.sample_upload);
+}
+
+/// Reconstruit au plus une IR de convolution par tour : le découpage et les FFT se font
+/// hors du verrou du mixer, l'effet garde l'ancienne IR d'ici là
+fn rebuild_impulse_response() {
+    let Some((id, request)) = MIXER.with(|m| m.lock().unwrap().take_impulse_request()) else {
+        return;
+    };
+    let response = request.build();
+    MIXER.with(|m| {
+        m.lock()
+            .unwrap()
+            .install_impulse(id, request.generation, response)
+    });
+}
+
+fn publish_gain_reductions(fx_buffers: &FxBuffers) {
+    MIXER.with(|m| {
+        let master = m
+            .lock()
+            .unwrap()
+            .take_gain_reductions(|id, reduction| fx_buffers.write_gain_reduction(id, reduction));
+        fx_buffers.write_master_gain_reduction(master);
+    });
 }
@@ -9,8 +9,8 @@ use crate::sound_engine::processor::AudioProcessor;
 use crate::sound_engine::synthetizer::sample_manager::SampleManager;
 
 thread_local! {
-    pub static SHARED_BUFFERS: OnceCell<SharedBuffers> = OnceCell::new();
-    pub static AUDIO_PROCESSOR: RefCell<Option<AudioProcessor>> = RefCell::new(None);
+    pub static SHARED_BUFFERS: OnceCell<SharedBuffers> = const { OnceCell::new() };
+    pub static AUDIO_PROCESSOR: RefCell<Option<AudioProcessor>> = const { RefCell::new(None) };
 
     pub static MIXER: Lazy<Mutex<Mixer>> = Lazy::new(|| {
         Mutex::new(Mixer::new())
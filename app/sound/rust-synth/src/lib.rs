@@ -1,10 +1,9 @@
 mod api;
 mod global;
 mod shared_memory;
-mod sound_engine;
-mod utils;
+pub mod sound_engine;
+pub mod utils;
 
-use console_error_panic_hook;
 use wasm_bindgen::prelude::wasm_bindgen;
 
 #[wasm_bindgen]
@@ -42,4 +42,27 @@ impl<'a> RingBufferManager<'a> {
 
         Atomics::store(self.write_idx_atomic, 0, current_write_idx).unwrap();
     }
+
+    /// Lit les samples écrits par le JS (entrée audio) en avançant `read_idx_atomic`,
+    /// complète par du silence si le JS n'en a pas fourni assez
+    pub fn read_samples(&self, read_idx_atomic: &Int32Array, samples: &mut [f32]) {
+        let write_idx = Atomics::load(self.write_idx_atomic, 0).unwrap();
+        let read_idx = Atomics::load(read_idx_atomic, 0).unwrap();
+        let available = ((write_idx - read_idx + self.buffer_size) % self.buffer_size) as usize;
+        let count = available.min(samples.len());
+        let contiguous = count.min((self.buffer_size - read_idx) as usize);
+
+        self.ring_buffer
+            .subarray(read_idx as u32, read_idx as u32 + contiguous as u32)
+            .copy_to(&mut samples[..contiguous]);
+        if count > contiguous {
+            self.ring_buffer
+                .subarray(0, (count - contiguous) as u32)
+                .copy_to(&mut samples[contiguous..count]);
+        }
+        samples[count..].fill(0.0);
+
+        let next_read_idx = (read_idx + count as i32) % self.buffer_size;
+        Atomics::store(read_idx_atomic, 0, next_read_idx).unwrap();
+    }
 }
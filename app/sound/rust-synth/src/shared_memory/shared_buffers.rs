@@ -1,8 +1,13 @@
 use js_sys::{Atomics, Float32Array, Int32Array, Uint8Array};
 
 use crate::utils::{
-    constants::{FX_QUEUE_CAPACITY, MIDI_EVENT_SIZE, MIDI_QUEUE_CAPACITY},
-    types::NoteDTO,
+    constants::{
+        FX_EVENT_SIZE_FLOAT, FX_EVENT_SIZE_INT, FX_METER_CAPACITY, FX_METER_SIZE,
+        FX_QUEUE_CAPACITY, MIDI_EVENT_SIZE, MIDI_QUEUE_CAPACITY, SAMPLE_TRANSFER_SLOT_SIZE,
+        SAMPLE_UPLOAD_EVENT_SIZE, SAMPLE_UPLOAD_QUEUE_CAPACITY, SAMPLE_UPLOAD_STATUS_CAPACITY,
+        SAMPLE_UPLOAD_STATUS_SIZE,
+    },
+    types::{EventType, MidiEvent, NoteDTO, SampleUploadChunk, UploadStatus},
 };
 
 pub struct AudioBuffers {
@@ -10,6 +15,10 @@ pub struct AudioBuffers {
     pub read_idx: Int32Array,
     pub write_idx: Int32Array,
     pub ring_buffer: Float32Array,
+    // entrée audio écrite par l'AudioWorklet, même taille que la sortie
+    pub input_write_idx: Int32Array,
+    pub input_read_idx: Int32Array,
+    pub input_ring_buffer: Float32Array,
 }
 
 pub struct MidiBuffers {
@@ -19,7 +28,7 @@ pub struct MidiBuffers {
 }
 
 impl MidiBuffers {
-    pub fn dequeue_event(&self) -> Option<NoteDTO> {
+    pub fn dequeue_event(&self) -> Option<MidiEvent> {
         let read_pos = Atomics::load(&self.read_idx, 0).unwrap() as u32;
         let write_pos = Atomics::load(&self.write_idx, 0).unwrap() as u32;
 
@@ -28,22 +37,29 @@ impl MidiBuffers {
         }
 
         let event_offset = read_pos * MIDI_EVENT_SIZE;
-        let _event_type = self.queue.get_index(event_offset);
-        let note_value = self.queue.get_index(event_offset + 1);
-        let velocity = self.queue.get_index(event_offset + 2);
+        let event_type = self.queue.get_index(event_offset);
+        let data1 = self.queue.get_index(event_offset + 1);
+        let data2 = self.queue.get_index(event_offset + 2);
 
         let new_read_pos = (read_pos + 1) % MIDI_QUEUE_CAPACITY;
         Atomics::store(&self.read_idx, 0, new_read_pos as i32).unwrap();
 
-        Some(NoteDTO {
-            value: note_value,
-            velocity,
+        Some(match EventType::try_from(event_type) {
+            Ok(EventType::ControlChange) => MidiEvent::ControlChange {
+                controller: data1,
+                value: data2,
+            },
+            Ok(EventType::ProgramChange) => MidiEvent::ProgramChange(data1),
+            _ => MidiEvent::Note(NoteDTO {
+                value: data1,
+                velocity: data2,
+            }),
         })
     }
 
     pub fn process_all_events<F>(&self, mut handler: F) -> u32
     where
-        F: FnMut(&NoteDTO),
+        F: FnMut(&MidiEvent),
     {
         let mut events_processed = 0;
 
@@ -59,8 +75,11 @@ impl MidiBuffers {
 pub struct FxBuffers {
     pub write_idx: Int32Array,
     pub read_idx: Int32Array,
-    pub queue_int: Int32Array,     // fx_id, param_index, event_type
-    pub queue_float: Float32Array, // value
+    pub queue_int: Int32Array,       // fx_id, param_index, event_type
+    pub queue_float: Float32Array,   // value
+    pub meters: Int32Array,          // réduction de gain du master puis des effets
+    pub response_header: Int32Array, // séquence, fx_id
+    pub response: Float32Array,
 }
 
 pub struct SamplerBuffers {
@@ -69,13 +88,22 @@ pub struct SamplerBuffers {
     pub queue: Uint8Array, // 8 octets par événement
 }
 
+pub struct SampleUploadBuffers {
+    pub write_idx: Int32Array,
+    pub read_idx: Int32Array,
+    pub slot_states: Int32Array, // 0 = libre, 1 = rempli par le JS
+    pub queue: Int32Array,
+    pub status: Int32Array, // upload_id, statut, longueur reçue
+    pub transfer: Float32Array,
+    pub transfer_bytes: Uint8Array, // même mémoire, pour les fichiers bruts
+}
+
 pub struct SharedBuffers {
     pub audio: AudioBuffers,
     pub midi: MidiBuffers,
     pub osc: SamplerBuffers,
     pub fx: FxBuffers,
-    pub sample_event: Int32Array,
-    pub sample_buffer: Float32Array,
+    pub sample_upload: SampleUploadBuffers,
 }
 
 pub struct FxEventDto {
@@ -94,8 +122,8 @@ impl FxBuffers {
             return None;
         }
 
-        let int_offset = read_pos * 3; // 3 int par événement
-        let float_offset = read_pos; // 1 float par événement
+        let int_offset = read_pos * FX_EVENT_SIZE_INT;
+        let float_offset = read_pos * FX_EVENT_SIZE_FLOAT;
 
         let fx_id = self.queue_int.get_index(int_offset) as u32;
         let event_type = self.queue_int.get_index(int_offset + 1) as u32;
@@ -114,6 +142,26 @@ impl FxBuffers {
         })
     }
 
+    pub fn write_master_gain_reduction(&self, reduction_db: f32) {
+        Atomics::store(&self.meters, 0, (reduction_db * 1000.0) as i32).unwrap();
+    }
+
+    pub fn write_gain_reduction(&self, fx_id: usize, reduction_db: f32) {
+        let offset = 1 + (fx_id as u32 % FX_METER_CAPACITY) * FX_METER_SIZE;
+        Atomics::store(&self.meters, offset, fx_id as i32).unwrap();
+        Atomics::store(&self.meters, offset + 1, (reduction_db * 1000.0) as i32).unwrap();
+    }
+
+    /// Publie une réponse en amplitude puis réveille le JS qui l'attend
+    pub fn write_response(&self, fx_id: u32, magnitudes: &[f32]) {
+        self.response
+            .subarray(0, magnitudes.len() as u32)
+            .copy_from(magnitudes);
+        Atomics::store(&self.response_header, 1, fx_id as i32).unwrap();
+        Atomics::add(&self.response_header, 0, 1).unwrap();
+        Atomics::notify(&self.response_header, 0).unwrap();
+    }
+
     pub fn process_all_events<F>(&self, mut handler: F) -> u32
     where
         F: FnMut(&FxEventDto),
@@ -128,3 +176,77 @@ impl FxBuffers {
         events_processed
     }
 }
+
+impl SampleUploadBuffers {
+    pub fn dequeue_event(&self) -> Option<SampleUploadChunk> {
+        let read_pos = Atomics::load(&self.read_idx, 0).unwrap() as u32;
+        let write_pos = Atomics::load(&self.write_idx, 0).unwrap() as u32;
+
+        if read_pos == write_pos {
+            return None;
+        }
+
+        let offset = read_pos * SAMPLE_UPLOAD_EVENT_SIZE;
+        let chunk = SampleUploadChunk {
+            upload_id: self.queue.get_index(offset) as u32,
+            sampler_id: self.queue.get_index(offset + 1) as u32,
+            sample_id: self.queue.get_index(offset + 2) as u32,
+            total_length: self.queue.get_index(offset + 3) as u32,
+            chunk_offset: self.queue.get_index(offset + 4) as u32,
+            chunk_length: self.queue.get_index(offset + 5) as u32,
+            slot: self.queue.get_index(offset + 6),
+            channels: self.queue.get_index(offset + 7) as u8,
+            hq: self.queue.get_index(offset + 8) as u8,
+            format: self.queue.get_index(offset + 9) as u8,
+            interpolation: self.queue.get_index(offset + 10) as u8,
+        };
+
+        let new_read_pos = (read_pos + 1) % SAMPLE_UPLOAD_QUEUE_CAPACITY;
+        Atomics::store(&self.read_idx, 0, new_read_pos as i32).unwrap();
+
+        Some(chunk)
+    }
+
+    pub fn process_all_events<F>(&self, mut handler: F) -> u32
+    where
+        F: FnMut(&SampleUploadChunk),
+    {
+        let mut events_processed = 0;
+
+        while let Some(chunk) = self.dequeue_event() {
+            events_processed += 1;
+            handler(&chunk);
+        }
+
+        events_processed
+    }
+
+    /// Copie le contenu d'un slot de transfert dans `destination`
+    pub fn copy_slot(&self, slot: u32, destination: &mut [f32]) {
+        let start = slot * SAMPLE_TRANSFER_SLOT_SIZE;
+        self.transfer
+            .subarray(start, start + destination.len() as u32)
+            .copy_to(destination);
+    }
+
+    /// Copie les octets d'un slot de transfert dans `destination`
+    pub fn copy_slot_bytes(&self, slot: u32, destination: &mut [u8]) {
+        let start = slot * SAMPLE_TRANSFER_SLOT_SIZE * 4;
+        self.transfer_bytes
+            .subarray(start, start + destination.len() as u32)
+            .copy_to(destination);
+    }
+
+    /// Rend le slot au JS pour le chunk suivant
+    pub fn release_slot(&self, slot: u32) {
+        Atomics::store(&self.slot_states, slot, 0).unwrap();
+        Atomics::notify(&self.slot_states, slot).unwrap();
+    }
+
+    pub fn write_status(&self, upload_id: u32, status: UploadStatus, received: u32) {
+        let offset = (upload_id % SAMPLE_UPLOAD_STATUS_CAPACITY) * SAMPLE_UPLOAD_STATUS_SIZE;
+        Atomics::store(&self.status, offset, upload_id as i32).unwrap();
+        Atomics::store(&self.status, offset + 2, received as i32).unwrap();
+        Atomics::store(&self.status, offset + 1, status as i32).unwrap();
+    }
+}
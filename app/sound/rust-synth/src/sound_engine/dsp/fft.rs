@@ -0,0 +1,172 @@
+use std::{
+    f32::consts::PI,
+    ops::{Add, AddAssign, Mul, Sub},
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Complex {
+    pub re: f32,
+    pub im: f32,
+}
+
+impl Complex {
+    pub const ZERO: Self = Self { re: 0.0, im: 0.0 };
+
+    pub fn new(re: f32, im: f32) -> Self {
+        Self { re, im }
+    }
+
+    pub fn conj(self) -> Self {
+        Self::new(self.re, -self.im)
+    }
+
+    pub fn norm(self) -> f32 {
+        self.re.hypot(self.im)
+    }
+
+    pub fn scale(self, factor: f32) -> Self {
+        Self::new(self.re * factor, self.im * factor)
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl AddAssign for Complex {
+    fn add_assign(&mut self, other: Self) {
+        self.re += other.re;
+        self.im += other.im;
+    }
+}
+
+impl Sub for Complex {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+/// FFT complexe radix-2 en place, twiddles et permutation précalculés pour une taille
+/// (puissance de deux). Non normalisée : `inverse(forward(x)) = size * x`.
+pub struct Fft {
+    size: usize,
+    twiddles: Vec<Complex>,
+    reversed: Vec<usize>,
+}
+
+impl Fft {
+    pub fn new(size: usize) -> Self {
+        assert!(
+            size >= 2 && size.is_power_of_two(),
+            "taille de FFT non puissance de deux"
+        );
+        let bits = size.trailing_zeros();
+        let twiddles = (0..size / 2)
+            .map(|k| {
+                let angle = -2.0 * PI * k as f32 / size as f32;
+                Complex::new(angle.cos(), angle.sin())
+            })
+            .collect();
+        let reversed = (0..size)
+            .map(|i| (i.reverse_bits() >> (usize::BITS - bits)) & (size - 1))
+            .collect();
+        Self {
+            size,
+            twiddles,
+            reversed,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn forward(&self, buffer: &mut [Complex]) {
+        self.transform(buffer, false);
+    }
+
+    pub fn inverse(&self, buffer: &mut [Complex]) {
+        self.transform(buffer, true);
+    }
+
+    fn transform(&self, buffer: &mut [Complex], inverse: bool) {
+        debug_assert_eq!(buffer.len(), self.size);
+        for (i, &j) in self.reversed.iter().enumerate() {
+            if i < j {
+                buffer.swap(i, j);
+            }
+        }
+
+        let mut half = 1;
+        while half < self.size {
+            let stride = self.size / (2 * half);
+            for start in (0..self.size).step_by(2 * half) {
+                for k in 0..half {
+                    let twiddle = self.twiddles[k * stride];
+                    let twiddle = if inverse { twiddle.conj() } else { twiddle };
+                    let odd = buffer[start + k + half] * twiddle;
+                    let even = buffer[start + k];
+                    buffer[start + k] = even + odd;
+                    buffer[start + k + half] = even - odd;
+                }
+            }
+            half *= 2;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverse_undoes_forward_up_to_size() {
+        let size = 256;
+        let fft = Fft::new(size);
+        let input: Vec<Complex> = (0..size)
+            .map(|i| Complex::new((i as f32 * 0.37).sin(), (i as f32 * 1.3).cos() * 0.5))
+            .collect();
+        let mut buffer = input.clone();
+        fft.forward(&mut buffer);
+        fft.inverse(&mut buffer);
+        for (value, expected) in buffer.iter().zip(&input) {
+            let value = value.scale(1.0 / size as f32);
+            assert!((value - *expected).norm() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn forward_matches_the_dft() {
+        let size = 16;
+        let fft = Fft::new(size);
+        let input: Vec<Complex> = (0..size)
+            .map(|i| Complex::new(i as f32 % 3.0 - 1.0, (i * i) as f32 % 5.0 * 0.1))
+            .collect();
+        let mut buffer = input.clone();
+        fft.forward(&mut buffer);
+        for (k, value) in buffer.iter().enumerate() {
+            let expected = input
+                .iter()
+                .enumerate()
+                .fold(Complex::ZERO, |sum, (n, &x)| {
+                    let angle = -2.0 * PI * (k * n) as f32 / size as f32;
+                    sum + x * Complex::new(angle.cos(), angle.sin())
+                });
+            assert!((*value - expected).norm() < 1e-4, "case {}", k);
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use std::{
+    any::Any,
+    f32::consts::{FRAC_PI_4, SQRT_2},
+};
+
+use crate::sound_engine::dsp::fx::{
+    EffectTrait, Lfo, LfoWaveform, NoteDivision, NoteModifier, TempoSync,
+    params::{LFO_WAVEFORMS, NOTE_DIVISIONS, NOTE_MODIFIERS, ParamInfo},
+};
+
+const MAX_RING_FREQUENCY: f32 = 5000.0;
+
+const WAVEFORM_PARAM: ParamInfo = ParamInfo::choice("Waveform", LFO_WAVEFORMS, 0);
+const PHASE_OFFSET_PARAM: ParamInfo = ParamInfo::new("Phase offset", "", 0.0, 1.0, 0.0);
+const DIVISION_PARAM: ParamInfo = ParamInfo::choice("Division", NOTE_DIVISIONS, 0);
+const MODIFIER_PARAM: ParamInfo = ParamInfo::choice("Modifier", NOTE_MODIFIERS, 0);
+
+/// LFO dont la fréquence libre peut être remplacée par une valeur de note au tempo
+pub struct SyncedLfo {
+    pub lfo: Lfo,
+    pub rate: f32,         // Hz, hors synchro
+    pub phase_offset: f32, // déphasage du canal droit, 0 à 1
+    pub sync: TempoSync,
+    tempo: f32,
+}
+
+impl SyncedLfo {
+    fn new(rate: f32, phase_offset: f32) -> Self {
+        Self {
+            lfo: Lfo::new(rate),
+            rate,
+            phase_offset,
+            sync: TempoSync::new(),
+            tempo: 120.0,
+        }
+    }
+
+    pub fn set_waveform(&mut self, waveform: f32) {
+        if let Ok(waveform) = LfoWaveform::try_from(waveform as u32) {
+            self.lfo.waveform = waveform;
+        }
+    }
+
+    pub fn set_division(&mut self, division: f32) {
+        if let Ok(division) = NoteDivision::try_from(division as u32) {
+            self.sync.division = division;
+        }
+    }
+
+    pub fn set_modifier(&mut self, modifier: f32) {
+        if let Ok(modifier) = NoteModifier::try_from(modifier as u32) {
+            self.sync.modifier = modifier;
+        }
+    }
+
+    /// Paramètres communs aux effets à LFO : 2 forme, 3 déphasage, 4 division, 5 modificateur
+    fn set_param(&mut self, index: u32, value: f32) -> bool {
+        match index {
+            2 => self.set_waveform(value),
+            3 => self.phase_offset = value,
+            4 => self.set_division(value),
+            5 => self.set_modifier(value),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Valeurs (-1 à 1) des canaux gauche et droit, puis avance d'un sample
+    fn next(&mut self) -> [f32; 2] {
+        let values = [self.lfo.value(0.0), self.lfo.value(self.phase_offset)];
+        self.lfo.rate = self
+            .sync
+            .seconds(self.tempo)
+            .map_or(self.rate, |seconds| 1.0 / seconds);
+        self.lfo.advance();
+        values
+    }
+}
+
+/// Tremolo : le gain oscille entre 1 et 1 - depth
+pub struct Tremolo {
+    id: usize,
+    pub modulator: SyncedLfo,
+    pub depth: f32, // 0 à 1
+}
+
+impl Tremolo {
+    pub fn new(id: usize) -> Self {
+        Self {
+            id,
+            modulator: SyncedLfo::new(5.0, 0.0),
+            depth: 0.5,
+        }
+    }
+}
+
+pub static TREMOLO_PARAMS: [ParamInfo; 6] = [
+    ParamInfo::new("Rate", "Hz", 0.01, 40.0, 5.0).logarithmic(),
+    ParamInfo::new("Depth", "", 0.0, 1.0, 0.5),
+    WAVEFORM_PARAM,
+    PHASE_OFFSET_PARAM,
+    DIVISION_PARAM,
+    MODIFIER_PARAM,
+];
+
+impl EffectTrait for Tremolo {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn params(&self) -> &'static [ParamInfo] {
+        &TREMOLO_PARAMS
+    }
+
+    fn set_param(&mut self, index: u32, value: f32) -> bool {
+        match index {
+            0 => self.modulator.rate = value,
+            1 => self.depth = value,
+            _ => return self.modulator.set_param(index, value),
+        }
+        true
+    }
+
+    fn process(&mut self, sample_l: &mut f32, sample_r: &mut f32) {
+        let values = self.modulator.next();
+        for (sample, value) in [sample_l, sample_r].into_iter().zip(values) {
+            *sample *= 1.0 - self.depth * 0.5 * (1.0 - value);
+        }
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn set_tempo(&mut self, bpm: f32) {
+        self.modulator.tempo = bpm;
+    }
+}
+
+/// Auto-pan à puissance constante : gain unitaire au centre, le déphasage du canal
+/// droit à 0 donne un panoramique classique, à 0.5 un tremolo en opposition
+pub struct AutoPan {
+    id: usize,
+    pub modulator: SyncedLfo,
+    pub depth: f32, // 0 à 1, amplitude du balayage
+}
+
+impl AutoPan {
+    pub fn new(id: usize) -> Self {
+        Self {
+            id,
+            modulator: SyncedLfo::new(0.5, 0.0),
+            depth: 1.0,
+        }
+    }
+}
+
+pub static AUTO_PAN_PARAMS: [ParamInfo; 6] = [
+    ParamInfo::new("Rate", "Hz", 0.01, 40.0, 0.5).logarithmic(),
+    ParamInfo::new("Depth", "", 0.0, 1.0, 1.0),
+    WAVEFORM_PARAM,
+    PHASE_OFFSET_PARAM,
+    DIVISION_PARAM,
+    MODIFIER_PARAM,
+];
+
+impl EffectTrait for AutoPan {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn params(&self) -> &'static [ParamInfo] {
+        &AUTO_PAN_PARAMS
+    }
+
+    fn set_param(&mut self, index: u32, value: f32) -> bool {
+        match index {
+            0 => self.modulator.rate = value,
+            1 => self.depth = value,
+            _ => return self.modulator.set_param(index, value),
+        }
+        true
+    }
+
+    fn process(&mut self, sample_l: &mut f32, sample_r: &mut f32) {
+        let [pan_l, pan_r] = self.modulator.next().map(|value| value * self.depth);
+        // position -1 (gauche) à 1 (droite) -> angle 0 à π/2
+        *sample_l *= ((pan_l + 1.0) * FRAC_PI_4).cos() * SQRT_2;
+        *sample_r *= ((pan_r + 1.0) * FRAC_PI_4).sin() * SQRT_2;
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn set_tempo(&mut self, bpm: f32) {
+        self.modulator.tempo = bpm;
+    }
+}
+
+/// Modulateur en anneau : multiplication par une porteuse, `depth` à 1 supprime le signal
+/// d'origine pour ne garder que les fréquences somme et différence
+pub struct RingModulator {
+    id: usize,
+    pub modulator: SyncedLfo,
+    pub depth: f32, // 0 à 1
+}
+
+impl RingModulator {
+    pub fn new(id: usize) -> Self {
+        Self {
+            id,
+            modulator: SyncedLfo::new(440.0, 0.0),
+            depth: 1.0,
+        }
+    }
+
+    pub fn set_frequency(&mut self, frequency: f32) {
+        self.modulator.rate = frequency.clamp(0.1, MAX_RING_FREQUENCY);
+    }
+}
+
+pub static RING_MODULATOR_PARAMS: [ParamInfo; 6] = [
+    ParamInfo::new("Frequency", "Hz", 0.1, MAX_RING_FREQUENCY, 440.0).logarithmic(),
+    ParamInfo::new("Depth", "", 0.0, 1.0, 1.0),
+    WAVEFORM_PARAM,
+    PHASE_OFFSET_PARAM,
+    DIVISION_PARAM,
+    MODIFIER_PARAM,
+];
+
+impl EffectTrait for RingModulator {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn params(&self) -> &'static [ParamInfo] {
+        &RING_MODULATOR_PARAMS
+    }
+
+    fn set_param(&mut self, index: u32, value: f32) -> bool {
+        match index {
+            0 => self.set_frequency(value),
+            1 => self.depth = value,
+            _ => return self.modulator.set_param(index, value),
+        }
+        true
+    }
+
+    fn process(&mut self, sample_l: &mut f32, sample_r: &mut f32) {
+        let values = self.modulator.next();
+        for (sample, carrier) in [sample_l, sample_r].into_iter().zip(values) {
+            *sample *= 1.0 - self.depth + self.depth * carrier;
+        }
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn set_tempo(&mut self, bpm: f32) {
+        self.modulator.tempo = bpm;
+    }
+}
@@ -0,0 +1,545 @@
+use std::any::Any;
+
+use crate::{
+    sound_engine::dsp::{
+        fft::{Complex, Fft},
+        fx::{EffectTrait, MemoryBuffer, params::ParamInfo},
+    },
+    utils::{
+        constants::SAMPLE_RATE,
+        types::{Mix, Sample},
+    },
+};
+
+// partitions de tête au quantum de rendu de l'AudioWorkletProcessor : c'est la latence de l'effet
+const HEAD_BLOCK: usize = 128;
+// la queue de l'IR est traitée par grandes partitions, bien moins coûteuses
+const TAIL_BLOCK: usize = 2048;
+const MAX_IR_SECONDS: f32 = 10.0;
+const MAX_PRE_DELAY_MS: f32 = 500.0;
+const FADE_OUT_MS: f32 = 5.0; // fondu en fin d'IR raccourcie
+// début et longueur : l'IR n'est reconstruite qu'une fois le réglage stable
+const REBUILD_DELAY_MS: f32 = 100.0;
+
+fn ms_to_samples(ms: f32) -> usize {
+    (ms / 1000.0 * SAMPLE_RATE) as usize
+}
+
+/// Convolueur à partitions uniformes (overlap-save) et ligne à retard fréquentielle.
+/// Les deux canaux sont empaquetés dans une seule FFT complexe (gauche en partie réelle,
+/// droite en imaginaire) : comme Xl = (Z + Z')/2 et i·Xr = (Z - Z')/2 avec Z' = conj(Z[N-k]),
+/// la sortie vaut Z·(Hl + Hr)/2 + Z'·(Hl - Hr)/2, le second terme disparaît pour une IR mono.
+/// Le calcul des partitions anciennes est étalé sur le bloc suivant pour éviter les pics.
+struct Partitioned {
+    block: usize,
+    fft: Fft,
+    sum: Vec<Vec<Complex>>,
+    difference: Option<Vec<Vec<Complex>>>,
+    spectra: Vec<Vec<Complex>>, // spectres d'entrée, le plus récent à `position`
+    position: usize,
+    input: Vec<Complex>, // bloc précédent puis bloc en cours
+    output: Vec<Complex>,
+    accumulator: Vec<Complex>,
+    cursor: usize, // produits (partition, case) déjà accumulés pour le prochain bloc
+    fill: usize,
+}
+
+impl Partitioned {
+    fn new(left: &[f32], right: Option<&[f32]>, block: usize) -> Self {
+        let size = 2 * block;
+        let fft = Fft::new(size);
+        let count = left.len().div_ceil(block).max(1);
+        let scale = 0.5 / size as f32; // normalisation de l'IFFT comprise
+
+        let spectrum = |ir: &[f32], partition: usize| {
+            let mut buffer = vec![Complex::ZERO; size];
+            let start = (partition * block).min(ir.len());
+            let end = (start + block).min(ir.len());
+            for (value, &sample) in buffer.iter_mut().zip(&ir[start..end]) {
+                value.re = sample;
+            }
+            fft.forward(&mut buffer);
+            buffer
+        };
+
+        let mut sum = Vec::with_capacity(count);
+        let mut difference = right.map(|_| Vec::with_capacity(count));
+        for partition in 0..count {
+            let spectrum_l = spectrum(left, partition);
+            match (right, difference.as_mut()) {
+                (Some(right), Some(difference)) => {
+                    let spectrum_r = spectrum(right, partition);
+                    let (s, d) = spectrum_l
+                        .iter()
+                        .zip(&spectrum_r)
+                        .map(|(&l, &r)| ((l + r).scale(scale), (l - r).scale(scale)))
+                        .unzip();
+                    sum.push(s);
+                    difference.push(d);
+                }
+                _ => sum.push(spectrum_l.iter().map(|h| h.scale(2.0 * scale)).collect()),
+            }
+        }
+
+        Self {
+            block,
+            fft,
+            sum,
+            difference,
+            spectra: vec![vec![Complex::ZERO; size]; count],
+            position: 0,
+            input: vec![Complex::ZERO; size],
+            output: vec![Complex::ZERO; block],
+            accumulator: vec![Complex::ZERO; size],
+            cursor: 0,
+            fill: 0,
+        }
+    }
+
+    /// Accumule les partitions 1.. du prochain bloc jusqu'au produit `end`
+    fn accumulate(&mut self, end: usize) {
+        let size = 2 * self.block;
+        let count = self.sum.len();
+        let end = end.min((count - 1) * size);
+
+        while self.cursor < end {
+            let partition = 1 + self.cursor / size;
+            let first = self.cursor % size;
+            let last = size.min(first + end - self.cursor);
+            // le prochain spectre prendra la place du plus ancien
+            let spectrum = &self.spectra[(self.position + partition - 1) % count];
+
+            let sum = &self.sum[partition];
+            for k in first..last {
+                self.accumulator[k] += spectrum[k] * sum[k];
+            }
+            if let Some(difference) = &self.difference {
+                let difference = &difference[partition];
+                for k in first..last {
+                    self.accumulator[k] += spectrum[(size - k) % size].conj() * difference[k];
+                }
+            }
+            self.cursor += last - first;
+        }
+    }
+
+    fn process_block(&mut self) {
+        let size = 2 * self.block;
+        let count = self.sum.len();
+        self.accumulate(usize::MAX);
+
+        self.position = (self.position + count - 1) % count;
+        let spectrum = &mut self.spectra[self.position];
+        spectrum.copy_from_slice(&self.input);
+        self.fft.forward(spectrum);
+        self.input.copy_within(self.block.., 0);
+
+        for ((value, &x), &h) in self
+            .accumulator
+            .iter_mut()
+            .zip(spectrum.iter())
+            .zip(&self.sum[0])
+        {
+            *value += x * h;
+        }
+        if let Some(difference) = &self.difference {
+            for k in 0..size {
+                self.accumulator[k] += spectrum[(size - k) % size].conj() * difference[0][k];
+            }
+        }
+
+        self.fft.inverse(&mut self.accumulator);
+        // overlap-save : seule la seconde moitié est une convolution linéaire valide
+        self.output.copy_from_slice(&self.accumulator[self.block..]);
+        self.accumulator.fill(Complex::ZERO);
+        self.cursor = 0;
+    }
+
+    /// Latence d'un bloc : la sortie correspond à l'entrée reçue `block` samples plus tôt
+    fn process(&mut self, input: Complex) -> Complex {
+        self.input[self.block + self.fill] = input;
+        let output = self.output[self.fill];
+        self.fill += 1;
+
+        if self.fill == self.block {
+            self.fill = 0;
+            self.process_block();
+        } else {
+            let work = ((self.sum.len() - 1) * 2 * self.block).div_ceil(self.block);
+            self.accumulate(self.cursor + work);
+        }
+        output
+    }
+}
+
+/// IR découpée et passée en fréquence, prête à remplacer celle d'une `Convolution`.
+/// Partitionnement non uniforme : le début de l'IR est convolué par blocs de `HEAD_BLOCK`,
+/// la suite (à partir de `TAIL_BLOCK - HEAD_BLOCK`) par blocs de `TAIL_BLOCK` dont la
+/// latence propre compense exactement ce décalage.
+pub struct ImpulseResponse {
+    head: Partitioned,
+    tail: Option<Partitioned>,
+}
+
+impl ImpulseResponse {
+    fn process(&mut self, input: Complex) -> Complex {
+        let mut wet = self.head.process(input);
+        if let Some(tail) = self.tail.as_mut() {
+            wet += tail.process(input);
+        }
+        wet
+    }
+}
+
+/// Réglages d'une IR à reconstruire, relevés sous le verrou du mixer ; `build` (FFT,
+/// allocations) se fait ensuite hors du rendu
+pub struct ImpulseRequest {
+    pub generation: u32,
+    sample: Sample,
+    trim_start: f32,
+    length: f32,
+}
+
+impl ImpulseRequest {
+    /// Canaux de l'IR ramenés à `SAMPLE_RATE`, découpés puis normalisés en énergie
+    fn channels(&self) -> Option<(Vec<f32>, Option<Vec<f32>>)> {
+        let sample = &self.sample;
+        let frames = sample.frames();
+        let step = sample.sample_rate / SAMPLE_RATE;
+        let start = ms_to_samples(self.trim_start);
+        let available = ((frames as f32 / step) as usize).saturating_sub(start);
+        let max_length = (MAX_IR_SECONDS * SAMPLE_RATE) as usize;
+        let length = match ms_to_samples(self.length) {
+            0 => available,
+            length => length.min(available),
+        }
+        .min(max_length);
+        if length == 0 {
+            return None;
+        }
+
+        // interpolation linéaire si l'IR n'est pas à la fréquence du moteur
+        let read = |channel: usize| -> Vec<f32> {
+            let values = &sample.values[channel * frames..(channel + 1) * frames];
+            (start..start + length)
+                .map(|i| {
+                    let position = i as f32 * step;
+                    let index = position as usize;
+                    let frac = position - index as f32;
+                    let a = values[index.min(frames - 1)];
+                    let b = values[(index + 1).min(frames - 1)];
+                    a + (b - a) * frac
+                })
+                .collect()
+        };
+        let mut left = read(0);
+        let mut right = (sample.channels == 2).then(|| read(1));
+
+        let truncated = length < available;
+        let fade = ms_to_samples(FADE_OUT_MS).min(length);
+        let energy = |ir: &[f32]| ir.iter().map(|v| v * v).sum::<f32>();
+        let peak_energy = energy(&left).max(right.as_deref().map_or(0.0, energy));
+        let gain = if peak_energy > 0.0 {
+            sample.gain / peak_energy.sqrt()
+        } else {
+            0.0
+        };
+
+        for ir in std::iter::once(&mut left).chain(right.as_mut()) {
+            for (i, value) in ir.iter_mut().enumerate() {
+                let remaining = length - i;
+                let fade_gain = if truncated && remaining < fade {
+                    remaining as f32 / fade as f32
+                } else {
+                    1.0
+                };
+                *value *= gain * fade_gain;
+            }
+        }
+        Some((left, right))
+    }
+
+    /// `None` : IR vide après découpe, le signal traité est coupé
+    pub fn build(&self) -> Option<ImpulseResponse> {
+        let (left, right) = self.channels()?;
+
+        let split = (TAIL_BLOCK - HEAD_BLOCK).min(left.len());
+        let right_head = right.as_deref().map(|r| &r[..split]);
+        let head = Partitioned::new(&left[..split], right_head, HEAD_BLOCK);
+        let tail = (left.len() > split).then(|| {
+            let right_tail = right.as_deref().map(|r| &r[split..]);
+            Partitioned::new(&left[split..], right_tail, TAIL_BLOCK)
+        });
+        Some(ImpulseResponse { head, tail })
+    }
+}
+
+/// Réverbération / simulation de baffle par convolution avec une IR chargée dans le
+/// `SampleManager`. Changer d'IR, de début ou de longueur ne fait que noter les réglages :
+/// l'IR est reconstruite hors du rendu (`take_request`) puis échangée (`install`), l'ancienne
+/// restant jouée d'ici là.
+pub struct Convolution {
+    id: usize,
+    pub trim_start: f32, // ms
+    pub length: f32,     // ms, 0 : jusqu'à la fin de l'IR
+    pub pre_delay: f32,  // ms
+    pub mix: Mix,
+    impulse: Option<Sample>,
+    response: Option<ImpulseResponse>,
+    generation: u32,      // incrémentée à chaque changement de réglage de l'IR
+    built: u32,           // génération de `response`
+    settle: usize,        // samples avant de demander la reconstruction
+    memory: MemoryBuffer, // pré-délai du signal traité, latence du signal direct
+}
+
+impl Convolution {
+    pub fn new(id: usize) -> Self {
+        Self {
+            id,
+            trim_start: 0.0,
+            length: 0.0,
+            pre_delay: 0.0,
+            mix: Mix { dry: 1.0, wet: 0.3 },
+            impulse: None,
+            response: None,
+            generation: 0,
+            built: 0,
+            settle: 0,
+            memory: MemoryBuffer::new(SAMPLE_RATE as usize, MAX_PRE_DELAY_MS / 1000.0 + 0.01),
+        }
+    }
+
+    /// `None` (sample introuvable) coupe le signal traité
+    pub fn set_impulse(&mut self, sample: Option<Sample>) {
+        self.impulse = sample;
+        self.invalidate(0);
+        if self.impulse.is_none() {
+            self.response = None;
+            self.built = self.generation;
+        }
+    }
+
+    pub fn set_trim_start(&mut self, ms: f32) {
+        self.trim_start = ms.max(0.0);
+        self.invalidate(ms_to_samples(REBUILD_DELAY_MS));
+    }
+
+    pub fn set_length(&mut self, ms: f32) {
+        self.length = ms.clamp(0.0, MAX_IR_SECONDS * 1000.0);
+        self.invalidate(ms_to_samples(REBUILD_DELAY_MS));
+    }
+
+    pub fn set_pre_delay(&mut self, ms: f32) {
+        self.pre_delay = ms.clamp(0.0, MAX_PRE_DELAY_MS);
+    }
+
+    fn invalidate(&mut self, settle: usize) {
+        self.generation = self.generation.wrapping_add(1);
+        self.settle = settle;
+    }
+
+    /// Réglages à reconstruire, une fois stables depuis `REBUILD_DELAY_MS`
+    pub fn take_request(&self) -> Option<ImpulseRequest> {
+        if self.built == self.generation || self.settle > 0 {
+            return None;
+        }
+        Some(ImpulseRequest {
+            generation: self.generation,
+            sample: self.impulse.clone()?,
+            trim_start: self.trim_start,
+            length: self.length,
+        })
+    }
+
+    /// Ignoré si les réglages ont changé depuis la demande : une nouvelle suivra
+    pub fn install(&mut self, generation: u32, response: Option<ImpulseResponse>) {
+        if generation == self.generation {
+            self.response = response;
+            self.built = generation;
+        }
+    }
+}
+
+pub static CONVOLUTION_PARAMS: [ParamInfo; 6] = [
+    ParamInfo::sample("Impulse"),
+    ParamInfo::new("Trim start", "ms", 0.0, MAX_IR_SECONDS * 1000.0, 0.0).instant(),
+    // 0 : IR entière
+    ParamInfo::new("Length", "ms", 0.0, MAX_IR_SECONDS * 1000.0, 0.0).instant(),
+    ParamInfo::new("Pre-delay", "ms", 0.0, MAX_PRE_DELAY_MS, 0.0),
+    ParamInfo::dry(1.0),
+    ParamInfo::wet(0.3),
+];
+
+impl EffectTrait for Convolution {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn params(&self) -> &'static [ParamInfo] {
+        &CONVOLUTION_PARAMS
+    }
+
+    fn set_param(&mut self, index: u32, value: f32) -> bool {
+        match index {
+            // l'IR (index 0) passe par `set_sample`
+            1 => self.set_trim_start(value),
+            2 => self.set_length(value),
+            3 => self.set_pre_delay(value),
+            4 => self.mix.dry = value,
+            5 => self.mix.wet = value,
+            _ => return false,
+        }
+        true
+    }
+
+    fn set_sample(&mut self, index: u32, sample: Option<Sample>) -> bool {
+        if index != 0 {
+            return false;
+        }
+        self.set_impulse(sample);
+        true
+    }
+
+    fn process(&mut self, sample_l: &mut f32, sample_r: &mut f32) {
+        self.settle = self.settle.saturating_sub(1);
+        self.memory.write(*sample_l, *sample_r);
+        // le signal direct est retardé d'autant que la convolution pour rester aligné
+        let dry_l = self.memory.read_left(HEAD_BLOCK + 1);
+        let dry_r = self.memory.read_right(HEAD_BLOCK + 1);
+        let pre_delay = ms_to_samples(self.pre_delay) + 1;
+        let input = Complex::new(
+            self.memory.read_left(pre_delay),
+            self.memory.read_right(pre_delay),
+        );
+
+        let wet = self
+            .response
+            .as_mut()
+            .map_or(Complex::ZERO, |response| response.process(input));
+
+        *sample_l = self.mix.dry * dry_l + self.mix.wet * wet.re;
+        *sample_r = self.mix.dry * dry_r + self.mix.wet * wet.im;
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bruit blanc reproductible entre -1 et 1
+    fn noise(length: usize, mut seed: u32) -> Vec<f32> {
+        (0..length)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                seed as f32 / u32::MAX as f32 * 2.0 - 1.0
+            })
+            .collect()
+    }
+
+    fn request(values: Vec<f32>, channels: u8, sample_rate: f32) -> ImpulseRequest {
+        let mut sample = Sample::new(1, values, channels, 0);
+        sample.sample_rate = sample_rate;
+        ImpulseRequest {
+            generation: 0,
+            sample,
+            trim_start: 0.0,
+            length: 0.0,
+        }
+    }
+
+    /// Compare la convolution partitionnée à la convolution directe, retardée de `HEAD_BLOCK`
+    fn check_against_direct(request: &ImpulseRequest, input_l: &[f32], input_r: &[f32]) {
+        let (ir_l, ir_r) = request.channels().unwrap();
+        let ir_r = ir_r.unwrap_or_else(|| ir_l.clone());
+        let mut response = request.build().unwrap();
+
+        let direct = |input: &[f32], ir: &[f32], n: usize| -> f32 {
+            (0..=n.min(ir.len() - 1))
+                .map(|k| ir[k] * input[n - k])
+                .sum()
+        };
+        for n in 0..input_l.len() {
+            let output = response.process(Complex::new(input_l[n], input_r[n]));
+            let (expected_l, expected_r) = match n.checked_sub(HEAD_BLOCK) {
+                Some(m) => (direct(input_l, &ir_l, m), direct(input_r, &ir_r, m)),
+                None => (0.0, 0.0),
+            };
+            assert!(
+                (output.re - expected_l).abs() < 1e-3,
+                "gauche, sample {}",
+                n
+            );
+            assert!(
+                (output.im - expected_r).abs() < 1e-3,
+                "droite, sample {}",
+                n
+            );
+        }
+    }
+
+    #[test]
+    fn impulse_returns_the_ir_after_one_head_block() {
+        let request = request(noise(TAIL_BLOCK + 700, 1), 1, SAMPLE_RATE);
+        let (ir, _) = request.channels().unwrap();
+        let mut response = request.build().unwrap();
+        assert!(response.tail.is_some());
+
+        for n in 0..ir.len() + HEAD_BLOCK + 10 {
+            let input = if n == 0 { 1.0 } else { 0.0 };
+            let output = response.process(Complex::new(input, input));
+            let expected = n
+                .checked_sub(HEAD_BLOCK)
+                .and_then(|m| ir.get(m))
+                .copied()
+                .unwrap_or(0.0);
+            assert!((output.re - expected).abs() < 1e-4, "sample {}", n);
+            assert!((output.im - expected).abs() < 1e-4, "sample {}", n);
+        }
+    }
+
+    #[test]
+    fn mono_ir_matches_direct_convolution() {
+        let request = request(noise(TAIL_BLOCK + 900, 2), 1, SAMPLE_RATE);
+        let length = 3 * TAIL_BLOCK;
+        check_against_direct(&request, &noise(length, 3), &noise(length, 4));
+    }
+
+    #[test]
+    fn stereo_ir_matches_direct_convolution() {
+        let frames = TAIL_BLOCK + 500;
+        let mut values = noise(frames, 5);
+        values.extend(noise(frames, 6).iter().map(|v| v * 0.5));
+        let request = request(values, 2, SAMPLE_RATE);
+        let length = 3 * TAIL_BLOCK;
+        check_against_direct(&request, &noise(length, 7), &noise(length, 8));
+    }
+
+    #[test]
+    fn ir_at_another_rate_is_resampled() {
+        let original = noise(300, 9);
+        let request = request(original.clone(), 1, SAMPLE_RATE / 2.0);
+        let (ir, right) = request.channels().unwrap();
+        assert!(right.is_none());
+        assert_eq!(ir.len(), 2 * original.len());
+
+        // normalisation en énergie : même rapport entre toutes les valeurs
+        let gain = ir[0] / original[0];
+        for (i, value) in ir.iter().enumerate().take(2 * original.len() - 2) {
+            let expected = if i % 2 == 0 {
+                original[i / 2]
+            } else {
+                0.5 * (original[i / 2] + original[i / 2 + 1])
+            };
+            assert!((value - expected * gain).abs() < 1e-5, "sample {}", i);
+        }
+
+        let length = TAIL_BLOCK + 200;
+        check_against_direct(&request, &noise(length, 10), &noise(length, 11));
+    }
+}
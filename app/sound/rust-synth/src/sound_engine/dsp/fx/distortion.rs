@@ -0,0 +1,254 @@
+use std::any::Any;
+
+use crate::{
+    sound_engine::dsp::fx::{BiquadCoeffs, BiquadState, EffectTrait, params::ParamInfo},
+    utils::{constants::SAMPLE_RATE, types::Mix},
+};
+
+// Butterworth d'ordre 8 : quatre sections aux Q complémentaires
+const BUTTERWORTH_Q: [f32; 4] = [0.5098, 0.6013, 0.9000, 2.5629];
+const MAX_OVERSAMPLING: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DistortionCurve {
+    SoftClip = 0, // tanh
+    HardClip = 1,
+    Foldback = 2,
+    Tube = 3, // asymétrique : harmoniques paires
+    Bitcrush = 4,
+}
+
+impl TryFrom<u32> for DistortionCurve {
+    type Error = ();
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(DistortionCurve::SoftClip),
+            1 => Ok(DistortionCurve::HardClip),
+            2 => Ok(DistortionCurve::Foldback),
+            3 => Ok(DistortionCurve::Tube),
+            4 => Ok(DistortionCurve::Bitcrush),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Filtres d'un canal : anti-imaging avant la courbe, anti-aliasing après
+#[derive(Default, Clone, Copy)]
+struct OversamplingState {
+    up: [BiquadState; BUTTERWORTH_Q.len()],
+    down: [BiquadState; BUTTERWORTH_Q.len()],
+}
+
+pub struct Distortion {
+    id: usize,
+    pub curve: DistortionCurve,
+    pub drive: f32,  // dB
+    pub output: f32, // dB
+    pub tone: f32,   // Hz, passe-bas après la saturation
+    pub oversampling: usize,
+    pub bits: f32,
+    pub downsample: usize, // facteur de sample & hold du bitcrush
+    pub mix: Mix,
+    drive_gain: f32,
+    output_gain: f32,
+    oversampling_coeffs: [BiquadCoeffs; BUTTERWORTH_Q.len()],
+    oversampling_states: [OversamplingState; 2],
+    tone_coeffs: BiquadCoeffs,
+    tone_states: [BiquadState; 2],
+    dc_states: [(f32, f32); 2], // (entrée, sortie) précédentes du bloqueur de continu
+    hold: [f32; 2],
+    hold_counter: usize,
+}
+
+impl Distortion {
+    pub fn new(id: usize) -> Self {
+        let mut distortion = Self {
+            id,
+            curve: DistortionCurve::SoftClip,
+            drive: 12.0,
+            output: -6.0,
+            tone: 8000.0,
+            oversampling: 2,
+            bits: 8.0,
+            downsample: 1,
+            mix: Mix { dry: 0.0, wet: 1.0 },
+            drive_gain: 1.0,
+            output_gain: 1.0,
+            oversampling_coeffs: [BiquadCoeffs::calc_coeffs_for_lowpass(1000.0, 0.7);
+                BUTTERWORTH_Q.len()],
+            oversampling_states: [OversamplingState::default(); 2],
+            tone_coeffs: BiquadCoeffs::calc_coeffs_for_lowpass(8000.0, 0.707),
+            tone_states: [BiquadState::default(); 2],
+            dc_states: [(0.0, 0.0); 2],
+            hold: [0.0; 2],
+            hold_counter: 0,
+        };
+        distortion.set_drive(12.0);
+        distortion.set_output(-6.0);
+        distortion.set_oversampling(2.0);
+        distortion
+    }
+
+    pub fn set_drive(&mut self, drive: f32) {
+        self.drive = drive;
+        self.drive_gain = 10f32.powf(drive / 20.0);
+    }
+
+    pub fn set_output(&mut self, output: f32) {
+        self.output = output;
+        self.output_gain = 10f32.powf(output / 20.0);
+    }
+
+    pub fn set_curve(&mut self, curve: f32) {
+        if let Ok(curve) = DistortionCurve::try_from(curve as u32) {
+            self.curve = curve;
+        }
+    }
+
+    /// Facteur 1, 2 ou 4
+    pub fn set_oversampling(&mut self, factor: f32) {
+        self.oversampling = match factor as usize {
+            0 | 1 => 1,
+            2 | 3 => 2,
+            _ => MAX_OVERSAMPLING,
+        };
+        // coupure à 0.9 × le Nyquist d'origine ; les coefficients étant calculés pour
+        // SAMPLE_RATE, la fréquence est divisée par le facteur pour tourner au rythme suréchantillonné
+        let cutoff = SAMPLE_RATE * 0.45 / self.oversampling as f32;
+        for (coeffs, q) in self.oversampling_coeffs.iter_mut().zip(BUTTERWORTH_Q) {
+            *coeffs = BiquadCoeffs::calc_coeffs_for_lowpass(cutoff, q);
+        }
+        self.oversampling_states = [OversamplingState::default(); 2];
+    }
+
+    pub fn set_tone(&mut self, tone: f32) {
+        self.tone = tone.clamp(200.0, SAMPLE_RATE * 0.45);
+        self.tone_coeffs = BiquadCoeffs::calc_coeffs_for_lowpass(self.tone, 0.707);
+    }
+
+    fn shape(curve: DistortionCurve, x: f32) -> f32 {
+        match curve {
+            DistortionCurve::SoftClip => x.tanh(),
+            DistortionCurve::HardClip => x.clamp(-1.0, 1.0),
+            DistortionCurve::Foldback => {
+                // replie le signal dans [-1, 1] (onde triangulaire de période 4)
+                let t = (x + 1.0).rem_euclid(4.0);
+                if t < 2.0 { t - 1.0 } else { 3.0 - t }
+            }
+            DistortionCurve::Tube => {
+                // les alternances négatives saturent plus tôt
+                if x >= 0.0 {
+                    x.tanh()
+                } else {
+                    (1.5 * x).tanh() / 1.5 * 1.2
+                }
+            }
+            DistortionCurve::Bitcrush => x,
+        }
+    }
+
+    fn oversampled(&mut self, channel: usize, input: f32, drive: f32) -> f32 {
+        let factor = self.oversampling;
+        if factor == 1 {
+            return Self::shape(self.curve, input * drive);
+        }
+
+        let coeffs = self.oversampling_coeffs;
+        let state = &mut self.oversampling_states[channel];
+        let mut output = 0.0;
+        for k in 0..factor {
+            // insertion de zéros, compensée par le facteur
+            let mut x = if k == 0 { input * factor as f32 } else { 0.0 };
+            for (section, c) in state.up.iter_mut().zip(&coeffs) {
+                x = section.process(c, x);
+            }
+            let mut y = Self::shape(self.curve, x * drive);
+            for (section, c) in state.down.iter_mut().zip(&coeffs) {
+                y = section.process(c, y);
+            }
+            output = y;
+        }
+        output
+    }
+
+    fn bitcrush(&mut self, channel: usize, input: f32, drive: f32) -> f32 {
+        if self.hold_counter == 0 {
+            let levels = 2f32.powf(self.bits - 1.0);
+            self.hold[channel] = ((input * drive).clamp(-1.0, 1.0) * levels).round() / levels;
+        }
+        self.hold[channel]
+    }
+}
+
+pub static DISTORTION_PARAMS: [ParamInfo; 9] = [
+    ParamInfo::choice(
+        "Curve",
+        &["soft clip", "hard clip", "foldback", "tube", "bitcrush"],
+        0,
+    ),
+    ParamInfo::new("Drive", "dB", 0.0, 48.0, 12.0),
+    ParamInfo::new("Output", "dB", -48.0, 12.0, -6.0),
+    ParamInfo::new("Tone", "Hz", 200.0, 19_845.0, 8000.0).logarithmic(),
+    ParamInfo::new("Oversampling", "x", 1.0, MAX_OVERSAMPLING as f32, 2.0).stepped(),
+    ParamInfo::new("Bits", "", 1.0, 16.0, 8.0),
+    ParamInfo::new("Downsample", "x", 1.0, 64.0, 1.0).stepped(),
+    ParamInfo::dry(0.0),
+    ParamInfo::wet(1.0),
+];
+
+impl EffectTrait for Distortion {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn params(&self) -> &'static [ParamInfo] {
+        &DISTORTION_PARAMS
+    }
+
+    fn set_param(&mut self, index: u32, value: f32) -> bool {
+        match index {
+            0 => self.set_curve(value),
+            1 => self.set_drive(value),
+            2 => self.set_output(value),
+            3 => self.set_tone(value),
+            4 => self.set_oversampling(value),
+            5 => self.bits = value,
+            6 => self.downsample = value as usize,
+            7 => self.mix.dry = value,
+            8 => self.mix.wet = value,
+            _ => return false,
+        }
+        true
+    }
+
+    fn process(&mut self, sample_l: &mut f32, sample_r: &mut f32) {
+        let (drive, output) = (self.drive_gain, self.output_gain);
+
+        let mut wet = [*sample_l, *sample_r];
+        for (channel, value) in wet.iter_mut().enumerate() {
+            let shaped = if self.curve == DistortionCurve::Bitcrush {
+                self.bitcrush(channel, *value, drive)
+            } else {
+                self.oversampled(channel, *value, drive)
+            };
+
+            // bloqueur de continu (la courbe tube est asymétrique)
+            let (last_in, last_out) = self.dc_states[channel];
+            let blocked = shaped - last_in + 0.995 * last_out;
+            self.dc_states[channel] = (shaped, blocked);
+
+            *value = self.tone_states[channel].process(&self.tone_coeffs, blocked) * output;
+        }
+        if self.curve == DistortionCurve::Bitcrush {
+            self.hold_counter = (self.hold_counter + 1) % self.downsample.max(1);
+        }
+
+        *sample_l = self.mix.dry * *sample_l + self.mix.wet * wet[0];
+        *sample_r = self.mix.dry * *sample_r + self.mix.wet * wet[1];
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,655 @@
+use std::{any::Any, collections::VecDeque};
+
+use crate::{
+    sound_engine::dsp::fx::{EffectTrait, SideInputs, params::ParamInfo},
+    utils::{constants::SAMPLE_RATE, types::Mix},
+};
+
+const MAX_LOOKAHEAD_MS: f32 = 10.0;
+const MAX_GATE_RANGE: f32 = 96.0;
+
+fn coefficient(ms: f32) -> f32 {
+    if ms <= 0.0 {
+        0.0
+    } else {
+        (-1.0 / (ms * 0.001 * SAMPLE_RATE)).exp()
+    }
+}
+
+fn to_db(value: f32) -> f32 {
+    20.0 * value.max(1e-6).log10()
+}
+
+fn to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// Niveau détecté par canal : `link` à 1 utilise le max des deux canaux (image stéréo conservée)
+fn linked_levels(sample_l: f32, sample_r: f32, link: f32) -> [f32; 2] {
+    let common = sample_l.abs().max(sample_r.abs());
+    [
+        sample_l.abs() + (common - sample_l.abs()) * link,
+        sample_r.abs() + (common - sample_r.abs()) * link,
+    ]
+}
+
+/// Lissage de la réduction de gain (dB) : `rise` quand elle augmente, `fall` quand elle diminue
+#[derive(Default)]
+struct Ballistics {
+    rise: f32,
+    fall: f32,
+    state: [f32; 2],
+}
+
+impl Ballistics {
+    fn process(&mut self, channel: usize, target: f32) -> f32 {
+        let state = &mut self.state[channel];
+        let coeff = if target > *state {
+            self.rise
+        } else {
+            self.fall
+        };
+        *state = target + coeff * (*state - target);
+        *state
+    }
+}
+
+pub struct Compressor {
+    id: usize,
+    pub threshold: f32, // dB
+    pub ratio: f32,
+    pub knee: f32,   // dB
+    pub attack: f32, // ms
+    pub release: f32,
+    pub makeup: f32, // dB
+    pub link: f32,
+    pub mix: Mix,
+    makeup_gain: f32,
+    ballistics: Ballistics,
+    peak_reduction: f32,
+}
+
+impl Compressor {
+    pub fn new(id: usize) -> Self {
+        let mut compressor = Self {
+            id,
+            threshold: -18.0,
+            ratio: 4.0,
+            knee: 6.0,
+            attack: 10.0,
+            release: 120.0,
+            makeup: 0.0,
+            link: 1.0,
+            mix: Mix { dry: 0.0, wet: 1.0 },
+            makeup_gain: 1.0,
+            ballistics: Ballistics::default(),
+            peak_reduction: 0.0,
+        };
+        compressor.set_attack(10.0);
+        compressor.set_release(120.0);
+        compressor
+    }
+
+    pub fn set_attack(&mut self, ms: f32) {
+        self.attack = ms.clamp(0.0, 500.0);
+        self.ballistics.rise = coefficient(self.attack);
+    }
+
+    pub fn set_release(&mut self, ms: f32) {
+        self.release = ms.clamp(1.0, 5000.0);
+        self.ballistics.fall = coefficient(self.release);
+    }
+
+    pub fn set_makeup(&mut self, db: f32) {
+        self.makeup = db;
+        self.makeup_gain = to_gain(db);
+    }
+
+    /// Réduction de gain statique (dB) avec un coude quadratique de largeur `knee`
+    fn gain_computer(&self, level_db: f32) -> f32 {
+        let over = level_db - self.threshold;
+        let slope = 1.0 - 1.0 / self.ratio;
+        if 2.0 * over <= -self.knee {
+            0.0
+        } else if 2.0 * over.abs() < self.knee {
+            slope * (over + self.knee / 2.0).powi(2) / (2.0 * self.knee)
+        } else {
+            slope * over
+        }
+    }
+}
+
+pub static COMPRESSOR_PARAMS: [ParamInfo; 9] = [
+    ParamInfo::new("Threshold", "dB", -60.0, 0.0, -18.0),
+    ParamInfo::new("Ratio", "", 1.0, 100.0, 4.0).logarithmic(),
+    ParamInfo::new("Knee", "dB", 0.0, 24.0, 6.0),
+    ParamInfo::new("Attack", "ms", 0.1, 500.0, 10.0).logarithmic(),
+    ParamInfo::new("Release", "ms", 1.0, 5000.0, 120.0).logarithmic(),
+    ParamInfo::new("Makeup", "dB", 0.0, 24.0, 0.0),
+    ParamInfo::new("Link", "", 0.0, 1.0, 1.0),
+    ParamInfo::dry(0.0),
+    ParamInfo::wet(1.0),
+];
+
+impl EffectTrait for Compressor {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn params(&self) -> &'static [ParamInfo] {
+        &COMPRESSOR_PARAMS
+    }
+
+    fn set_param(&mut self, index: u32, value: f32) -> bool {
+        match index {
+            0 => self.threshold = value,
+            1 => self.ratio = value,
+            2 => self.knee = value,
+            3 => self.set_attack(value),
+            4 => self.set_release(value),
+            5 => self.set_makeup(value),
+            6 => self.link = value,
+            7 => self.mix.dry = value,
+            8 => self.mix.wet = value,
+            _ => return false,
+        }
+        true
+    }
+
+    fn process(&mut self, sample_l: &mut f32, sample_r: &mut f32) {
+        self.process_with_inputs(sample_l, sample_r, &SideInputs::default());
+    }
+
+    /// Avec un sidechain, la détection se fait sur lui plutôt que sur le signal traité
+    fn process_with_inputs(&mut self, sample_l: &mut f32, sample_r: &mut f32, inputs: &SideInputs) {
+        let [key_l, key_r] = inputs.sidechain.unwrap_or([*sample_l, *sample_r]);
+        let levels = linked_levels(key_l, key_r, self.link);
+        let makeup = self.makeup_gain;
+
+        let mut wet = [*sample_l, *sample_r];
+        for (channel, value) in wet.iter_mut().enumerate() {
+            let target = self.gain_computer(to_db(levels[channel]));
+            let reduction = self.ballistics.process(channel, target);
+            self.peak_reduction = self.peak_reduction.max(reduction);
+            *value *= to_gain(-reduction) * makeup;
+        }
+
+        *sample_l = self.mix.dry * *sample_l + self.mix.wet * wet[0];
+        *sample_r = self.mix.dry * *sample_r + self.mix.wet * wet[1];
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn take_gain_reduction(&mut self) -> Option<f32> {
+        Some(std::mem::take(&mut self.peak_reduction))
+    }
+}
+
+/// Noise gate (expandeur vers le bas) : atténue le signal sous le seuil
+pub struct Gate {
+    id: usize,
+    pub threshold: f32, // dB
+    pub ratio: f32,     // au-delà de ~20, le gate coupe franchement
+    pub knee: f32,
+    pub attack: f32,  // ms, ouverture
+    pub release: f32, // ms, fermeture
+    pub range: f32,   // atténuation maximale (dB)
+    pub link: f32,
+    ballistics: Ballistics,
+    peak_reduction: f32,
+}
+
+impl Gate {
+    pub fn new(id: usize) -> Self {
+        let mut gate = Self {
+            id,
+            threshold: -50.0,
+            ratio: 20.0,
+            knee: 6.0,
+            attack: 1.0,
+            release: 150.0,
+            range: 80.0,
+            link: 1.0,
+            ballistics: Ballistics::default(),
+            peak_reduction: 0.0,
+        };
+        gate.set_attack(1.0);
+        gate.set_release(150.0);
+        gate
+    }
+
+    pub fn set_attack(&mut self, ms: f32) {
+        self.attack = ms.clamp(0.0, 500.0);
+        self.ballistics.fall = coefficient(self.attack);
+    }
+
+    pub fn set_release(&mut self, ms: f32) {
+        self.release = ms.clamp(1.0, 5000.0);
+        self.ballistics.rise = coefficient(self.release);
+    }
+
+    pub fn set_range(&mut self, db: f32) {
+        self.range = db.clamp(0.0, MAX_GATE_RANGE);
+    }
+
+    fn gain_computer(&self, level_db: f32) -> f32 {
+        let under = self.threshold - level_db;
+        let slope = self.ratio - 1.0;
+        let reduction = if 2.0 * under <= -self.knee {
+            0.0
+        } else if 2.0 * under.abs() < self.knee {
+            slope * (under + self.knee / 2.0).powi(2) / (2.0 * self.knee)
+        } else {
+            slope * under
+        };
+        reduction.min(self.range)
+    }
+}
+
+pub static GATE_PARAMS: [ParamInfo; 7] = [
+    ParamInfo::new("Threshold", "dB", -96.0, 0.0, -50.0),
+    ParamInfo::new("Ratio", "", 1.0, 100.0, 20.0).logarithmic(),
+    ParamInfo::new("Knee", "dB", 0.0, 24.0, 6.0),
+    ParamInfo::new("Attack", "ms", 0.1, 500.0, 1.0).logarithmic(),
+    ParamInfo::new("Release", "ms", 1.0, 5000.0, 150.0).logarithmic(),
+    ParamInfo::new("Range", "dB", 0.0, MAX_GATE_RANGE, 80.0),
+    ParamInfo::new("Link", "", 0.0, 1.0, 1.0),
+];
+
+impl EffectTrait for Gate {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn params(&self) -> &'static [ParamInfo] {
+        &GATE_PARAMS
+    }
+
+    fn set_param(&mut self, index: u32, value: f32) -> bool {
+        match index {
+            0 => self.threshold = value,
+            1 => self.ratio = value,
+            2 => self.knee = value,
+            3 => self.set_attack(value),
+            4 => self.set_release(value),
+            5 => self.set_range(value),
+            6 => self.link = value,
+            _ => return false,
+        }
+        true
+    }
+
+    fn process(&mut self, sample_l: &mut f32, sample_r: &mut f32) {
+        self.process_with_inputs(sample_l, sample_r, &SideInputs::default());
+    }
+
+    fn process_with_inputs(&mut self, sample_l: &mut f32, sample_r: &mut f32, inputs: &SideInputs) {
+        let [key_l, key_r] = inputs.sidechain.unwrap_or([*sample_l, *sample_r]);
+        let levels = linked_levels(key_l, key_r, self.link);
+
+        for (channel, value) in [sample_l, sample_r].into_iter().enumerate() {
+            let target = self.gain_computer(to_db(levels[channel]));
+            let reduction = self.ballistics.process(channel, target);
+            self.peak_reduction = self.peak_reduction.max(reduction);
+            *value *= to_gain(-reduction);
+        }
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn take_gain_reduction(&mut self) -> Option<f32> {
+        Some(std::mem::take(&mut self.peak_reduction))
+    }
+}
+
+/// Ducker : atténue le signal de `depth` dB tant que le sidechain dépasse le seuil, avec
+/// maintien puis relâchement. Sans sidechain, il réagit au signal traité lui-même.
+pub struct Ducker {
+    id: usize,
+    pub threshold: f32, // dB
+    pub depth: f32,     // dB, atténuation maximale
+    pub attack: f32,    // ms
+    pub hold: f32,
+    pub release: f32,
+    threshold_gain: f32,
+    attack_coeff: f32,
+    release_coeff: f32,
+    envelope: f32, // 0 : pas d'atténuation, 1 : `depth` complète
+    held: usize,
+    peak_reduction: f32,
+}
+
+impl Ducker {
+    pub fn new(id: usize) -> Self {
+        let mut ducker = Self {
+            id,
+            threshold: -30.0,
+            depth: 12.0,
+            attack: 5.0,
+            hold: 20.0,
+            release: 200.0,
+            threshold_gain: 1.0,
+            attack_coeff: 0.0,
+            release_coeff: 0.0,
+            envelope: 0.0,
+            held: 0,
+            peak_reduction: 0.0,
+        };
+        ducker.set_threshold(-30.0);
+        ducker.set_attack(5.0);
+        ducker.set_release(200.0);
+        ducker
+    }
+
+    pub fn set_threshold(&mut self, db: f32) {
+        self.threshold = db;
+        self.threshold_gain = to_gain(db);
+    }
+
+    pub fn set_attack(&mut self, ms: f32) {
+        self.attack = ms.clamp(0.0, 500.0);
+        self.attack_coeff = coefficient(self.attack);
+    }
+
+    pub fn set_release(&mut self, ms: f32) {
+        self.release = ms.clamp(1.0, 5000.0);
+        self.release_coeff = coefficient(self.release);
+    }
+}
+
+pub static DUCKER_PARAMS: [ParamInfo; 5] = [
+    ParamInfo::new("Threshold", "dB", -96.0, 0.0, -30.0),
+    ParamInfo::new("Depth", "dB", 0.0, 96.0, 12.0),
+    ParamInfo::new("Attack", "ms", 0.1, 500.0, 5.0).logarithmic(),
+    ParamInfo::new("Hold", "ms", 0.0, 2000.0, 20.0),
+    ParamInfo::new("Release", "ms", 1.0, 5000.0, 200.0).logarithmic(),
+];
+
+impl EffectTrait for Ducker {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn params(&self) -> &'static [ParamInfo] {
+        &DUCKER_PARAMS
+    }
+
+    fn set_param(&mut self, index: u32, value: f32) -> bool {
+        match index {
+            0 => self.set_threshold(value),
+            1 => self.depth = value,
+            2 => self.set_attack(value),
+            3 => self.hold = value,
+            4 => self.set_release(value),
+            _ => return false,
+        }
+        true
+    }
+
+    fn process(&mut self, sample_l: &mut f32, sample_r: &mut f32) {
+        self.process_with_inputs(sample_l, sample_r, &SideInputs::default());
+    }
+
+    fn process_with_inputs(&mut self, sample_l: &mut f32, sample_r: &mut f32, inputs: &SideInputs) {
+        let [key_l, key_r] = inputs.sidechain.unwrap_or([*sample_l, *sample_r]);
+        if key_l.abs().max(key_r.abs()) > self.threshold_gain {
+            self.held = (self.hold * 0.001 * SAMPLE_RATE) as usize + 1;
+        }
+        let (target, coeff) = if self.held > 0 {
+            self.held -= 1;
+            (1.0, self.attack_coeff)
+        } else {
+            (0.0, self.release_coeff)
+        };
+        self.envelope = target + coeff * (self.envelope - target);
+
+        let reduction = self.depth * self.envelope;
+        self.peak_reduction = self.peak_reduction.max(reduction);
+        let gain = to_gain(-reduction);
+        *sample_l *= gain;
+        *sample_r *= gain;
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn take_gain_reduction(&mut self) -> Option<f32> {
+        Some(std::mem::take(&mut self.peak_reduction))
+    }
+}
+
+/// Minimum glissant en O(1) amorti (file monotone)
+struct SlidingMin {
+    values: VecDeque<(usize, f32)>,
+    counter: usize,
+}
+
+impl SlidingMin {
+    fn new(capacity: usize) -> Self {
+        Self {
+            values: VecDeque::with_capacity(capacity + 1),
+            counter: 0,
+        }
+    }
+
+    fn push(&mut self, value: f32, window: usize) -> f32 {
+        while self.values.back().is_some_and(|&(_, last)| last >= value) {
+            self.values.pop_back();
+        }
+        self.values.push_back((self.counter, value));
+        while self
+            .values
+            .front()
+            .is_some_and(|&(index, _)| index + window <= self.counter)
+        {
+            self.values.pop_front();
+        }
+        self.counter += 1;
+        self.values.front().map_or(1.0, |&(_, min)| min)
+    }
+
+    fn clear(&mut self) {
+        self.values.clear();
+        self.counter = 0;
+    }
+}
+
+/// Limiteur brickwall à anticipation : le gain requis est maintenu sur la fenêtre
+/// d'anticipation puis moyenné sur la même durée, il atteint donc sa valeur avant
+/// que le pic ne sorte de la ligne à retard.
+pub struct Limiter {
+    id: usize,
+    pub ceiling: f32, // dB
+    pub input_gain: f32,
+    pub release: f32, // ms
+    pub lookahead: f32,
+    pub link: f32,
+    input_gain_linear: f32,
+    ceiling_gain: f32,
+    release_coeff: f32,
+    window: usize, // anticipation en samples
+    delay: Vec<[f32; 2]>,
+    averages: Vec<[f32; 2]>,
+    position: usize,
+    sums: [f64; 2],
+    minimums: [SlidingMin; 2],
+    envelopes: [f32; 2],
+    min_gain: f32, // converti en réduction (dB) à la lecture
+}
+
+impl Limiter {
+    pub fn new(id: usize) -> Self {
+        let capacity = (MAX_LOOKAHEAD_MS * 0.001 * SAMPLE_RATE) as usize + 1;
+        let mut limiter = Self {
+            id,
+            ceiling: -0.3,
+            input_gain: 0.0,
+            release: 50.0,
+            lookahead: 1.5,
+            link: 1.0,
+            input_gain_linear: 1.0,
+            ceiling_gain: to_gain(-0.3),
+            release_coeff: coefficient(50.0),
+            window: 1,
+            delay: vec![[0.0; 2]; capacity],
+            averages: vec![[1.0; 2]; capacity],
+            position: 0,
+            sums: [1.0; 2],
+            minimums: [SlidingMin::new(capacity), SlidingMin::new(capacity)],
+            envelopes: [1.0; 2],
+            min_gain: 1.0,
+        };
+        limiter.set_lookahead(1.5);
+        limiter
+    }
+
+    pub fn set_ceiling(&mut self, db: f32) {
+        self.ceiling = db;
+        self.ceiling_gain = to_gain(db);
+    }
+
+    pub fn set_input_gain(&mut self, db: f32) {
+        self.input_gain = db;
+        self.input_gain_linear = to_gain(db);
+    }
+
+    pub fn set_release(&mut self, ms: f32) {
+        self.release = ms.clamp(1.0, 5000.0);
+        self.release_coeff = coefficient(self.release);
+    }
+
+    /// Change la latence du limiteur : les lignes sont remises à zéro
+    pub fn set_lookahead(&mut self, ms: f32) {
+        self.lookahead = ms.clamp(0.1, MAX_LOOKAHEAD_MS);
+        self.window = ((self.lookahead * 0.001 * SAMPLE_RATE) as usize).clamp(1, self.delay.len());
+
+        self.delay.fill([0.0; 2]);
+        self.averages.fill([1.0; 2]);
+        self.position = 0;
+        self.sums = [self.window as f64; 2];
+        self.minimums.iter_mut().for_each(SlidingMin::clear);
+        self.envelopes = [1.0; 2];
+    }
+}
+
+pub static LIMITER_PARAMS: [ParamInfo; 5] = [
+    ParamInfo::new("Ceiling", "dB", -24.0, 0.0, -0.3),
+    ParamInfo::new("Release", "ms", 1.0, 5000.0, 50.0).logarithmic(),
+    ParamInfo::new("Lookahead", "ms", 0.1, MAX_LOOKAHEAD_MS, 1.5).instant(),
+    ParamInfo::new("Input gain", "dB", 0.0, 24.0, 0.0),
+    ParamInfo::new("Link", "", 0.0, 1.0, 1.0),
+];
+
+impl EffectTrait for Limiter {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn params(&self) -> &'static [ParamInfo] {
+        &LIMITER_PARAMS
+    }
+
+    fn set_param(&mut self, index: u32, value: f32) -> bool {
+        match index {
+            0 => self.set_ceiling(value),
+            1 => self.set_release(value),
+            2 => self.set_lookahead(value),
+            3 => self.set_input_gain(value),
+            4 => self.link = value,
+            _ => return false,
+        }
+        true
+    }
+
+    fn process(&mut self, sample_l: &mut f32, sample_r: &mut f32) {
+        let (input_gain, ceiling) = (self.input_gain_linear, self.ceiling_gain);
+        let input = [*sample_l * input_gain, *sample_r * input_gain];
+        let levels = linked_levels(input[0], input[1], self.link);
+
+        let delayed = self.delay[self.position];
+        self.delay[self.position] = input;
+
+        let mut output = [0.0; 2];
+        for channel in 0..2 {
+            let required = if levels[channel] > ceiling {
+                ceiling / levels[channel]
+            } else {
+                1.0
+            };
+            // fenêtre window + 1 : le pic est couvert par toutes les valeurs moyennées
+            let held = self.minimums[channel].push(required, self.window + 1);
+
+            let envelope = &mut self.envelopes[channel];
+            *envelope = if held < *envelope {
+                held
+            } else {
+                held + self.release_coeff * (*envelope - held)
+            };
+
+            self.sums[channel] += (*envelope - self.averages[self.position][channel]) as f64;
+            self.averages[self.position][channel] = *envelope;
+            let gain = (self.sums[channel] / self.window as f64) as f32;
+
+            self.min_gain = self.min_gain.min(gain);
+            output[channel] = (delayed[channel] * gain).clamp(-ceiling, ceiling);
+        }
+        self.position = (self.position + 1) % self.window;
+
+        *sample_l = output[0];
+        *sample_r = output[1];
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn take_gain_reduction(&mut self) -> Option<f32> {
+        Some(-to_db(std::mem::replace(&mut self.min_gain, 1.0)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limiter_keeps_peaks_under_the_ceiling() {
+        let mut limiter = Limiter::new(0);
+        let mut seed = 7u32;
+        for n in 0..20_000 {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            let noise = seed as f32 / u32::MAX as f32 * 2.0 - 1.0;
+            // rafales jusqu'à +12 dBFS
+            let level = if (n / 2000) % 2 == 0 { 4.0 } else { 0.5 };
+            let (mut left, mut right) = (noise * level, -noise * level * 0.7);
+            limiter.process(&mut left, &mut right);
+            assert!(left.abs() <= limiter.ceiling_gain, "sample {}", n);
+            assert!(right.abs() <= limiter.ceiling_gain, "sample {}", n);
+        }
+        assert!(limiter.take_gain_reduction().unwrap() > 0.0);
+    }
+
+    #[test]
+    fn limiter_delays_by_its_window() {
+        let mut limiter = Limiter::new(0);
+        limiter.set_lookahead(3.0);
+        let window = limiter.window;
+        assert_eq!(window, (3.0 * 0.001 * SAMPLE_RATE) as usize);
+
+        for n in 0..2 * window {
+            let input = if n == 0 { 0.5 } else { 0.0 };
+            let (mut left, mut right) = (input, input);
+            limiter.process(&mut left, &mut right);
+            let expected = if n == window { 0.5 } else { 0.0 };
+            assert!((left - expected).abs() < 1e-6, "sample {}", n);
+            assert!((right - expected).abs() < 1e-6, "sample {}", n);
+        }
+    }
+}
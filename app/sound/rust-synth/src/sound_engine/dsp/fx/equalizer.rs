@@ -0,0 +1,196 @@
+use std::any::Any;
+
+use crate::sound_engine::dsp::fx::{
+    BiquadCoeffs, BiquadState, BlockInputs, EffectTrait, FilterType,
+    params::{FILTER_TYPES, ParamInfo},
+};
+
+pub const EQ_BANDS: usize = 8;
+pub const EQ_BAND_PARAMS: u32 = 5; // type, fréquence, gain, Q, activée
+pub const EQ_OUTPUT_PARAM: u32 = EQ_BANDS as u32 * EQ_BAND_PARAMS;
+
+#[derive(Clone, Copy)]
+pub struct EqBand {
+    pub filter_type: FilterType,
+    pub frequency: f32,
+    pub gain: f32, // dB
+    pub q: f32,
+    pub enabled: bool,
+    coeffs: BiquadCoeffs,
+    states: [BiquadState; 2],
+}
+
+impl EqBand {
+    fn new(filter_type: FilterType, frequency: f32) -> Self {
+        Self {
+            filter_type,
+            frequency,
+            gain: 0.0,
+            q: 0.707,
+            enabled: true,
+            coeffs: BiquadCoeffs::new(filter_type, frequency, 0.707, 0.0),
+            states: [BiquadState::default(); 2],
+        }
+    }
+
+    fn update_coeffs(&mut self) {
+        self.coeffs = BiquadCoeffs::new(self.filter_type, self.frequency, self.q, self.gain);
+    }
+}
+
+/// Égaliseur paramétrique : bandes biquad en série, chacune désactivable
+pub struct Equalizer {
+    id: usize,
+    pub bands: [EqBand; EQ_BANDS],
+    pub output: f32, // dB
+    output_gain: f32,
+}
+
+impl Equalizer {
+    pub fn new(id: usize) -> Self {
+        // type et fréquence de départ de chaque bande : ceux de `EQ_PARAMS`
+        let bands = std::array::from_fn(|index| {
+            let params = &EQ_PARAMS[index * EQ_BAND_PARAMS as usize..];
+            let filter_type =
+                FilterType::try_from(params[0].default as u32).unwrap_or(FilterType::Bell);
+            EqBand::new(filter_type, params[1].default)
+        });
+
+        Self {
+            id,
+            bands,
+            output: 0.0,
+            output_gain: 1.0,
+        }
+    }
+
+    /// `param` : 0 type, 1 fréquence, 2 gain, 3 Q, 4 activée (0 ou 1) ; `false` si invalide
+    pub fn set_band_param(&mut self, band: usize, param: u32, value: f32) -> bool {
+        let Some(band) = self.bands.get_mut(band) else {
+            return false;
+        };
+        match param {
+            0 => match FilterType::try_from(value as u32) {
+                Ok(filter_type) => band.filter_type = filter_type,
+                Err(_) => return false,
+            },
+            1 => band.frequency = value,
+            2 => band.gain = value.clamp(-24.0, 24.0),
+            3 => band.q = value.clamp(0.1, 24.0),
+            4 => {
+                let enabled = value >= 0.5;
+                if enabled && !band.enabled {
+                    band.states = [BiquadState::default(); 2];
+                }
+                band.enabled = enabled;
+            }
+            _ => return false,
+        }
+        band.update_coeffs();
+        true
+    }
+
+    /// Réponse en amplitude (dB) de toutes les bandes actives, gain de sortie compris
+    pub fn magnitude_response(&self, frequencies: &[f32], magnitudes: &mut [f32]) {
+        for (frequency, magnitude) in frequencies.iter().zip(magnitudes.iter_mut()) {
+            let linear: f32 = self
+                .bands
+                .iter()
+                .filter(|band| band.enabled)
+                .map(|band| band.coeffs.magnitude(*frequency))
+                .product();
+            *magnitude = 20.0 * linear.max(1e-6).log10() + self.output;
+        }
+    }
+}
+
+/// Paramètres de chaque bande (`EQ_BAND_PARAMS`), puis gain de sortie
+macro_rules! eq_params {
+    ($(($band:literal, $filter_type:expr, $frequency:literal)),*) => {
+        [
+            $(
+                ParamInfo::choice(
+                    concat!("Band ", $band, " type"),
+                    FILTER_TYPES,
+                    $filter_type as usize,
+                ),
+                ParamInfo::new(
+                    concat!("Band ", $band, " frequency"),
+                    "Hz",
+                    20.0,
+                    20_000.0,
+                    $frequency,
+                )
+                .logarithmic(),
+                ParamInfo::new(concat!("Band ", $band, " gain"), "dB", -24.0, 24.0, 0.0),
+                ParamInfo::new(concat!("Band ", $band, " Q"), "", 0.1, 24.0, 0.707).logarithmic(),
+                ParamInfo::toggle(concat!("Band ", $band, " enabled"), true),
+            )*
+            ParamInfo::new("Output", "dB", -24.0, 24.0, 0.0),
+        ]
+    };
+}
+
+pub static EQ_PARAMS: [ParamInfo; EQ_OUTPUT_PARAM as usize + 1] = eq_params!(
+    (1, FilterType::LowShelf, 40.0),
+    (2, FilterType::Bell, 100.0),
+    (3, FilterType::Bell, 250.0),
+    (4, FilterType::Bell, 600.0),
+    (5, FilterType::Bell, 1500.0),
+    (6, FilterType::Bell, 3500.0),
+    (7, FilterType::Bell, 8000.0),
+    (8, FilterType::HighShelf, 16000.0)
+);
+
+impl EffectTrait for Equalizer {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn params(&self) -> &'static [ParamInfo] {
+        &EQ_PARAMS
+    }
+
+    fn set_param(&mut self, index: u32, value: f32) -> bool {
+        if index == EQ_OUTPUT_PARAM {
+            self.output = value;
+            self.output_gain = 10f32.powf(value / 20.0);
+            return true;
+        }
+        self.set_band_param(
+            (index / EQ_BAND_PARAMS) as usize,
+            index % EQ_BAND_PARAMS,
+            value,
+        )
+    }
+
+    fn process(&mut self, sample_l: &mut f32, sample_r: &mut f32) {
+        for band in self.bands.iter_mut().filter(|band| band.enabled) {
+            let [state_l, state_r] = &mut band.states;
+            *sample_l = state_l.process(&band.coeffs, *sample_l);
+            *sample_r = state_r.process(&band.coeffs, *sample_r);
+        }
+
+        *sample_l *= self.output_gain;
+        *sample_r *= self.output_gain;
+    }
+
+    /// Bande par bande sur tout le bloc, comme `BiquadFilter`
+    fn process_block(&mut self, frames: &mut [[f32; 2]], _inputs: &BlockInputs) {
+        for band in self.bands.iter_mut().filter(|band| band.enabled) {
+            let [state_l, state_r] = &mut band.states;
+            for [sample_l, sample_r] in frames.iter_mut() {
+                *sample_l = state_l.process(&band.coeffs, *sample_l);
+                *sample_r = state_r.process(&band.coeffs, *sample_r);
+            }
+        }
+        for [sample_l, sample_r] in frames.iter_mut() {
+            *sample_l *= self.output_gain;
+            *sample_r *= self.output_gain;
+        }
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,965 @@
+use std::any::Any;
+
+use web_sys::console;
+
+use crate::{
+    sound_engine::dsp::fx::params::{FILTER_TYPES, NOTE_DIVISIONS, NOTE_MODIFIERS, ParamInfo},
+    utils::{
+        constants::SAMPLE_RATE,
+        toolkit::ToolKit,
+        types::{Mix, Sample},
+    },
+};
+
+pub mod amplitude;
+pub mod convolution;
+pub mod distortion;
+pub mod dynamics;
+pub mod equalizer;
+pub mod modulation;
+pub mod params;
+pub mod pitch;
+pub mod reverb;
+pub mod stereo;
+pub mod vocoder;
+pub mod zdf;
+
+pub struct MemoryBuffer {
+    pub buffer: Vec<f32>,
+    pub size: usize,
+    pub write_index: usize,
+}
+
+impl MemoryBuffer {
+    /// Crée un buffer pour `duration_seconds` à `sample_rate` Hz
+    pub fn new(sample_rate: usize, duration_seconds: f32) -> Self {
+        let size = (sample_rate as f32 * duration_seconds * 2.0) as usize;
+        Self {
+            buffer: vec![0.0; size],
+            size,
+            write_index: 0,
+        }
+    }
+
+    pub fn write(&mut self, sample_l: f32, sample_r: f32) {
+        self.buffer[self.write_index] = sample_l;
+        self.buffer[self.write_index + 1] = sample_r;
+        self.write_index = (self.write_index + 2) % self.size;
+    }
+
+    pub fn read_left(&self, delay_samples: usize) -> f32 {
+        // On recule de delay_samples * 2 cases (car stéréo)
+        let read_index = (self.size + self.write_index - delay_samples * 2) % self.size;
+        self.buffer[read_index]
+    }
+
+    pub fn read_right(&self, delay_samples: usize) -> f32 {
+        let read_index = (self.size + self.write_index - delay_samples * 2) % self.size;
+        // Toujours dans la paire stéréo
+        self.buffer[(read_index + 1) % self.size]
+    }
+
+    /// Lecture stéréo à un retard fractionnaire (en frames), interpolée linéairement
+    pub fn read_fractional(&self, delay_samples: f32) -> (f32, f32) {
+        let max_delay = (self.size / 2 - 1) as f32;
+        let delay = delay_samples.clamp(1.0, max_delay);
+        let whole = delay as usize;
+        let frac = delay - whole as f32;
+
+        let (l0, r0) = (self.read_left(whole), self.read_right(whole));
+        let (l1, r1) = (self.read_left(whole + 1), self.read_right(whole + 1));
+        (l0 + (l1 - l0) * frac, r0 + (r1 - r0) * frac)
+    }
+}
+
+/// Oscillateur basse fréquence, entre -1 et 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LfoWaveform {
+    Sine = 0,
+    Triangle = 1,
+    Square = 2,
+    SawUp = 3,
+    SawDown = 4,
+}
+
+impl TryFrom<u32> for LfoWaveform {
+    type Error = ();
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(LfoWaveform::Sine),
+            1 => Ok(LfoWaveform::Triangle),
+            2 => Ok(LfoWaveform::Square),
+            3 => Ok(LfoWaveform::SawUp),
+            4 => Ok(LfoWaveform::SawDown),
+            _ => Err(()),
+        }
+    }
+}
+
+pub struct Lfo {
+    pub phase: f32, // 0 à 1
+    pub rate: f32,  // Hz
+    pub waveform: LfoWaveform,
+}
+
+impl Lfo {
+    pub fn new(rate: f32) -> Self {
+        Self {
+            phase: 0.0,
+            rate,
+            waveform: LfoWaveform::Sine,
+        }
+    }
+
+    /// Valeur (-1 à 1) à `offset` (fraction de période) de la phase courante
+    pub fn value(&self, offset: f32) -> f32 {
+        let phase = (self.phase + offset).rem_euclid(1.0);
+        match self.waveform {
+            LfoWaveform::Sine => (phase * std::f32::consts::TAU).sin(),
+            // même phase que le sinus : 0 en 0, maximum au quart de période
+            LfoWaveform::Triangle => 1.0 - 4.0 * ((phase + 0.25).fract() - 0.5).abs(),
+            LfoWaveform::Square => {
+                if phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            LfoWaveform::SawUp => 2.0 * (phase + 0.5).fract() - 1.0,
+            LfoWaveform::SawDown => 1.0 - 2.0 * (phase + 0.5).fract(),
+        }
+    }
+
+    pub fn advance(&mut self) {
+        self.phase = (self.phase + self.rate / SAMPLE_RATE).fract();
+    }
+}
+
+/// Valeur de note pour la synchronisation au tempo (0 : pas de synchro)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoteDivision {
+    Off = 0,
+    Whole = 1,
+    Half = 2,
+    Quarter = 3,
+    Eighth = 4,
+    Sixteenth = 5,
+    ThirtySecond = 6,
+}
+
+impl TryFrom<u32> for NoteDivision {
+    type Error = ();
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(NoteDivision::Off),
+            1 => Ok(NoteDivision::Whole),
+            2 => Ok(NoteDivision::Half),
+            3 => Ok(NoteDivision::Quarter),
+            4 => Ok(NoteDivision::Eighth),
+            5 => Ok(NoteDivision::Sixteenth),
+            6 => Ok(NoteDivision::ThirtySecond),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoteModifier {
+    Straight = 0,
+    Dotted = 1,  // × 3/2
+    Triplet = 2, // × 2/3
+}
+
+impl TryFrom<u32> for NoteModifier {
+    type Error = ();
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(NoteModifier::Straight),
+            1 => Ok(NoteModifier::Dotted),
+            2 => Ok(NoteModifier::Triplet),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TempoSync {
+    pub division: NoteDivision,
+    pub modifier: NoteModifier,
+}
+
+impl TempoSync {
+    pub fn new() -> Self {
+        Self {
+            division: NoteDivision::Off,
+            modifier: NoteModifier::Straight,
+        }
+    }
+
+    /// Durée de la note en secondes à `bpm`, `None` si la synchro est désactivée
+    pub fn seconds(&self, bpm: f32) -> Option<f32> {
+        if self.division == NoteDivision::Off {
+            return None;
+        }
+        // une ronde = 4 noires
+        let beats = 4.0 / 2f32.powi(self.division as i32 - 1);
+        let beats = match self.modifier {
+            NoteModifier::Straight => beats,
+            NoteModifier::Dotted => beats * 1.5,
+            NoteModifier::Triplet => beats * 2.0 / 3.0,
+        };
+        Some(beats * 60.0 / bpm.max(1.0))
+    }
+}
+
+impl Default for TempoSync {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub enum EffectsEnum {
+    Echo,
+    Filter,
+    Reverb,
+    Chorus,
+    Flanger,
+    Phaser,
+    Distortion,
+    Compressor,
+    Limiter,
+    Gate,
+    Equalizer,
+    StateVariableFilter,
+    LadderFilter,
+    Convolution,
+    Tremolo,
+    AutoPan,
+    RingModulator,
+    PitchShifter,
+    StereoTool,
+    Vocoder,
+    Ducker,
+}
+
+impl TryFrom<u32> for EffectsEnum {
+    type Error = ();
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(EffectsEnum::Echo),
+            1 => Ok(EffectsEnum::Filter),
+            2 => Ok(EffectsEnum::Reverb),
+            3 => Ok(EffectsEnum::Chorus),
+            4 => Ok(EffectsEnum::Flanger),
+            5 => Ok(EffectsEnum::Phaser),
+            6 => Ok(EffectsEnum::Distortion),
+            7 => Ok(EffectsEnum::Compressor),
+            8 => Ok(EffectsEnum::Limiter),
+            9 => Ok(EffectsEnum::Gate),
+            10 => Ok(EffectsEnum::Equalizer),
+            11 => Ok(EffectsEnum::StateVariableFilter),
+            12 => Ok(EffectsEnum::LadderFilter),
+            13 => Ok(EffectsEnum::Convolution),
+            14 => Ok(EffectsEnum::Tremolo),
+            15 => Ok(EffectsEnum::AutoPan),
+            16 => Ok(EffectsEnum::RingModulator),
+            17 => Ok(EffectsEnum::PitchShifter),
+            18 => Ok(EffectsEnum::StereoTool),
+            19 => Ok(EffectsEnum::Vocoder),
+            20 => Ok(EffectsEnum::Ducker),
+            _ => Err(()),
+        }
+    }
+}
+impl EffectsEnum {
+    /// Paramètres d'un type d'effet, sans avoir à en créer un
+    pub fn params(&self) -> &'static [ParamInfo] {
+        match self {
+            EffectsEnum::Echo => &ECHO_PARAMS,
+            EffectsEnum::Filter => &FILTER_PARAMS,
+            EffectsEnum::Reverb => &reverb::REVERB_PARAMS,
+            EffectsEnum::Chorus => &modulation::CHORUS_PARAMS,
+            EffectsEnum::Flanger => &modulation::FLANGER_PARAMS,
+            EffectsEnum::Phaser => &modulation::PHASER_PARAMS,
+            EffectsEnum::Distortion => &distortion::DISTORTION_PARAMS,
+            EffectsEnum::Compressor => &dynamics::COMPRESSOR_PARAMS,
+            EffectsEnum::Limiter => &dynamics::LIMITER_PARAMS,
+            EffectsEnum::Gate => &dynamics::GATE_PARAMS,
+            EffectsEnum::Equalizer => &equalizer::EQ_PARAMS,
+            EffectsEnum::StateVariableFilter => &zdf::SVF_PARAMS,
+            EffectsEnum::LadderFilter => &zdf::LADDER_PARAMS,
+            EffectsEnum::Convolution => &convolution::CONVOLUTION_PARAMS,
+            EffectsEnum::Tremolo => &amplitude::TREMOLO_PARAMS,
+            EffectsEnum::AutoPan => &amplitude::AUTO_PAN_PARAMS,
+            EffectsEnum::RingModulator => &amplitude::RING_MODULATOR_PARAMS,
+            EffectsEnum::PitchShifter => &pitch::SHIFTER_PARAMS,
+            EffectsEnum::StereoTool => &stereo::STEREO_TOOL_PARAMS,
+            EffectsEnum::Vocoder => &vocoder::VOCODER_PARAMS,
+            EffectsEnum::Ducker => &dynamics::DUCKER_PARAMS,
+        }
+    }
+
+    /// Nouvel effet `id` : chaque paramètre prend la valeur par défaut de son `ParamInfo`,
+    /// seule référence (le constructeur ne fait que fournir un état valide)
+    pub fn create(&self, id: usize) -> Box<dyn EffectTrait> {
+        let mut effect: Box<dyn EffectTrait> = match self {
+            EffectsEnum::Echo => Box::new(Echo::new(id)),
+            EffectsEnum::Filter => {
+                Box::new(BiquadFilter::new(800.0, 0.7, id, FilterType::Lowpass, 5.0))
+            }
+            EffectsEnum::Reverb => Box::new(reverb::Reverb::new(id)),
+            EffectsEnum::Chorus => Box::new(modulation::Chorus::new(id)),
+            EffectsEnum::Flanger => Box::new(modulation::Flanger::new(id)),
+            EffectsEnum::Phaser => Box::new(modulation::Phaser::new(id)),
+            EffectsEnum::Distortion => Box::new(distortion::Distortion::new(id)),
+            EffectsEnum::Compressor => Box::new(dynamics::Compressor::new(id)),
+            EffectsEnum::Limiter => Box::new(dynamics::Limiter::new(id)),
+            EffectsEnum::Gate => Box::new(dynamics::Gate::new(id)),
+            EffectsEnum::Equalizer => Box::new(equalizer::Equalizer::new(id)),
+            EffectsEnum::StateVariableFilter => Box::new(zdf::StateVariableFilter::new(id)),
+            EffectsEnum::LadderFilter => Box::new(zdf::LadderFilter::new(id)),
+            EffectsEnum::Convolution => Box::new(convolution::Convolution::new(id)),
+            EffectsEnum::Tremolo => Box::new(amplitude::Tremolo::new(id)),
+            EffectsEnum::AutoPan => Box::new(amplitude::AutoPan::new(id)),
+            EffectsEnum::RingModulator => Box::new(amplitude::RingModulator::new(id)),
+            EffectsEnum::PitchShifter => Box::new(pitch::PitchShifter::new(id)),
+            EffectsEnum::StereoTool => Box::new(stereo::StereoTool::new(id)),
+            EffectsEnum::Vocoder => Box::new(vocoder::Vocoder::new(id)),
+            EffectsEnum::Ducker => Box::new(dynamics::Ducker::new(id)),
+        };
+        for (index, info) in effect.params().iter().enumerate() {
+            effect.set_param(index as u32, info.default);
+        }
+        effect
+    }
+}
+
+/// Signaux annexes de la frame traitée
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SideInputs {
+    pub audio_input: [f32; 2],       // entrée audio du navigateur
+    pub sidechain: Option<[f32; 2]>, // source de détection choisie pour l'effet
+}
+
+/// Signaux annexes d'un bloc, alignés frame par frame sur le bloc traité
+#[derive(Debug, Clone, Copy)]
+pub struct BlockInputs<'a> {
+    pub audio_input: &'a [[f32; 2]],
+    pub sidechain: Option<&'a [[f32; 2]]>,
+}
+
+impl BlockInputs<'_> {
+    pub fn at(&self, frame: usize) -> SideInputs {
+        SideInputs {
+            audio_input: self.audio_input.get(frame).copied().unwrap_or_default(),
+            sidechain: self
+                .sidechain
+                .and_then(|sidechain| sidechain.get(frame).copied()),
+        }
+    }
+
+    /// Signaux du sous-bloc commençant à la frame `start`
+    pub fn from_frame(&self, start: usize) -> Self {
+        BlockInputs {
+            audio_input: self.audio_input.get(start..).unwrap_or_default(),
+            sidechain: self
+                .sidechain
+                .map(|sidechain| sidechain.get(start..).unwrap_or_default()),
+        }
+    }
+}
+
+pub trait EffectTrait: Any {
+    fn id(&self) -> usize;
+    fn process(&mut self, sample_l: &mut f32, sample_r: &mut f32);
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    /// Paramètres réglables, dans l'ordre de leurs index
+    fn params(&self) -> &'static [ParamInfo];
+
+    /// Applique un paramètre déjà ramené dans sa plage par `ParamInfo::constrain`,
+    /// `false` si l'index n'existe pas
+    fn set_param(&mut self, index: u32, value: f32) -> bool;
+
+    /// Applique un paramètre `ParamInfo::sample` : le sample est cherché par l'appelant,
+    /// hors du verrou du mixer (`None` s'il est introuvable)
+    fn set_sample(&mut self, _index: u32, _sample: Option<Sample>) -> bool {
+        false
+    }
+
+    /// Tempo de l'hôte (BPM), pour les effets synchronisables
+    fn set_tempo(&mut self, _bpm: f32) {}
+
+    /// Réduction de gain maximale (dB) depuis le dernier appel, pour les vumètres du JS
+    fn take_gain_reduction(&mut self) -> Option<f32> {
+        None
+    }
+
+    /// Forme étendue de `process` appelée par le mixer, à implémenter par les effets qui
+    /// utilisent l'entrée audio ou un sidechain
+    fn process_with_inputs(
+        &mut self,
+        sample_l: &mut f32,
+        sample_r: &mut f32,
+        _inputs: &SideInputs,
+    ) {
+        self.process(sample_l, sample_r);
+    }
+
+    /// Traite un bloc de frames en place : c'est ce qu'appelle le mixer, une fois par bloc.
+    /// L'implémentation par défaut, compilée pour chaque effet, repasse par le traitement
+    /// sample par sample sans appel virtuel ; à surcharger si l'effet gagne à travailler
+    /// sur le bloc entier (pour l'instant `BiquadFilter` et `Equalizer`)
+    fn process_block(&mut self, frames: &mut [[f32; 2]], inputs: &BlockInputs) {
+        for (index, [sample_l, sample_r]) in frames.iter_mut().enumerate() {
+            self.process_with_inputs(sample_l, sample_r, &inputs.at(index));
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BiquadCoeffs {
+    pub b0: f32,
+    pub b1: f32,
+    pub b2: f32,
+    pub a1: f32,
+    pub a2: f32,
+}
+
+/// Types de biquad (formules du RBJ Audio EQ Cookbook)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FilterType {
+    Lowpass = 0,
+    Highpass = 1,
+    Bell = 2,
+    Bandpass = 3, // gain unitaire au centre
+    Notch = 4,
+    Allpass = 5,
+    LowShelf = 6,
+    HighShelf = 7,
+    Tilt = 8, // -gain/2 dans les graves, +gain/2 dans les aigus, pivot à `frequency`
+}
+
+impl FilterType {
+    /// Types dont la pente est réglable par mise en cascade
+    pub fn has_slope(&self) -> bool {
+        matches!(self, FilterType::Lowpass | FilterType::Highpass)
+    }
+
+    pub fn uses_gain(&self) -> bool {
+        matches!(
+            self,
+            FilterType::Bell | FilterType::LowShelf | FilterType::HighShelf | FilterType::Tilt
+        )
+    }
+}
+
+impl TryFrom<u32> for FilterType {
+    type Error = ();
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(FilterType::Lowpass),
+            1 => Ok(FilterType::Highpass),
+            2 => Ok(FilterType::Bell),
+            3 => Ok(FilterType::Bandpass),
+            4 => Ok(FilterType::Notch),
+            5 => Ok(FilterType::Allpass),
+            6 => Ok(FilterType::LowShelf),
+            7 => Ok(FilterType::HighShelf),
+            8 => Ok(FilterType::Tilt),
+            _ => Err(()),
+        }
+    }
+}
+
+impl BiquadCoeffs {
+    pub fn new(filter_type: FilterType, frequency: f32, q: f32, gain_db: f32) -> BiquadCoeffs {
+        match filter_type {
+            FilterType::Lowpass => Self::calc_coeffs_for_lowpass(frequency, q),
+            FilterType::Highpass => Self::calc_coeffs_for_highpass(frequency, q),
+            FilterType::Bell => Self::calc_coeffs_for_bell(frequency, q, gain_db),
+            FilterType::Bandpass => Self::calc_coeffs_for_bandpass(frequency, q),
+            FilterType::Notch => Self::calc_coeffs_for_notch(frequency, q),
+            FilterType::Allpass => Self::calc_coeffs_for_allpass(frequency, q),
+            FilterType::LowShelf => Self::calc_coeffs_for_low_shelf(frequency, q, gain_db),
+            FilterType::HighShelf => Self::calc_coeffs_for_high_shelf(frequency, q, gain_db),
+            FilterType::Tilt => Self::calc_coeffs_for_tilt(frequency, q, gain_db),
+        }
+    }
+
+    /// Pulsation normalisée et alpha, fréquence bornée sous Nyquist pour rester stable
+    fn omega(frequency: f32, q: f32) -> (f32, f32) {
+        let frequency = frequency.clamp(10.0, SAMPLE_RATE * 0.49);
+        let w0 = 2.0 * std::f32::consts::PI * frequency / SAMPLE_RATE;
+        (w0, w0.sin() / (2.0 * q.max(0.01)))
+    }
+
+    fn normalized(b0: f32, b1: f32, b2: f32, a0: f32, a1: f32, a2: f32) -> BiquadCoeffs {
+        BiquadCoeffs {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+
+    pub fn calc_coeffs_for_lowpass(frequency: f32, q: f32) -> BiquadCoeffs {
+        let (w0, alpha) = Self::omega(frequency, q);
+
+        let b0 = (1.0 - w0.cos()) / 2.0;
+        let b1 = 1.0 - w0.cos();
+        let b2 = (1.0 - w0.cos()) / 2.0;
+        Self::normalized(b0, b1, b2, 1.0 + alpha, -2.0 * w0.cos(), 1.0 - alpha)
+    }
+
+    pub fn calc_coeffs_for_highpass(frequency: f32, q: f32) -> BiquadCoeffs {
+        let (w0, alpha) = Self::omega(frequency, q);
+
+        let b0 = (1.0 + w0.cos()) / 2.0;
+        let b1 = -(1.0 + w0.cos());
+        let b2 = (1.0 + w0.cos()) / 2.0;
+        Self::normalized(b0, b1, b2, 1.0 + alpha, -2.0 * w0.cos(), 1.0 - alpha)
+    }
+
+    pub fn calc_coeffs_for_bell(frequency: f32, q: f32, gain_db: f32) -> BiquadCoeffs {
+        let a = 10f32.powf(gain_db / 40.0); // amplitude linéaire
+        let (w0, alpha) = Self::omega(frequency, q);
+
+        let b0 = 1.0 + alpha * a;
+        let b1 = -2.0 * w0.cos();
+        let b2 = 1.0 - alpha * a;
+        let a0 = 1.0 + alpha / a;
+        let a1 = -2.0 * w0.cos();
+        let a2 = 1.0 - alpha / a;
+        Self::normalized(b0, b1, b2, a0, a1, a2)
+    }
+
+    pub fn calc_coeffs_for_bandpass(frequency: f32, q: f32) -> BiquadCoeffs {
+        let (w0, alpha) = Self::omega(frequency, q);
+        Self::normalized(
+            alpha,
+            0.0,
+            -alpha,
+            1.0 + alpha,
+            -2.0 * w0.cos(),
+            1.0 - alpha,
+        )
+    }
+
+    pub fn calc_coeffs_for_notch(frequency: f32, q: f32) -> BiquadCoeffs {
+        let (w0, alpha) = Self::omega(frequency, q);
+        let b1 = -2.0 * w0.cos();
+        Self::normalized(1.0, b1, 1.0, 1.0 + alpha, b1, 1.0 - alpha)
+    }
+
+    pub fn calc_coeffs_for_allpass(frequency: f32, q: f32) -> BiquadCoeffs {
+        let (w0, alpha) = Self::omega(frequency, q);
+        let b1 = -2.0 * w0.cos();
+        Self::normalized(1.0 - alpha, b1, 1.0 + alpha, 1.0 + alpha, b1, 1.0 - alpha)
+    }
+
+    pub fn calc_coeffs_for_low_shelf(frequency: f32, q: f32, gain_db: f32) -> BiquadCoeffs {
+        let a = 10f32.powf(gain_db / 40.0);
+        let (w0, alpha) = Self::omega(frequency, q);
+        let (cos, sqrt) = (w0.cos(), 2.0 * a.sqrt() * alpha);
+
+        let b0 = a * ((a + 1.0) - (a - 1.0) * cos + sqrt);
+        let b1 = 2.0 * a * ((a - 1.0) - (a + 1.0) * cos);
+        let b2 = a * ((a + 1.0) - (a - 1.0) * cos - sqrt);
+        let a0 = (a + 1.0) + (a - 1.0) * cos + sqrt;
+        let a1 = -2.0 * ((a - 1.0) + (a + 1.0) * cos);
+        let a2 = (a + 1.0) + (a - 1.0) * cos - sqrt;
+        Self::normalized(b0, b1, b2, a0, a1, a2)
+    }
+
+    pub fn calc_coeffs_for_high_shelf(frequency: f32, q: f32, gain_db: f32) -> BiquadCoeffs {
+        let a = 10f32.powf(gain_db / 40.0);
+        let (w0, alpha) = Self::omega(frequency, q);
+        let (cos, sqrt) = (w0.cos(), 2.0 * a.sqrt() * alpha);
+
+        let b0 = a * ((a + 1.0) + (a - 1.0) * cos + sqrt);
+        let b1 = -2.0 * a * ((a - 1.0) + (a + 1.0) * cos);
+        let b2 = a * ((a + 1.0) + (a - 1.0) * cos - sqrt);
+        let a0 = (a + 1.0) - (a - 1.0) * cos + sqrt;
+        let a1 = 2.0 * ((a - 1.0) - (a + 1.0) * cos);
+        let a2 = (a + 1.0) - (a - 1.0) * cos - sqrt;
+        Self::normalized(b0, b1, b2, a0, a1, a2)
+    }
+
+    /// Amplitude linéaire de la réponse à `frequency` (Hz)
+    pub fn magnitude(&self, frequency: f32) -> f32 {
+        let w = 2.0 * std::f32::consts::PI * frequency / SAMPLE_RATE;
+        let (cos1, sin1) = (w.cos(), w.sin());
+        let (cos2, sin2) = ((2.0 * w).cos(), (2.0 * w).sin());
+
+        let num_re = self.b0 + self.b1 * cos1 + self.b2 * cos2;
+        let num_im = -(self.b1 * sin1 + self.b2 * sin2);
+        let den_re = 1.0 + self.a1 * cos1 + self.a2 * cos2;
+        let den_im = -(self.a1 * sin1 + self.a2 * sin2);
+
+        ((num_re * num_re + num_im * num_im) / (den_re * den_re + den_im * den_im)).sqrt()
+    }
+
+    /// High shelf de `gain_db` abaissé de moitié : la pente pivote autour de `frequency`
+    pub fn calc_coeffs_for_tilt(frequency: f32, q: f32, gain_db: f32) -> BiquadCoeffs {
+        let shelf = Self::calc_coeffs_for_high_shelf(frequency, q, gain_db);
+        let trim = 10f32.powf(-gain_db / 40.0);
+        BiquadCoeffs {
+            b0: shelf.b0 * trim,
+            b1: shelf.b1 * trim,
+            b2: shelf.b2 * trim,
+            ..shelf
+        }
+    }
+}
+
+/// Section biquad mono (forme directe II transposée), pour les filtres internes aux effets
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BiquadState {
+    z1: f32,
+    z2: f32,
+}
+
+impl BiquadState {
+    pub fn process(&mut self, coeffs: &BiquadCoeffs, input: f32) -> f32 {
+        let output = coeffs.b0 * input + self.z1;
+        self.z1 = coeffs.b1 * input - coeffs.a1 * output + self.z2;
+        self.z2 = coeffs.b2 * input - coeffs.a2 * output;
+        output
+    }
+}
+
+pub const MAX_FILTER_SECTIONS: usize = 4; // 48 dB/oct
+
+/// Q des sections d'un Butterworth d'ordre 2 × `sections`, de la plus amortie à la plus résonante
+pub fn butterworth_qs(sections: usize) -> impl Iterator<Item = f32> {
+    let order = (2 * sections) as f32;
+    (1..=sections).rev().map(move |k| {
+        let theta = std::f32::consts::PI * (2 * k - 1) as f32 / (2.0 * order);
+        1.0 / (2.0 * theta.cos())
+    })
+}
+
+pub struct BiquadFilter {
+    id: usize,
+    pub coeffs: [BiquadCoeffs; MAX_FILTER_SECTIONS],
+    states: [[BiquadState; 2]; MAX_FILTER_SECTIONS],
+    pub sections: usize,
+    pub frequency: f32,
+    pub q: f32,
+    pub filter_type: FilterType,
+    pub gain: f32,
+}
+
+impl BiquadFilter {
+    pub fn new(frequency: f32, q: f32, id: usize, filter_type: FilterType, gain: f32) -> Self {
+        let mut filter = BiquadFilter {
+            coeffs: [BiquadCoeffs::new(filter_type, frequency, q, gain); MAX_FILTER_SECTIONS],
+            states: [[BiquadState::default(); 2]; MAX_FILTER_SECTIONS],
+            sections: 1,
+            id,
+            frequency,
+            q,
+            filter_type,
+            gain,
+        };
+        filter.update_coeffs();
+        filter
+    }
+
+    pub fn set_frequency(&mut self, frequency: f32) {
+        self.frequency = frequency;
+        self.update_coeffs();
+    }
+
+    pub fn set_q(&mut self, q: f32) {
+        self.q = q;
+        self.update_coeffs();
+    }
+
+    pub fn set_gain(&mut self, gain: f32) {
+        self.gain = gain;
+        self.update_coeffs();
+    }
+
+    pub fn set_type(&mut self, filter_type: f32) {
+        match FilterType::try_from(filter_type as u32) {
+            Ok(filter_type) => {
+                self.filter_type = filter_type;
+                self.update_coeffs();
+            }
+            Err(_) => console::error_1(&format!("Unknown filter type {}", filter_type).into()),
+        }
+    }
+
+    /// Pente en dB/oct (12, 24, 36 ou 48), seulement pour le passe-bas et le passe-haut
+    pub fn set_slope(&mut self, slope: f32) {
+        let sections = ((slope / 12.0).round() as usize).clamp(1, MAX_FILTER_SECTIONS);
+        if sections > self.sections {
+            // les nouvelles sections partent d'un état vierge
+            for state in &mut self.states[self.sections..sections] {
+                *state = [BiquadState::default(); 2];
+            }
+        }
+        self.sections = sections;
+        self.update_coeffs();
+    }
+
+    fn active_sections(&self) -> usize {
+        if self.filter_type.has_slope() {
+            self.sections
+        } else {
+            1
+        }
+    }
+
+    fn update_coeffs(&mut self) {
+        let sections = self.active_sections();
+        if sections == 1 {
+            self.coeffs[0] = BiquadCoeffs::new(self.filter_type, self.frequency, self.q, self.gain);
+            return;
+        }
+
+        // cascade Butterworth, la résonance du Q s'applique à la dernière section
+        let resonance = self.q / std::f32::consts::FRAC_1_SQRT_2;
+        for (index, section_q) in butterworth_qs(sections).enumerate() {
+            let q = if index == sections - 1 {
+                section_q * resonance
+            } else {
+                section_q
+            };
+            self.coeffs[index] = BiquadCoeffs::new(self.filter_type, self.frequency, q, self.gain);
+        }
+    }
+}
+
+pub static FILTER_PARAMS: [ParamInfo; 5] = [
+    ParamInfo::new("Frequency", "Hz", 20.0, 20_000.0, 800.0).logarithmic(),
+    ParamInfo::new("Q", "", 0.1, 24.0, 0.7).logarithmic(),
+    ParamInfo::choice("Type", FILTER_TYPES, 0),
+    ParamInfo::new("Gain", "dB", -24.0, 24.0, 5.0),
+    ParamInfo::new("Slope", "dB/oct", 12.0, 48.0, 12.0).stepped(),
+];
+
+impl EffectTrait for BiquadFilter {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn params(&self) -> &'static [ParamInfo] {
+        &FILTER_PARAMS
+    }
+
+    fn set_param(&mut self, index: u32, value: f32) -> bool {
+        match index {
+            0 => self.set_frequency(value),
+            1 => self.set_q(value),
+            2 => self.set_type(value),
+            3 => self.set_gain(value),
+            4 => self.set_slope(value),
+            _ => return false,
+        }
+        true
+    }
+
+    fn process(&mut self, sample_l: &mut f32, sample_r: &mut f32) {
+        for index in 0..self.active_sections() {
+            let coeffs = &self.coeffs[index];
+            let [state_l, state_r] = &mut self.states[index];
+            *sample_l = state_l.process(coeffs, *sample_l);
+            *sample_r = state_r.process(coeffs, *sample_r);
+        }
+    }
+
+    /// Section par section sur tout le bloc : coefficients et états restent en registres
+    fn process_block(&mut self, frames: &mut [[f32; 2]], _inputs: &BlockInputs) {
+        for index in 0..self.active_sections() {
+            let coeffs = &self.coeffs[index];
+            let [state_l, state_r] = &mut self.states[index];
+            for [sample_l, sample_r] in frames.iter_mut() {
+                *sample_l = state_l.process(coeffs, *sample_l);
+                *sample_r = state_r.process(coeffs, *sample_r);
+            }
+        }
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// glissement du temps de retard (~50 ms) : effet bande plutôt que clic
+const DELAY_GLIDE: f32 = 0.05;
+
+pub struct Echo {
+    id: usize,
+    pub delay: f32, // frames, temps libre quand la synchro est désactivée
+    pub feedback: f32,
+    pub memory: MemoryBuffer,
+    pub r_delay_offset: f32,
+    pub l_delay_offset: f32,
+    pub mix: Mix,
+    pub sync: TempoSync,
+    pub tempo: f32,
+    pub ping_pong: bool,
+    pub low_cut: f32,         // Hz, passe-haut dans la boucle
+    pub high_cut: f32,        // Hz, passe-bas dans la boucle
+    pub saturation: f32,      // 0 à 1
+    current_delays: [f64; 2], // f64 : en f32 le glissement cale à quelques frames de la cible
+    loop_coeffs: [BiquadCoeffs; 2],
+    loop_states: [[BiquadState; 2]; 2], // [canal][passe-haut, passe-bas]
+}
+
+impl Echo {
+    pub fn new(id: usize) -> Self {
+        let mut echo = Echo {
+            mix: Mix { dry: 1.0, wet: 0.7 },
+            delay: ToolKit::convert_ms_to_sample(300.0) as f32,
+            feedback: 0.7,
+            memory: MemoryBuffer::new(44100, 10.0),
+            r_delay_offset: ToolKit::convert_ms_to_sample(50.0) as f32,
+            l_delay_offset: ToolKit::convert_ms_to_sample(10.0) as f32,
+            id,
+            sync: TempoSync::new(),
+            tempo: 120.0,
+            ping_pong: false,
+            low_cut: 20.0,
+            high_cut: 19_845.0,
+            saturation: 0.0,
+            current_delays: [0.0; 2],
+            loop_coeffs: [BiquadCoeffs::calc_coeffs_for_highpass(20.0, 0.707); 2],
+            loop_states: [[BiquadState::default(); 2]; 2],
+        };
+        echo.current_delays = echo.target_delays().map(f64::from);
+        echo.set_loop_filter(20.0, 19_845.0);
+        echo
+    }
+
+    pub fn set_loop_filter(&mut self, low_cut: f32, high_cut: f32) {
+        self.low_cut = low_cut.clamp(20.0, 2000.0);
+        self.high_cut = high_cut.clamp(500.0, SAMPLE_RATE * 0.45);
+        self.loop_coeffs = [
+            BiquadCoeffs::calc_coeffs_for_highpass(self.low_cut, 0.707),
+            BiquadCoeffs::calc_coeffs_for_lowpass(self.high_cut, 0.707),
+        ];
+    }
+
+    pub fn set_division(&mut self, division: f32) {
+        if let Ok(division) = NoteDivision::try_from(division as u32) {
+            self.sync.division = division;
+        }
+    }
+
+    pub fn set_modifier(&mut self, modifier: f32) {
+        if let Ok(modifier) = NoteModifier::try_from(modifier as u32) {
+            self.sync.modifier = modifier;
+        }
+    }
+
+    /// Retards visés (frames) pour chaque canal, la synchro remplace le temps libre
+    fn target_delays(&self) -> [f32; 2] {
+        let base = self
+            .sync
+            .seconds(self.tempo)
+            .map_or(self.delay, |seconds| seconds * SAMPLE_RATE);
+        [base + self.l_delay_offset, base + self.r_delay_offset]
+    }
+
+    fn process_loop(&mut self, channel: usize, sample: f32) -> f32 {
+        let [high_pass, low_pass] = &mut self.loop_states[channel];
+        let filtered = low_pass.process(
+            &self.loop_coeffs[1],
+            high_pass.process(&self.loop_coeffs[0], sample),
+        );
+        if self.saturation <= 0.0 {
+            return filtered;
+        }
+        // tanh normalisé : gain unitaire à faible niveau
+        let drive = 1.0 + self.saturation * 9.0;
+        (filtered * drive).tanh() / drive
+    }
+}
+
+/// Temps en ms, convertis en frames par `set_param`
+pub static ECHO_PARAMS: [ParamInfo; 12] = [
+    ParamInfo::new("Delay", "ms", 1.0, 5000.0, 300.0).logarithmic(),
+    ParamInfo::new("Feedback", "", 0.0, 1.0, 0.7),
+    ParamInfo::new("Left offset", "ms", 0.0, 100.0, 10.0),
+    ParamInfo::new("Right offset", "ms", 0.0, 100.0, 50.0),
+    ParamInfo::dry(1.0),
+    ParamInfo::wet(0.7),
+    ParamInfo::choice("Division", NOTE_DIVISIONS, 0),
+    ParamInfo::choice("Modifier", NOTE_MODIFIERS, 0),
+    ParamInfo::toggle("Ping-pong", false),
+    ParamInfo::new("Low cut", "Hz", 20.0, 2000.0, 20.0).logarithmic(),
+    ParamInfo::new("High cut", "Hz", 500.0, 19_845.0, 19_845.0).logarithmic(),
+    ParamInfo::new("Saturation", "", 0.0, 1.0, 0.0),
+];
+
+impl EffectTrait for Echo {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn params(&self) -> &'static [ParamInfo] {
+        &ECHO_PARAMS
+    }
+
+    fn set_param(&mut self, index: u32, value: f32) -> bool {
+        match index {
+            0 => self.delay = ToolKit::convert_ms_to_sample(value) as f32,
+            1 => self.feedback = value,
+            2 => self.l_delay_offset = ToolKit::convert_ms_to_sample(value) as f32,
+            3 => self.r_delay_offset = ToolKit::convert_ms_to_sample(value) as f32,
+            4 => self.mix.dry = value,
+            5 => self.mix.wet = value,
+            6 => self.set_division(value),
+            7 => self.set_modifier(value),
+            8 => self.ping_pong = value >= 0.5,
+            9 => self.set_loop_filter(value, self.high_cut),
+            10 => self.set_loop_filter(self.low_cut, value),
+            11 => self.saturation = value,
+            _ => return false,
+        }
+        true
+    }
+
+    fn process(&mut self, input_l: &mut f32, input_r: &mut f32) {
+        let glide = 1.0 - (-1.0 / (DELAY_GLIDE as f64 * SAMPLE_RATE as f64)).exp();
+        let targets = self.target_delays();
+        for (current, target) in self.current_delays.iter_mut().zip(targets) {
+            *current += (target as f64 - *current) * glide;
+        }
+
+        let (l, _) = self.memory.read_fractional(self.current_delays[0] as f32);
+        let (_, r) = self.memory.read_fractional(self.current_delays[1] as f32);
+        let loop_l = self.process_loop(0, l) * self.feedback;
+        let loop_r = self.process_loop(1, r) * self.feedback;
+
+        // ping-pong : l'entrée (mono) n'entre qu'à gauche et les répétitions se croisent
+        let (write_l, write_r) = if self.ping_pong {
+            ((*input_l + *input_r) * 0.5 + loop_r, loop_l)
+        } else {
+            (*input_l + loop_l, *input_r + loop_r)
+        };
+        self.memory.write(write_l, write_r);
+
+        *input_l = self.mix.dry * *input_l + self.mix.wet * l;
+        *input_r = self.mix.dry * *input_r + self.mix.wet * r;
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn set_tempo(&mut self, bpm: f32) {
+        self.tempo = bpm;
+    }
+}
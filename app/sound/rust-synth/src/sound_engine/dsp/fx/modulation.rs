@@ -0,0 +1,304 @@
+use std::any::Any;
+
+use crate::{
+    sound_engine::dsp::fx::{EffectTrait, Lfo, MemoryBuffer, params::ParamInfo},
+    utils::{constants::SAMPLE_RATE, types::Mix},
+};
+
+const MAX_CHORUS_VOICES: usize = 4;
+const MAX_PHASER_STAGES: usize = 12;
+
+fn ms_to_samples(ms: f32) -> f32 {
+    ms / 1000.0 * SAMPLE_RATE
+}
+
+/// Chorus : plusieurs lectures d'un même retard modulées par des LFO déphasés
+pub struct Chorus {
+    id: usize,
+    pub lfo: Lfo,
+    pub depth: f32, // ms
+    pub delay: f32, // ms
+    pub voices: usize,
+    pub spread: f32, // déphasage du canal droit, 0 à 1
+    pub mix: Mix,
+    memory: MemoryBuffer,
+}
+
+impl Chorus {
+    pub fn new(id: usize) -> Self {
+        Self {
+            id,
+            lfo: Lfo::new(0.8),
+            depth: 3.0,
+            delay: 15.0,
+            voices: 3,
+            spread: 0.5,
+            mix: Mix { dry: 1.0, wet: 0.5 },
+            memory: MemoryBuffer::new(SAMPLE_RATE as usize, 0.1),
+        }
+    }
+
+    pub fn set_voices(&mut self, voices: f32) {
+        self.voices = (voices as usize).clamp(1, MAX_CHORUS_VOICES);
+    }
+}
+
+pub static CHORUS_PARAMS: [ParamInfo; 7] = [
+    ParamInfo::new("Rate", "Hz", 0.01, 10.0, 0.8).logarithmic(),
+    ParamInfo::new("Depth", "ms", 0.0, 20.0, 3.0),
+    ParamInfo::new("Delay", "ms", 1.0, 50.0, 15.0),
+    ParamInfo::new("Voices", "", 1.0, MAX_CHORUS_VOICES as f32, 3.0).stepped(),
+    ParamInfo::new("Spread", "", 0.0, 1.0, 0.5),
+    ParamInfo::dry(1.0),
+    ParamInfo::wet(0.5),
+];
+
+impl EffectTrait for Chorus {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn params(&self) -> &'static [ParamInfo] {
+        &CHORUS_PARAMS
+    }
+
+    fn set_param(&mut self, index: u32, value: f32) -> bool {
+        match index {
+            0 => self.lfo.rate = value,
+            1 => self.depth = value,
+            2 => self.delay = value,
+            3 => self.set_voices(value),
+            4 => self.spread = value,
+            5 => self.mix.dry = value,
+            6 => self.mix.wet = value,
+            _ => return false,
+        }
+        true
+    }
+
+    fn process(&mut self, sample_l: &mut f32, sample_r: &mut f32) {
+        self.memory.write(*sample_l, *sample_r);
+
+        let (mut wet_l, mut wet_r) = (0.0, 0.0);
+        for voice in 0..self.voices {
+            let offset = voice as f32 / self.voices as f32;
+            // chaque voix lit son canal à un retard différent
+            let delay_l = self.delay + self.depth * 0.5 * (1.0 + self.lfo.value(offset));
+            let delay_r =
+                self.delay + self.depth * 0.5 * (1.0 + self.lfo.value(offset + self.spread * 0.5));
+            wet_l += self.memory.read_fractional(ms_to_samples(delay_l)).0;
+            wet_r += self.memory.read_fractional(ms_to_samples(delay_r)).1;
+        }
+        self.lfo.advance();
+
+        let norm = 1.0 / self.voices as f32;
+        *sample_l = self.mix.dry * *sample_l + self.mix.wet * wet_l * norm;
+        *sample_r = self.mix.dry * *sample_r + self.mix.wet * wet_r * norm;
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Flanger : retard court modulé, réinjecté dans la ligne
+pub struct Flanger {
+    id: usize,
+    pub lfo: Lfo,
+    pub depth: f32,    // ms
+    pub delay: f32,    // ms, retard minimum
+    pub feedback: f32, // -0.95 à 0.95
+    pub spread: f32,
+    pub mix: Mix,
+    memory: MemoryBuffer,
+}
+
+impl Flanger {
+    pub fn new(id: usize) -> Self {
+        Self {
+            id,
+            lfo: Lfo::new(0.25),
+            depth: 2.0,
+            delay: 1.0,
+            feedback: 0.5,
+            spread: 0.25,
+            mix: Mix { dry: 1.0, wet: 0.7 },
+            memory: MemoryBuffer::new(SAMPLE_RATE as usize, 0.05),
+        }
+    }
+}
+
+pub static FLANGER_PARAMS: [ParamInfo; 7] = [
+    ParamInfo::new("Rate", "Hz", 0.01, 10.0, 0.25).logarithmic(),
+    ParamInfo::new("Depth", "ms", 0.0, 10.0, 2.0),
+    ParamInfo::new("Delay", "ms", 0.1, 20.0, 1.0),
+    ParamInfo::new("Feedback", "", -0.95, 0.95, 0.5),
+    ParamInfo::new("Spread", "", 0.0, 1.0, 0.25),
+    ParamInfo::dry(1.0),
+    ParamInfo::wet(0.7),
+];
+
+impl EffectTrait for Flanger {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn params(&self) -> &'static [ParamInfo] {
+        &FLANGER_PARAMS
+    }
+
+    fn set_param(&mut self, index: u32, value: f32) -> bool {
+        match index {
+            0 => self.lfo.rate = value,
+            1 => self.depth = value,
+            2 => self.delay = value,
+            3 => self.feedback = value,
+            4 => self.spread = value,
+            5 => self.mix.dry = value,
+            6 => self.mix.wet = value,
+            _ => return false,
+        }
+        true
+    }
+
+    fn process(&mut self, sample_l: &mut f32, sample_r: &mut f32) {
+        let delay_l = self.delay + self.depth * 0.5 * (1.0 + self.lfo.value(0.0));
+        let delay_r = self.delay + self.depth * 0.5 * (1.0 + self.lfo.value(self.spread * 0.5));
+        let wet_l = self.memory.read_fractional(ms_to_samples(delay_l)).0;
+        let wet_r = self.memory.read_fractional(ms_to_samples(delay_r)).1;
+        self.lfo.advance();
+
+        self.memory.write(
+            *sample_l + wet_l * self.feedback,
+            *sample_r + wet_r * self.feedback,
+        );
+
+        *sample_l = self.mix.dry * *sample_l + self.mix.wet * wet_l;
+        *sample_r = self.mix.dry * *sample_r + self.mix.wet * wet_r;
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Phaser : passe-tout du premier ordre en série dont la fréquence suit le LFO
+pub struct Phaser {
+    id: usize,
+    pub lfo: Lfo,
+    pub depth: f32,     // 0 à 1, en octaves autour de `frequency`
+    pub frequency: f32, // Hz, centre du balayage
+    pub feedback: f32,  // -0.95 à 0.95
+    pub stages: usize,
+    pub spread: f32,
+    pub mix: Mix,
+    state_l: [f32; MAX_PHASER_STAGES],
+    state_r: [f32; MAX_PHASER_STAGES],
+    last_l: f32,
+    last_r: f32,
+}
+
+impl Phaser {
+    pub fn new(id: usize) -> Self {
+        Self {
+            id,
+            lfo: Lfo::new(0.5),
+            depth: 0.8,
+            frequency: 800.0,
+            feedback: 0.4,
+            stages: 6,
+            spread: 0.25,
+            mix: Mix { dry: 1.0, wet: 1.0 },
+            state_l: [0.0; MAX_PHASER_STAGES],
+            state_r: [0.0; MAX_PHASER_STAGES],
+            last_l: 0.0,
+            last_r: 0.0,
+        }
+    }
+
+    pub fn set_stages(&mut self, stages: f32) {
+        // nombre pair : chaque paire de passe-tout crée une encoche
+        self.stages = ((stages as usize / 2) * 2).clamp(2, MAX_PHASER_STAGES);
+    }
+
+    fn coefficient(&self, lfo: f32) -> f32 {
+        // balayage de ±3 octaves au maximum
+        let frequency =
+            (self.frequency * 2f32.powf(lfo * self.depth * 3.0)).clamp(20.0, SAMPLE_RATE * 0.45);
+        let t = (std::f32::consts::PI * frequency / SAMPLE_RATE).tan();
+        (t - 1.0) / (t + 1.0)
+    }
+
+    fn run_stages(states: &mut [f32], coefficient: f32, input: f32) -> f32 {
+        let mut signal = input;
+        for state in states {
+            let output = coefficient * signal + *state;
+            *state = signal - coefficient * output;
+            signal = output;
+        }
+        signal
+    }
+}
+
+pub static PHASER_PARAMS: [ParamInfo; 8] = [
+    ParamInfo::new("Rate", "Hz", 0.01, 10.0, 0.5).logarithmic(),
+    ParamInfo::new("Depth", "", 0.0, 1.0, 0.8),
+    ParamInfo::new("Frequency", "Hz", 20.0, 10_000.0, 800.0).logarithmic(),
+    ParamInfo::new("Feedback", "", -0.95, 0.95, 0.4),
+    ParamInfo::new("Stages", "", 2.0, MAX_PHASER_STAGES as f32, 6.0).stepped(),
+    ParamInfo::new("Spread", "", 0.0, 1.0, 0.25),
+    ParamInfo::dry(1.0),
+    ParamInfo::wet(1.0),
+];
+
+impl EffectTrait for Phaser {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn params(&self) -> &'static [ParamInfo] {
+        &PHASER_PARAMS
+    }
+
+    fn set_param(&mut self, index: u32, value: f32) -> bool {
+        match index {
+            0 => self.lfo.rate = value,
+            1 => self.depth = value,
+            2 => self.frequency = value,
+            3 => self.feedback = value,
+            4 => self.set_stages(value),
+            5 => self.spread = value,
+            6 => self.mix.dry = value,
+            7 => self.mix.wet = value,
+            _ => return false,
+        }
+        true
+    }
+
+    fn process(&mut self, sample_l: &mut f32, sample_r: &mut f32) {
+        let coefficient_l = self.coefficient(self.lfo.value(0.0));
+        let coefficient_r = self.coefficient(self.lfo.value(self.spread * 0.5));
+        self.lfo.advance();
+
+        let stages = self.stages;
+        let wet_l = Self::run_stages(
+            &mut self.state_l[..stages],
+            coefficient_l,
+            *sample_l + self.last_l * self.feedback,
+        );
+        let wet_r = Self::run_stages(
+            &mut self.state_r[..stages],
+            coefficient_r,
+            *sample_r + self.last_r * self.feedback,
+        );
+        self.last_l = wet_l;
+        self.last_r = wet_r;
+
+        *sample_l = self.mix.dry * *sample_l + self.mix.wet * wet_l;
+        *sample_r = self.mix.dry * *sample_r + self.mix.wet * wet_r;
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
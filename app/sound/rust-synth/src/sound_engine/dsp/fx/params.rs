@@ -0,0 +1,141 @@
+/// Échelle d'un paramètre entre `min` et `max`, pour construire les contrôles côté JS
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamCurve {
+    Linear = 0,
+    Logarithmic = 1, // fréquences, temps : `min` > 0
+    Stepped = 2,     // valeurs entières
+}
+
+/// Description d'un paramètre d'effet ; son index est sa position dans `params()`
+#[derive(Debug, Clone)]
+pub struct ParamInfo {
+    pub name: &'static str,
+    pub unit: &'static str, // vide sans unité
+    pub min: f32,
+    pub max: f32,
+    pub default: f32,
+    pub curve: ParamCurve,
+    pub options: &'static [&'static str], // libellés des valeurs 0, 1, 2... d'un choix
+    pub instant: bool,                    // reconstruit l'effet : appliqué sans rampe
+}
+
+// ids de sample, entiers exacts en f32 jusqu'à 2^24
+pub const MAX_SAMPLE_ID: f32 = 16_777_216.0;
+
+pub const LFO_WAVEFORMS: &[&str] = &["sine", "triangle", "square", "saw up", "saw down"];
+pub const NOTE_DIVISIONS: &[&str] = &["off", "1/1", "1/2", "1/4", "1/8", "1/16", "1/32"];
+pub const NOTE_MODIFIERS: &[&str] = &["straight", "dotted", "triplet"];
+pub const FILTER_TYPES: &[&str] = &[
+    "lowpass",
+    "highpass",
+    "bell",
+    "bandpass",
+    "notch",
+    "allpass",
+    "low shelf",
+    "high shelf",
+    "tilt",
+];
+
+impl ParamInfo {
+    pub const fn new(
+        name: &'static str,
+        unit: &'static str,
+        min: f32,
+        max: f32,
+        default: f32,
+    ) -> Self {
+        Self {
+            name,
+            unit,
+            min,
+            max,
+            default,
+            curve: ParamCurve::Linear,
+            options: &[],
+            instant: false,
+        }
+    }
+
+    pub const fn logarithmic(mut self) -> Self {
+        self.curve = ParamCurve::Logarithmic;
+        self
+    }
+
+    pub const fn stepped(mut self) -> Self {
+        self.curve = ParamCurve::Stepped;
+        self
+    }
+
+    /// Paramètre trop coûteux ou qui réinitialise l'effet pour être suivi pendant une rampe
+    pub const fn instant(mut self) -> Self {
+        self.instant = true;
+        self
+    }
+
+    /// Paramètre discret dont la valeur est l'index de l'option
+    pub const fn choice(
+        name: &'static str,
+        options: &'static [&'static str],
+        default: usize,
+    ) -> Self {
+        let mut info = Self::new(name, "", 0.0, (options.len() - 1) as f32, default as f32);
+        info.curve = ParamCurve::Stepped;
+        info.options = options;
+        info
+    }
+
+    /// Id d'un sample du `SampleManager` (IR, modulateur...), résolu hors du verrou du
+    /// mixer et transmis par `EffectTrait::set_sample`
+    pub const fn sample(name: &'static str) -> Self {
+        Self::new(name, "sample", 0.0, MAX_SAMPLE_ID, 0.0).stepped()
+    }
+
+    pub const fn toggle(name: &'static str, default: bool) -> Self {
+        Self::choice(name, &["off", "on"], default as usize)
+    }
+
+    pub const fn dry(default: f32) -> Self {
+        Self::new("Dry", "", 0.0, 1.0, default)
+    }
+
+    pub const fn wet(default: f32) -> Self {
+        Self::new("Wet", "", 0.0, 1.0, default)
+    }
+
+    /// Ramène une valeur reçue du JS dans la plage, arrondie pour un paramètre discret
+    pub fn constrain(&self, value: f32) -> f32 {
+        if value.is_nan() {
+            return self.default;
+        }
+        let value = value.clamp(self.min, self.max);
+        match self.curve {
+            ParamCurve::Stepped => value.round(),
+            _ => value,
+        }
+    }
+
+    pub fn is_sample(&self) -> bool {
+        self.unit == "sample"
+    }
+
+    /// Lissé par le mixer : tout paramètre continu qui ne reconstruit pas l'effet
+    pub fn is_smoothed(&self) -> bool {
+        !self.instant && self.curve != ParamCurve::Stepped
+    }
+
+    /// Valeur dans l'espace où la rampe est linéaire : une fréquence glisse par octaves
+    pub fn to_ramp(&self, value: f32) -> f32 {
+        match self.curve {
+            ParamCurve::Logarithmic => value.ln(),
+            _ => value,
+        }
+    }
+
+    pub fn from_ramp(&self, value: f32) -> f32 {
+        match self.curve {
+            ParamCurve::Logarithmic => value.exp(),
+            _ => value,
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use std::{any::Any, f32::consts::TAU};
+
+use crate::{
+    sound_engine::dsp::fx::{EffectTrait, MemoryBuffer, params::ParamInfo},
+    utils::{constants::SAMPLE_RATE, types::Mix},
+};
+
+pub const SHIFTER_VOICES: usize = 4;
+pub const SHIFTER_VOICE_PARAMS: u32 = 4;
+// paramètres globaux après ceux des voix
+pub const SHIFTER_WINDOW_PARAM: u32 = SHIFTER_VOICES as u32 * SHIFTER_VOICE_PARAMS;
+pub const SHIFTER_DRY_PARAM: u32 = SHIFTER_WINDOW_PARAM + 1;
+pub const SHIFTER_WET_PARAM: u32 = SHIFTER_WINDOW_PARAM + 2;
+
+const MIN_WINDOW_MS: f32 = 10.0;
+const MAX_WINDOW_MS: f32 = 100.0;
+const DIRECT_FADE_MS: f32 = 10.0; // bascule vers le signal direct sans transposition
+
+/// Voix transposée : deux lectures de la ligne à retard décalées d'une demi-fenêtre,
+/// dont le retard glisse à la vitesse `1 - ratio` et qui se relaient en fondu (Hann)
+#[derive(Debug, Clone, Copy)]
+pub struct ShifterVoice {
+    pub semitones: f32, // -24 à 24
+    pub cents: f32,     // -100 à 100
+    pub level: f32,     // 0 : voix coupée
+    pub pan: f32,       // -1.0 (gauche) à 1.0 (droite)
+    phase: f32,
+    direct: f32, // part du signal direct, 1 quand la voix ne transpose pas
+}
+
+impl ShifterVoice {
+    fn new(level: f32) -> Self {
+        Self {
+            semitones: 0.0,
+            cents: 0.0,
+            level,
+            pan: 0.0,
+            phase: 0.0,
+            direct: 1.0,
+        }
+    }
+
+    fn ratio(&self) -> f32 {
+        2f32.powf((self.semitones + self.cents / 100.0) / 12.0)
+    }
+
+    fn process(&mut self, memory: &MemoryBuffer, window: f32, dry: (f32, f32)) -> (f32, f32) {
+        let ratio = self.ratio();
+        // sans transposition, les deux lectures fixes formeraient un filtre en peigne : la
+        // voix passe alors en fondu au signal direct
+        let target = if ratio == 1.0 { 1.0 } else { 0.0 };
+        let step = 1000.0 / (DIRECT_FADE_MS * SAMPLE_RATE);
+        self.direct = (self.direct + (target - self.direct).clamp(-step, step)).clamp(0.0, 1.0);
+
+        let (mut l, mut r) = (0.0, 0.0);
+        if self.direct < 1.0 {
+            for offset in [0.0, 0.5] {
+                let phase = (self.phase + offset).fract();
+                let gain = 0.5 - 0.5 * (TAU * phase).cos();
+                let (tap_l, tap_r) = memory.read_fractional(1.0 + phase * window);
+                l += tap_l * gain;
+                r += tap_r * gain;
+            }
+            self.phase = (self.phase + (1.0 - ratio) / window).rem_euclid(1.0);
+        }
+        let shifted = 1.0 - self.direct;
+        let (l, r) = (
+            l * shifted + dry.0 * self.direct,
+            r * shifted + dry.1 * self.direct,
+        );
+
+        let gain_l = self.level * (1.0 - self.pan).min(1.0);
+        let gain_r = self.level * (1.0 + self.pan).min(1.0);
+        (l * gain_l, r * gain_r)
+    }
+}
+
+/// Pitch shifter / harmoniseur par ligne à retard : les formants suivent la hauteur
+/// (pas de correction d'enveloppe spectrale). Chaque voix a sa transposition, son
+/// niveau et son panoramique ; seule la première est active par défaut.
+pub struct PitchShifter {
+    id: usize,
+    pub voices: [ShifterVoice; SHIFTER_VOICES],
+    pub window: f32, // ms, plus long = moins de modulation, plus de latence
+    pub mix: Mix,
+    memory: MemoryBuffer,
+}
+
+impl PitchShifter {
+    pub fn new(id: usize) -> Self {
+        let mut voices = [ShifterVoice::new(0.0); SHIFTER_VOICES];
+        voices[0] = ShifterVoice::new(1.0);
+        voices[0].semitones = 12.0;
+        Self {
+            id,
+            voices,
+            window: 50.0,
+            mix: Mix { dry: 1.0, wet: 0.7 },
+            memory: MemoryBuffer::new(SAMPLE_RATE as usize, MAX_WINDOW_MS / 1000.0 + 0.01),
+        }
+    }
+
+    pub fn set_window(&mut self, ms: f32) {
+        self.window = ms.clamp(MIN_WINDOW_MS, MAX_WINDOW_MS);
+    }
+
+    /// `param` : 0 demi-tons, 1 cents, 2 niveau, 3 pan
+    pub fn set_voice_param(&mut self, voice: usize, param: u32, value: f32) -> bool {
+        let Some(voice) = self.voices.get_mut(voice) else {
+            return false;
+        };
+        match param {
+            0 => voice.semitones = value.clamp(-24.0, 24.0),
+            1 => voice.cents = value.clamp(-100.0, 100.0),
+            2 => voice.level = value.clamp(0.0, 1.0),
+            3 => voice.pan = value.clamp(-1.0, 1.0),
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Paramètres de chaque voix (`SHIFTER_VOICE_PARAMS`), puis fenêtre, dry et wet
+macro_rules! shifter_params {
+    ($(($voice:literal, $semitones:literal, $level:literal)),*) => {
+        [
+            $(
+                ParamInfo::new(
+                    concat!("Voice ", $voice, " semitones"),
+                    "st",
+                    -24.0,
+                    24.0,
+                    $semitones,
+                ),
+                ParamInfo::new(concat!("Voice ", $voice, " cents"), "cents", -100.0, 100.0, 0.0),
+                ParamInfo::new(concat!("Voice ", $voice, " level"), "", 0.0, 1.0, $level),
+                ParamInfo::new(concat!("Voice ", $voice, " pan"), "", -1.0, 1.0, 0.0),
+            )*
+            ParamInfo::new("Window", "ms", MIN_WINDOW_MS, MAX_WINDOW_MS, 50.0),
+            ParamInfo::dry(1.0),
+            ParamInfo::wet(0.7),
+        ]
+    };
+}
+
+// seule la première voix sonne, une octave au-dessus
+pub static SHIFTER_PARAMS: [ParamInfo; SHIFTER_WET_PARAM as usize + 1] =
+    shifter_params!((1, 12.0, 1.0), (2, 0.0, 0.0), (3, 0.0, 0.0), (4, 0.0, 0.0));
+
+impl EffectTrait for PitchShifter {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn params(&self) -> &'static [ParamInfo] {
+        &SHIFTER_PARAMS
+    }
+
+    fn set_param(&mut self, index: u32, value: f32) -> bool {
+        match index {
+            SHIFTER_WINDOW_PARAM => self.set_window(value),
+            SHIFTER_DRY_PARAM => self.mix.dry = value,
+            SHIFTER_WET_PARAM => self.mix.wet = value,
+            _ => {
+                return self.set_voice_param(
+                    (index / SHIFTER_VOICE_PARAMS) as usize,
+                    index % SHIFTER_VOICE_PARAMS,
+                    value,
+                );
+            }
+        }
+        true
+    }
+
+    fn process(&mut self, sample_l: &mut f32, sample_r: &mut f32) {
+        self.memory.write(*sample_l, *sample_r);
+        let window = self.window / 1000.0 * SAMPLE_RATE;
+
+        let (mut wet_l, mut wet_r) = (0.0, 0.0);
+        for voice in self.voices.iter_mut().filter(|v| v.level > 0.0) {
+            let (l, r) = voice.process(&self.memory, window, (*sample_l, *sample_r));
+            wet_l += l;
+            wet_r += r;
+        }
+
+        *sample_l = self.mix.dry * *sample_l + self.mix.wet * wet_l;
+        *sample_r = self.mix.dry * *sample_r + self.mix.wet * wet_r;
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,238 @@
+use std::any::Any;
+
+use crate::{
+    sound_engine::dsp::fx::{EffectTrait, params::ParamInfo},
+    utils::{constants::SAMPLE_RATE, types::Mix},
+};
+
+const LINES: usize = 8;
+// longueurs de base des lignes du FDN (ms), premières entre elles pour éviter les résonances
+const LINE_LENGTHS_MS: [f32; LINES] = [29.7, 37.1, 41.1, 43.7, 53.3, 59.9, 67.1, 73.7];
+const DIFFUSER_LENGTHS_MS: [f32; 4] = [4.77, 3.59, 12.73, 9.31];
+const MAX_SIZE: f32 = 2.0;
+const MAX_PRE_DELAY_MS: f32 = 500.0;
+
+fn ms_to_samples(ms: f32) -> usize {
+    (ms / 1000.0 * SAMPLE_RATE) as usize
+}
+
+/// Ligne à retard mono
+struct DelayLine {
+    buffer: Vec<f32>,
+    index: usize,
+}
+
+impl DelayLine {
+    fn new(size: usize) -> Self {
+        Self {
+            buffer: vec![0.0; size.max(1)],
+            index: 0,
+        }
+    }
+
+    fn read(&self, delay: usize) -> f32 {
+        let len = self.buffer.len();
+        self.buffer[(self.index + len - delay.clamp(1, len)) % len]
+    }
+
+    /// Retard non entier (interpolation linéaire) : suit sans saut un réglage lissé
+    fn read_fractional(&self, delay: f32) -> f32 {
+        let whole = delay as usize;
+        let frac = delay - whole as f32;
+        let (a, b) = (self.read(whole), self.read(whole + 1));
+        a + (b - a) * frac
+    }
+
+    fn write(&mut self, value: f32) {
+        self.buffer[self.index] = value;
+        self.index = (self.index + 1) % self.buffer.len();
+    }
+}
+
+/// Passe-tout de Schroeder pour la diffusion d'entrée
+struct Allpass {
+    line: DelayLine,
+    delay: usize,
+}
+
+impl Allpass {
+    fn process(&mut self, input: f32, gain: f32) -> f32 {
+        let delayed = self.line.read(self.delay);
+        let v = input + gain * delayed;
+        self.line.write(v);
+        delayed - gain * v
+    }
+}
+
+/// Réverbération FDN 8 lignes : pré-délai, diffusion d'entrée, matrice de Hadamard,
+/// amortissement des aigus dans la boucle
+pub struct Reverb {
+    id: usize,
+    pub size: f32,      // 0 à 1
+    pub decay: f32,     // RT60 en secondes
+    pub pre_delay: f32, // ms
+    pub damping: f32,   // 0 à 1
+    pub diffusion: f32, // 0 à 1
+    pub width: f32,     // 0 (mono) à 1
+    pub mix: Mix,
+    pre_delay_l: DelayLine,
+    pre_delay_r: DelayLine,
+    diffusers_l: Vec<Allpass>,
+    diffusers_r: Vec<Allpass>,
+    lines: Vec<DelayLine>,
+    line_lengths: [f32; LINES], // en samples, non entières : la taille est lissée
+    line_gains: [f32; LINES],
+    damping_state: [f32; LINES],
+}
+
+impl Reverb {
+    pub fn new(id: usize) -> Self {
+        let max_line = |ms: f32| DelayLine::new(ms_to_samples(ms * MAX_SIZE) + 1);
+        let diffusers = |offset: f32| {
+            DIFFUSER_LENGTHS_MS
+                .iter()
+                .map(|&ms| Allpass {
+                    line: DelayLine::new(ms_to_samples(ms + offset) + 1),
+                    delay: ms_to_samples(ms + offset),
+                })
+                .collect()
+        };
+
+        let mut reverb = Self {
+            id,
+            size: 0.5,
+            decay: 2.0,
+            pre_delay: 10.0,
+            damping: 0.4,
+            diffusion: 0.7,
+            width: 1.0,
+            mix: Mix { dry: 1.0, wet: 0.3 },
+            pre_delay_l: DelayLine::new(ms_to_samples(MAX_PRE_DELAY_MS) + 1),
+            pre_delay_r: DelayLine::new(ms_to_samples(MAX_PRE_DELAY_MS) + 1),
+            // décalage entre canaux pour décorréler la diffusion
+            diffusers_l: diffusers(0.0),
+            diffusers_r: diffusers(0.37),
+            lines: LINE_LENGTHS_MS.iter().map(|&ms| max_line(ms)).collect(),
+            line_lengths: [0.0; LINES],
+            line_gains: [0.0; LINES],
+            damping_state: [0.0; LINES],
+        };
+        reverb.update_lines();
+        reverb
+    }
+
+    pub fn set_size(&mut self, size: f32) {
+        self.size = size.clamp(0.0, 1.0);
+        self.update_lines();
+    }
+
+    pub fn set_decay(&mut self, decay: f32) {
+        self.decay = decay.clamp(0.1, 30.0);
+        self.update_lines();
+    }
+
+    pub fn set_pre_delay(&mut self, pre_delay: f32) {
+        self.pre_delay = pre_delay.clamp(0.0, MAX_PRE_DELAY_MS);
+    }
+
+    /// Longueurs des lignes selon la taille, et gains de boucle pour obtenir le RT60
+    fn update_lines(&mut self) {
+        let scale = 0.25 + self.size * (MAX_SIZE - 0.25);
+        for (i, &ms) in LINE_LENGTHS_MS.iter().enumerate() {
+            let length = (ms * scale / 1000.0 * SAMPLE_RATE).max(1.0);
+            self.line_lengths[i] = length;
+            // -60 dB après `decay` secondes
+            self.line_gains[i] = 10f32.powf(-3.0 * length / (self.decay * SAMPLE_RATE));
+        }
+    }
+}
+
+pub static REVERB_PARAMS: [ParamInfo; 8] = [
+    ParamInfo::new("Size", "", 0.0, 1.0, 0.5),
+    ParamInfo::new("Decay", "s", 0.1, 30.0, 2.0).logarithmic(),
+    ParamInfo::new("Pre-delay", "ms", 0.0, MAX_PRE_DELAY_MS, 10.0),
+    ParamInfo::new("Damping", "", 0.0, 0.99, 0.4),
+    ParamInfo::new("Diffusion", "", 0.0, 1.0, 0.7),
+    ParamInfo::new("Width", "", 0.0, 1.0, 1.0),
+    ParamInfo::dry(1.0),
+    ParamInfo::wet(0.3),
+];
+
+impl EffectTrait for Reverb {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn params(&self) -> &'static [ParamInfo] {
+        &REVERB_PARAMS
+    }
+
+    fn set_param(&mut self, index: u32, value: f32) -> bool {
+        match index {
+            0 => self.set_size(value),
+            1 => self.set_decay(value),
+            2 => self.set_pre_delay(value),
+            3 => self.damping = value,
+            4 => self.diffusion = value,
+            5 => self.width = value,
+            6 => self.mix.dry = value,
+            7 => self.mix.wet = value,
+            _ => return false,
+        }
+        true
+    }
+
+    fn process(&mut self, sample_l: &mut f32, sample_r: &mut f32) {
+        let pre_delay = self.pre_delay / 1000.0 * SAMPLE_RATE;
+        let mut in_l = self.pre_delay_l.read_fractional(pre_delay);
+        let mut in_r = self.pre_delay_r.read_fractional(pre_delay);
+        self.pre_delay_l.write(*sample_l);
+        self.pre_delay_r.write(*sample_r);
+
+        let diffusion = self.diffusion * 0.75;
+        for (diffuser_l, diffuser_r) in self.diffusers_l.iter_mut().zip(&mut self.diffusers_r) {
+            in_l = diffuser_l.process(in_l, diffusion);
+            in_r = diffuser_r.process(in_r, diffusion);
+        }
+
+        let mut outputs = [0.0; LINES];
+        for (i, output) in outputs.iter_mut().enumerate() {
+            let delayed = self.lines[i].read_fractional(self.line_lengths[i]);
+            // passe-bas à un pôle : les aigus s'éteignent plus vite
+            self.damping_state[i] = delayed + (self.damping_state[i] - delayed) * self.damping;
+            *output = self.damping_state[i] * self.line_gains[i];
+        }
+
+        // Hadamard 8x8 rapide, normalisée
+        let mut mixed = outputs;
+        let mut span = 1;
+        while span < LINES {
+            for block in (0..LINES).step_by(span * 2) {
+                for i in block..block + span {
+                    let (a, b) = (mixed[i], mixed[i + span]);
+                    mixed[i] = a + b;
+                    mixed[i + span] = a - b;
+                }
+            }
+            span *= 2;
+        }
+        let norm = 1.0 / (LINES as f32).sqrt();
+
+        for (i, line) in self.lines.iter_mut().enumerate() {
+            let input = if i % 2 == 0 { in_l } else { in_r };
+            line.write(mixed[i] * norm + input * 0.5);
+        }
+
+        let wet_l = outputs[0] + outputs[2] - outputs[4] + outputs[6];
+        let wet_r = outputs[1] + outputs[3] - outputs[5] + outputs[7];
+        let mid = (wet_l + wet_r) * 0.5;
+        let side = (wet_l - wet_r) * 0.5 * self.width;
+
+        *sample_l = self.mix.dry * *sample_l + self.mix.wet * (mid + side) * 0.5;
+        *sample_r = self.mix.dry * *sample_r + self.mix.wet * (mid - side) * 0.5;
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
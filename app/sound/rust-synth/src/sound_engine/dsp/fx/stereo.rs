@@ -0,0 +1,148 @@
+use std::any::Any;
+
+use crate::{
+    sound_engine::dsp::fx::{
+        BiquadCoeffs, BiquadState, EffectTrait, MemoryBuffer, params::ParamInfo,
+    },
+    utils::constants::SAMPLE_RATE,
+};
+
+const MAX_HAAS_MS: f32 = 30.0;
+const MAX_WIDTH: f32 = 2.0;
+
+/// Outil stéréo : inversions de polarité, permutation, retard de Haas puis traitement
+/// mid/side (largeur, graves en mono sous `bass_mono`) et enfin somme mono de contrôle.
+pub struct StereoTool {
+    id: usize,
+    pub width: f32,     // 0 : mono, 1 : inchangé, 2 : side doublé
+    pub haas: f32,      // ms, négatif retarde le canal gauche, positif le droit
+    pub bass_mono: f32, // Hz, 0 : désactivé
+    pub swap: bool,
+    pub invert_left: bool,
+    pub invert_right: bool,
+    pub mono: bool,
+    crossover: BiquadCoeffs,
+    side_states: [BiquadState; 2], // passe-haut Linkwitz-Riley 24 dB/oct sur le side
+    memory: MemoryBuffer,
+}
+
+impl StereoTool {
+    pub fn new(id: usize) -> Self {
+        Self {
+            id,
+            width: 1.0,
+            haas: 0.0,
+            bass_mono: 0.0,
+            swap: false,
+            invert_left: false,
+            invert_right: false,
+            mono: false,
+            crossover: BiquadCoeffs::calc_coeffs_for_highpass(120.0, 0.707),
+            side_states: [BiquadState::default(); 2],
+            memory: MemoryBuffer::new(SAMPLE_RATE as usize, MAX_HAAS_MS / 1000.0 + 0.01),
+        }
+    }
+
+    pub fn set_width(&mut self, width: f32) {
+        self.width = width.clamp(0.0, MAX_WIDTH);
+    }
+
+    pub fn set_haas(&mut self, ms: f32) {
+        self.haas = ms.clamp(-MAX_HAAS_MS, MAX_HAAS_MS);
+    }
+
+    pub fn set_bass_mono(&mut self, frequency: f32) {
+        self.bass_mono = if frequency < 20.0 {
+            0.0
+        } else {
+            frequency.min(500.0)
+        };
+        if self.bass_mono > 0.0 {
+            self.crossover = BiquadCoeffs::calc_coeffs_for_highpass(self.bass_mono, 0.707);
+        }
+    }
+}
+
+pub static STEREO_TOOL_PARAMS: [ParamInfo; 7] = [
+    ParamInfo::new("Width", "", 0.0, MAX_WIDTH, 1.0),
+    ParamInfo::new("Haas", "ms", -MAX_HAAS_MS, MAX_HAAS_MS, 0.0),
+    // sous 20 Hz : désactivé
+    ParamInfo::new("Bass mono", "Hz", 0.0, 500.0, 0.0),
+    ParamInfo::toggle("Swap", false),
+    ParamInfo::toggle("Invert left", false),
+    ParamInfo::toggle("Invert right", false),
+    ParamInfo::toggle("Mono", false),
+];
+
+impl EffectTrait for StereoTool {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn params(&self) -> &'static [ParamInfo] {
+        &STEREO_TOOL_PARAMS
+    }
+
+    fn set_param(&mut self, index: u32, value: f32) -> bool {
+        match index {
+            0 => self.set_width(value),
+            1 => self.set_haas(value),
+            2 => self.set_bass_mono(value),
+            3 => self.swap = value >= 0.5,
+            4 => self.invert_left = value >= 0.5,
+            5 => self.invert_right = value >= 0.5,
+            6 => self.mono = value >= 0.5,
+            _ => return false,
+        }
+        true
+    }
+
+    fn process(&mut self, sample_l: &mut f32, sample_r: &mut f32) {
+        let mut l = if self.invert_left {
+            -*sample_l
+        } else {
+            *sample_l
+        };
+        let mut r = if self.invert_right {
+            -*sample_r
+        } else {
+            *sample_r
+        };
+        if self.swap {
+            std::mem::swap(&mut l, &mut r);
+        }
+
+        self.memory.write(l, r);
+        // retard non entier : le réglage lissé glisse sans saut d'un sample à l'autre
+        let delay = self.haas.abs() / 1000.0 * SAMPLE_RATE;
+        if delay > 0.0 {
+            // read_fractional(1.0) est le sample qui vient d'être écrit
+            let (delayed_l, delayed_r) = self.memory.read_fractional(delay + 1.0);
+            if self.haas < 0.0 {
+                l = delayed_l;
+            } else {
+                r = delayed_r;
+            }
+        }
+
+        let mid = 0.5 * (l + r);
+        let mut side = 0.5 * (l - r) * self.width;
+        if self.bass_mono > 0.0 {
+            for state in &mut self.side_states {
+                side = state.process(&self.crossover, side);
+            }
+        }
+
+        if self.mono {
+            *sample_l = mid;
+            *sample_r = mid;
+        } else {
+            *sample_l = mid + side;
+            *sample_r = mid - side;
+        }
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
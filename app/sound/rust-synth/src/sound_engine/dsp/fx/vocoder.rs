@@ -0,0 +1,367 @@
+use std::any::Any;
+
+use crate::{
+    sound_engine::dsp::fx::{
+        BiquadCoeffs, BiquadState, EffectTrait, SideInputs, params::ParamInfo,
+    },
+    utils::{
+        constants::SAMPLE_RATE,
+        types::{Mix, Sample},
+    },
+};
+
+pub const MAX_VOCODER_BANDS: usize = 32;
+const MIN_VOCODER_BANDS: usize = 4;
+
+fn coefficient(ms: f32) -> f32 {
+    (-1.0 / (ms.max(0.1) * 0.001 * SAMPLE_RATE)).exp()
+}
+
+/// Répartition des bornes de bandes entre `low` et `high`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BandSpacing {
+    Logarithmic = 0,
+    Mel = 1, // plus serré dans les graves que le linéaire, moins que le log
+    Linear = 2,
+}
+
+impl TryFrom<u32> for BandSpacing {
+    type Error = ();
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(BandSpacing::Logarithmic),
+            1 => Ok(BandSpacing::Mel),
+            2 => Ok(BandSpacing::Linear),
+            _ => Err(()),
+        }
+    }
+}
+
+impl BandSpacing {
+    /// Fréquence de la borne à `t` (0 à 1) entre `low` et `high`
+    fn edge(self, low: f32, high: f32, t: f32) -> f32 {
+        let mel = |f: f32| 2595.0 * (1.0 + f / 700.0).log10();
+        match self {
+            BandSpacing::Logarithmic => low * (high / low).powf(t),
+            BandSpacing::Mel => {
+                let m = mel(low) + (mel(high) - mel(low)) * t;
+                700.0 * (10f32.powf(m / 2595.0) - 1.0)
+            }
+            BandSpacing::Linear => low + (high - low) * t,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModulatorSource {
+    Sample = 0,     // sample du SampleManager joué en boucle
+    AudioInput = 1, // entrée audio du navigateur (micro...)
+}
+
+impl TryFrom<u32> for ModulatorSource {
+    type Error = ();
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ModulatorSource::Sample),
+            1 => Ok(ModulatorSource::AudioInput),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Passe-bande 4 pôles (deux sections identiques) sur le modulateur et sur chaque canal
+/// de la porteuse
+#[derive(Debug, Clone, Copy)]
+struct VocoderBand {
+    coeffs: BiquadCoeffs,
+    modulator: [BiquadState; 2],
+    carrier: [[BiquadState; 2]; 2], // [canal][section]
+    envelope: f32,
+}
+
+impl VocoderBand {
+    fn new(low: f32, high: f32) -> Self {
+        let mut band = Self {
+            coeffs: BiquadCoeffs::calc_coeffs_for_bandpass(1000.0, 1.0),
+            modulator: [BiquadState::default(); 2],
+            carrier: [[BiquadState::default(); 2]; 2],
+            envelope: 0.0,
+        };
+        band.set_edges(low, high);
+        band
+    }
+
+    fn set_edges(&mut self, low: f32, high: f32) {
+        let center = (low * high).sqrt();
+        let q = center / (high - low).max(1.0);
+        self.coeffs = BiquadCoeffs::calc_coeffs_for_bandpass(center, q);
+    }
+}
+
+/// Vocodeur à canaux : l'entrée de l'effet (le synthé, placé en tête de chaîne) sert de
+/// porteuse, filtrée par bande puis modulée par l'enveloppe de la même bande du modulateur.
+/// Le bruit ajouté à la porteuse laisse passer les consonnes non voisées.
+/// La porteuse n'étant que l'entrée de l'insert, le vocodeur s'emploie sur la tranche d'un
+/// sampler ou sur le master ; sur un bus, elle se réduit aux envois qui y arrivent.
+pub struct Vocoder {
+    id: usize,
+    pub source: ModulatorSource,
+    pub band_count: usize,
+    pub spacing: BandSpacing,
+    pub low: f32,    // Hz, borne basse de la première bande
+    pub high: f32,   // Hz, borne haute de la dernière
+    pub attack: f32, // ms
+    pub release: f32,
+    pub noise: f32,  // 0 à 1
+    pub output: f32, // dB
+    pub mix: Mix,
+    bands: [VocoderBand; MAX_VOCODER_BANDS], // seules les `band_count` premières servent
+    output_gain: f32,
+    attack_coeff: f32,
+    release_coeff: f32,
+    modulator_sample: Option<Sample>,
+    position: f64, // en frames du sample modulateur
+    input: [f32; 2],
+    noise_seed: u32,
+}
+
+impl Vocoder {
+    pub fn new(id: usize) -> Self {
+        let mut vocoder = Self {
+            id,
+            source: ModulatorSource::Sample,
+            band_count: 16,
+            spacing: BandSpacing::Logarithmic,
+            low: 100.0,
+            high: 8000.0,
+            attack: 5.0,
+            release: 50.0,
+            noise: 0.05,
+            output: 6.0,
+            mix: Mix { dry: 0.0, wet: 1.0 },
+            bands: [VocoderBand::new(100.0, 8000.0); MAX_VOCODER_BANDS],
+            output_gain: 1.0,
+            attack_coeff: 0.0,
+            release_coeff: 0.0,
+            modulator_sample: None,
+            position: 0.0,
+            input: [0.0; 2],
+            noise_seed: 0x9E37_79B9,
+        };
+        vocoder.set_attack(5.0);
+        vocoder.set_release(50.0);
+        vocoder.update_bands();
+        vocoder.set_output(6.0);
+        vocoder
+    }
+
+    /// `None` (sample introuvable) coupe le modulateur
+    pub fn set_modulator_sample(&mut self, sample: Option<Sample>) {
+        self.modulator_sample = sample;
+        self.position = 0.0;
+    }
+
+    pub fn set_source(&mut self, source: f32) {
+        if let Ok(source) = ModulatorSource::try_from(source as u32) {
+            self.source = source;
+        }
+    }
+
+    pub fn set_band_count(&mut self, bands: f32) {
+        let previous = self.band_count;
+        self.band_count = (bands as usize).clamp(MIN_VOCODER_BANDS, MAX_VOCODER_BANDS);
+        // les bandes réactivées repartent de zéro
+        for band in self.bands.iter_mut().take(self.band_count).skip(previous) {
+            *band = VocoderBand::new(self.low, self.high);
+        }
+        self.update_bands();
+        self.set_output(self.output);
+    }
+
+    pub fn set_spacing(&mut self, spacing: f32) {
+        if let Ok(spacing) = BandSpacing::try_from(spacing as u32) {
+            self.spacing = spacing;
+            self.update_bands();
+        }
+    }
+
+    pub fn set_range(&mut self, low: f32, high: f32) {
+        self.low = low.clamp(20.0, 2000.0);
+        self.high = high.clamp(self.low * 2.0, SAMPLE_RATE * 0.45);
+        self.update_bands();
+    }
+
+    pub fn set_attack(&mut self, ms: f32) {
+        self.attack = ms.clamp(0.1, 500.0);
+        self.attack_coeff = coefficient(self.attack);
+    }
+
+    pub fn set_release(&mut self, ms: f32) {
+        self.release = ms.clamp(1.0, 2000.0);
+        self.release_coeff = coefficient(self.release);
+    }
+
+    /// Chaque bande ne garde qu'une partie de l'énergie des deux signaux : compensé ici
+    pub fn set_output(&mut self, db: f32) {
+        self.output = db;
+        self.output_gain = 10f32.powf(db / 20.0) * (self.band_count as f32).sqrt();
+    }
+
+    /// Recalcule les coefficients des bandes actives, sans toucher à leurs états
+    fn update_bands(&mut self) {
+        let count = self.band_count;
+        for (index, band) in self.bands.iter_mut().take(count).enumerate() {
+            let low = self
+                .spacing
+                .edge(self.low, self.high, index as f32 / count as f32);
+            let high = self
+                .spacing
+                .edge(self.low, self.high, (index + 1) as f32 / count as f32);
+            band.set_edges(low, high);
+        }
+    }
+
+    /// Sample modulateur (mono) lu en boucle et ramené à `SAMPLE_RATE`
+    fn next_sample_frame(&mut self) -> f32 {
+        let Some(sample) = self.modulator_sample.as_ref() else {
+            return 0.0;
+        };
+        let frames = sample.frames();
+        if frames == 0 {
+            return 0.0;
+        }
+
+        let index = self.position as usize;
+        let frac = (self.position - index as f64) as f32;
+        let next = (index + 1) % frames;
+        let channels = sample.channels as usize;
+        let mut value = 0.0;
+        for channel in 0..channels {
+            let values = &sample.values[channel * frames..(channel + 1) * frames];
+            value += values[index] + (values[next] - values[index]) * frac;
+        }
+
+        self.position += (sample.sample_rate / SAMPLE_RATE) as f64;
+        if self.position >= frames as f64 {
+            self.position -= frames as f64;
+        }
+        value * sample.gain / channels as f32
+    }
+
+    fn next_noise(&mut self) -> f32 {
+        // xorshift32, suffisant pour un bruit blanc
+        self.noise_seed ^= self.noise_seed << 13;
+        self.noise_seed ^= self.noise_seed >> 17;
+        self.noise_seed ^= self.noise_seed << 5;
+        self.noise_seed as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
+
+pub static VOCODER_PARAMS: [ParamInfo; 12] = [
+    ParamInfo::choice("Source", &["sample", "audio input"], 0),
+    ParamInfo::sample("Modulator"),
+    ParamInfo::new(
+        "Bands",
+        "",
+        MIN_VOCODER_BANDS as f32,
+        MAX_VOCODER_BANDS as f32,
+        16.0,
+    )
+    .stepped(),
+    ParamInfo::choice("Spacing", &["logarithmic", "mel", "linear"], 0),
+    ParamInfo::new("Low", "Hz", 20.0, 2000.0, 100.0)
+        .logarithmic()
+        .instant(),
+    ParamInfo::new("High", "Hz", 40.0, 19_845.0, 8000.0)
+        .logarithmic()
+        .instant(),
+    ParamInfo::new("Attack", "ms", 0.1, 500.0, 5.0).logarithmic(),
+    ParamInfo::new("Release", "ms", 1.0, 2000.0, 50.0).logarithmic(),
+    ParamInfo::new("Noise", "", 0.0, 1.0, 0.05),
+    ParamInfo::new("Output", "dB", -24.0, 24.0, 6.0),
+    ParamInfo::dry(0.0),
+    ParamInfo::wet(1.0),
+];
+
+impl EffectTrait for Vocoder {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn params(&self) -> &'static [ParamInfo] {
+        &VOCODER_PARAMS
+    }
+
+    fn set_param(&mut self, index: u32, value: f32) -> bool {
+        match index {
+            0 => self.set_source(value),
+            // le modulateur (index 1) passe par `set_sample`
+            2 => self.set_band_count(value),
+            3 => self.set_spacing(value),
+            4 => self.set_range(value, self.high),
+            5 => self.set_range(self.low, value),
+            6 => self.set_attack(value),
+            7 => self.set_release(value),
+            8 => self.noise = value,
+            9 => self.set_output(value),
+            10 => self.mix.dry = value,
+            11 => self.mix.wet = value,
+            _ => return false,
+        }
+        true
+    }
+
+    fn set_sample(&mut self, index: u32, sample: Option<Sample>) -> bool {
+        if index != 1 {
+            return false;
+        }
+        self.set_modulator_sample(sample);
+        true
+    }
+
+    fn process(&mut self, sample_l: &mut f32, sample_r: &mut f32) {
+        let modulator = match self.source {
+            ModulatorSource::Sample => self.next_sample_frame(),
+            ModulatorSource::AudioInput => 0.5 * (self.input[0] + self.input[1]),
+        };
+        let noise = self.next_noise() * self.noise;
+        let carrier = [*sample_l + noise, *sample_r + noise];
+
+        let mut wet = [0.0; 2];
+        for band in &mut self.bands[..self.band_count] {
+            let level = band
+                .modulator
+                .iter_mut()
+                .fold(modulator, |x, state| state.process(&band.coeffs, x))
+                .abs();
+            let coeff = if level > band.envelope {
+                self.attack_coeff
+            } else {
+                self.release_coeff
+            };
+            band.envelope = level + coeff * (band.envelope - level);
+
+            for (channel, value) in wet.iter_mut().enumerate() {
+                let filtered = band.carrier[channel]
+                    .iter_mut()
+                    .fold(carrier[channel], |x, state| state.process(&band.coeffs, x));
+                *value += filtered * band.envelope;
+            }
+        }
+
+        let gain = self.output_gain;
+        *sample_l = self.mix.dry * *sample_l + self.mix.wet * wet[0] * gain;
+        *sample_r = self.mix.dry * *sample_r + self.mix.wet * wet[1] * gain;
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn process_with_inputs(&mut self, sample_l: &mut f32, sample_r: &mut f32, inputs: &SideInputs) {
+        self.input = inputs.audio_input;
+        self.process(sample_l, sample_r);
+    }
+}
@@ -0,0 +1,275 @@
+use std::any::Any;
+
+use crate::{
+    sound_engine::dsp::fx::{EffectTrait, params::ParamInfo},
+    utils::{constants::SAMPLE_RATE, types::Mix},
+};
+
+// glissement de la coupure vers sa cible (~5 ms), évite les sauts de coefficients
+const CUTOFF_GLIDE: f32 = 0.005;
+
+/// Gain de l'intégrateur TPT, coupure bornée pour que tan() reste fini
+fn prewarp(cutoff: f32) -> f32 {
+    let cutoff = cutoff.clamp(20.0, SAMPLE_RATE * 0.45);
+    (std::f32::consts::PI * cutoff / SAMPLE_RATE).tan()
+}
+
+/// Coupure lissée à l'échantillon : `g` n'est recalculé que pendant un glissement
+struct Cutoff {
+    target: f32,
+    current: f32,
+    g: f32,
+}
+
+impl Cutoff {
+    fn new(frequency: f32) -> Self {
+        Self {
+            target: frequency,
+            current: frequency,
+            g: prewarp(frequency),
+        }
+    }
+
+    fn next(&mut self) -> f32 {
+        if self.current != self.target {
+            let glide = 1.0 - (-1.0 / (CUTOFF_GLIDE * SAMPLE_RATE)).exp();
+            self.current += (self.target - self.current) * glide;
+            if (self.target - self.current).abs() < 0.01 {
+                self.current = self.target;
+            }
+            self.g = prewarp(self.current);
+        }
+        self.g
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SvfOutputs {
+    pub low: f32,
+    pub band: f32,
+    pub high: f32,
+}
+
+/// Filtre d'état TPT (Zavalishin / Simper), mono : stable quel que soit le rythme de
+/// modulation de `g` puisque l'état ne dépend pas des coefficients
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Svf {
+    ic1eq: f32,
+    ic2eq: f32,
+}
+
+impl Svf {
+    /// `g` : gain prewarpé (voir `prewarp`), `k` : amortissement (1 / Q)
+    pub fn process(&mut self, g: f32, k: f32, input: f32) -> SvfOutputs {
+        let a1 = 1.0 / (1.0 + g * (g + k));
+        let a2 = g * a1;
+        let a3 = g * a2;
+
+        let v3 = input - self.ic2eq;
+        let v1 = a1 * self.ic1eq + a2 * v3;
+        let v2 = self.ic2eq + a2 * self.ic1eq + a3 * v3;
+        self.ic1eq = 2.0 * v1 - self.ic1eq;
+        self.ic2eq = 2.0 * v2 - self.ic2eq;
+
+        SvfOutputs {
+            low: v2,
+            band: v1,
+            high: input - k * v1 - v2,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// SVF dont les trois sorties sont mélangées : LP + HP donne un notch, LP - HP un peak...
+pub struct StateVariableFilter {
+    id: usize,
+    cutoff: Cutoff,
+    pub resonance: f32, // Q
+    pub low: f32,
+    pub band: f32,
+    pub high: f32,
+    pub mix: Mix,
+    filters: [Svf; 2],
+}
+
+impl StateVariableFilter {
+    pub fn new(id: usize) -> Self {
+        Self {
+            id,
+            cutoff: Cutoff::new(1000.0),
+            resonance: 0.707,
+            low: 1.0,
+            band: 0.0,
+            high: 0.0,
+            mix: Mix { dry: 0.0, wet: 1.0 },
+            filters: [Svf::default(); 2],
+        }
+    }
+
+    pub fn set_cutoff(&mut self, frequency: f32) {
+        self.cutoff.target = frequency.clamp(20.0, SAMPLE_RATE * 0.45);
+    }
+
+    pub fn set_resonance(&mut self, q: f32) {
+        self.resonance = q.clamp(0.5, 40.0);
+    }
+}
+
+pub static SVF_PARAMS: [ParamInfo; 7] = [
+    ParamInfo::new("Cutoff", "Hz", 20.0, 19_845.0, 1000.0).logarithmic(),
+    ParamInfo::new("Resonance", "", 0.5, 40.0, 0.707).logarithmic(),
+    ParamInfo::new("Low", "", -1.0, 1.0, 1.0),
+    ParamInfo::new("Band", "", -1.0, 1.0, 0.0),
+    ParamInfo::new("High", "", -1.0, 1.0, 0.0),
+    ParamInfo::dry(0.0),
+    ParamInfo::wet(1.0),
+];
+
+impl EffectTrait for StateVariableFilter {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn params(&self) -> &'static [ParamInfo] {
+        &SVF_PARAMS
+    }
+
+    fn set_param(&mut self, index: u32, value: f32) -> bool {
+        match index {
+            0 => self.set_cutoff(value),
+            1 => self.set_resonance(value),
+            2 => self.low = value,
+            3 => self.band = value,
+            4 => self.high = value,
+            5 => self.mix.dry = value,
+            6 => self.mix.wet = value,
+            _ => return false,
+        }
+        true
+    }
+
+    fn process(&mut self, sample_l: &mut f32, sample_r: &mut f32) {
+        let g = self.cutoff.next();
+        let k = 1.0 / self.resonance;
+
+        for (filter, sample) in self.filters.iter_mut().zip([sample_l, sample_r]) {
+            let outputs = filter.process(g, k, *sample);
+            let wet = outputs.low * self.low + outputs.band * self.band + outputs.high * self.high;
+            *sample = self.mix.dry * *sample + self.mix.wet * wet;
+        }
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Ladder type Moog à 4 pôles TPT : la boucle de contre-réaction est résolue
+/// linéairement puis saturée par tanh, ce qui borne l'auto-oscillation
+pub struct LadderFilter {
+    id: usize,
+    cutoff: Cutoff,
+    pub resonance: f32, // 0 à 1.1, auto-oscillation à partir de 1
+    pub drive: f32,     // dB
+    pub poles: usize,   // 2 (12 dB/oct) ou 4 (24 dB/oct)
+    pub mix: Mix,
+    states: [[f32; 4]; 2],
+}
+
+impl LadderFilter {
+    pub fn new(id: usize) -> Self {
+        Self {
+            id,
+            cutoff: Cutoff::new(1000.0),
+            resonance: 0.3,
+            drive: 0.0,
+            poles: 4,
+            mix: Mix { dry: 0.0, wet: 1.0 },
+            states: [[0.0; 4]; 2],
+        }
+    }
+
+    pub fn set_cutoff(&mut self, frequency: f32) {
+        self.cutoff.target = frequency.clamp(20.0, SAMPLE_RATE * 0.45);
+    }
+
+    pub fn set_poles(&mut self, poles: f32) {
+        self.poles = if poles < 3.0 { 2 } else { 4 };
+    }
+
+    fn process_channel(&mut self, channel: usize, g: f32, input: f32) -> f32 {
+        let big_g = g / (1.0 + g);
+        let k = 4.0 * self.resonance;
+        let drive = 10f32.powf(self.drive / 20.0);
+        let states = &mut self.states[channel];
+
+        // y4 = G⁴ u + Σ : contribution des états de chaque étage
+        let sigma = states
+            .iter()
+            .fold(0.0, |acc, state| acc * big_g + state * (1.0 - big_g));
+        let g4 = big_g.powi(4);
+        let y4 = (g4 * input * drive + sigma) / (1.0 + k * g4);
+
+        let mut stage_input = (input * drive - k * y4).tanh();
+        let mut taps = [0.0; 4];
+        for (state, tap) in states.iter_mut().zip(taps.iter_mut()) {
+            let v = (stage_input - *state) * big_g;
+            let output = v + *state;
+            *state = output + v;
+            *tap = output;
+            stage_input = output;
+        }
+
+        // compensation partielle de la perte de graves due à la résonance
+        let output = if self.poles == 2 { taps[1] } else { taps[3] };
+        output * (1.0 + 0.5 * k) / drive.sqrt()
+    }
+}
+
+pub static LADDER_PARAMS: [ParamInfo; 6] = [
+    ParamInfo::new("Cutoff", "Hz", 20.0, 19_845.0, 1000.0).logarithmic(),
+    ParamInfo::new("Resonance", "", 0.0, 1.1, 0.3),
+    ParamInfo::new("Drive", "dB", 0.0, 24.0, 0.0),
+    ParamInfo::new("Poles", "", 2.0, 4.0, 4.0).stepped(),
+    ParamInfo::dry(0.0),
+    ParamInfo::wet(1.0),
+];
+
+impl EffectTrait for LadderFilter {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn params(&self) -> &'static [ParamInfo] {
+        &LADDER_PARAMS
+    }
+
+    fn set_param(&mut self, index: u32, value: f32) -> bool {
+        match index {
+            0 => self.set_cutoff(value),
+            1 => self.resonance = value,
+            2 => self.drive = value,
+            3 => self.set_poles(value),
+            4 => self.mix.dry = value,
+            5 => self.mix.wet = value,
+            _ => return false,
+        }
+        true
+    }
+
+    fn process(&mut self, sample_l: &mut f32, sample_r: &mut f32) {
+        let g = self.cutoff.next();
+        let wet_l = self.process_channel(0, g, *sample_l);
+        let wet_r = self.process_channel(1, g, *sample_r);
+
+        *sample_l = self.mix.dry * *sample_l + self.mix.wet * wet_l;
+        *sample_r = self.mix.dry * *sample_r + self.mix.wet * wet_r;
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -1,91 +1,754 @@
-use std::iter::Filter;
-
 use web_sys::console;
 
 use crate::{
-    sound_engine::dsp::fx::{BiquadFilter, Echo, EchoParams, EffectTrait},
-    utils::{toolkit::ToolKit, types::Mix},
+    sound_engine::{
+        dsp::fx::{
+            BlockInputs, EffectTrait, EffectsEnum,
+            convolution::{Convolution, ImpulseRequest, ImpulseResponse},
+            equalizer::Equalizer,
+            params::ParamInfo,
+        },
+        dsp::sidechain::{SidechainSource, SidechainTaps},
+        dsp::smoothing::{MAX_RAMP_MS, SMOOTHING_BLOCK, SmoothedParam},
+        synthetizer::sampler::Sampler,
+    },
+    utils::{
+        constants::{CHANNEL_CHAIN_BASE, MASTER_LIMITER_ID, MAX_FX_BUSES, SAMPLE_RATE},
+        types::Sample,
+    },
 };
 
+const FADE_MS: f32 = 10.0; // bypass, mute et solo
+
+/// Avance d'un sample la rampe `value` vers `target`
+fn fade_toward(value: f32, target: f32) -> f32 {
+    let step = 1000.0 / (FADE_MS * SAMPLE_RATE);
+    if target > value {
+        (value + step).min(target)
+    } else {
+        (value - step).max(target)
+    }
+}
+
+/// Effet de la chaîne et ce que le mixer applique autour, identique pour tous les effets :
+/// bypass en fondu, dosage avec le signal entrant, gain de sortie de l'effet et lissage
+/// de ses paramètres continus
+pub struct EffectSlot {
+    pub effect: Box<dyn EffectTrait>,
+    pub bypass: bool,
+    pub mix: SmoothedParam, // 0 : signal entrant seul, 1 : sortie de l'effet seule
+    pub gain: SmoothedParam, // linéaire, appliqué à la sortie de l'effet seulement
+    pub sidechain: SidechainSource,
+    params: Vec<Option<SmoothedParam>>, // par index de paramètre, `None` : appliqué tel quel
+    fade: f32,                          // 0 : bypassé, 1 : actif
+    dry: Vec<[f32; 2]>,                 // copie du bloc entrant pour le dosage
+}
+
+impl EffectSlot {
+    /// Les paramètres lissés partent de leur valeur par défaut, celle que l'effet a reçue
+    /// de `EffectsEnum::create`
+    fn new(effect: Box<dyn EffectTrait>) -> Self {
+        let params = effect
+            .params()
+            .iter()
+            .map(|info| {
+                info.is_smoothed()
+                    .then(|| SmoothedParam::new(info.to_ramp(info.default)))
+            })
+            .collect();
+        Self {
+            effect,
+            bypass: false,
+            mix: SmoothedParam::new(1.0),
+            gain: SmoothedParam::new(1.0),
+            sidechain: SidechainSource::None,
+            params,
+            fade: 1.0,
+            dry: Vec::new(),
+        }
+    }
+
+    /// Paramètre de l'effet ramené dans sa plage, rejoint par une rampe s'il est continu
+    fn set_param(&mut self, index: u32, value: f32) -> bool {
+        let Some(info) = self.effect.params().get(index as usize) else {
+            return false;
+        };
+        let value = info.constrain(value);
+        match self.params.get_mut(index as usize) {
+            Some(Some(param)) => {
+                param.set(info.to_ramp(value));
+                true
+            }
+            _ => self.effect.set_param(index, value),
+        }
+    }
+
+    /// Durée des rampes des paramètres, du dosage et du gain
+    fn set_ramp(&mut self, ms: f32) {
+        for param in self.params.iter_mut().flatten() {
+            param.set_ramp(ms);
+        }
+        self.mix.set_ramp(ms);
+        self.gain.set_ramp(ms);
+    }
+
+    fn is_smoothing(&self) -> bool {
+        self.params
+            .iter()
+            .flatten()
+            .any(SmoothedParam::is_smoothing)
+    }
+
+    /// Avance les rampes en cours de `frames` et transmet les nouvelles valeurs à l'effet
+    /// (un filtre recalcule alors ses coefficients)
+    fn advance_params(&mut self, frames: usize) {
+        let infos = self.effect.params();
+        for (index, (info, param)) in infos.iter().zip(&mut self.params).enumerate() {
+            if let Some(param) = param.as_mut().filter(|param| param.is_smoothing()) {
+                let value = info.constrain(info.from_ramp(param.advance(frames)));
+                self.effect.set_param(index as u32, value);
+            }
+        }
+    }
+
+    /// Un effet bypassé n'est plus calculé une fois le fondu terminé : son état (traîne
+    /// d'écho...) reste figé jusqu'à sa réactivation. Pendant une rampe, le bloc est traité
+    /// par sous-blocs de `SMOOTHING_BLOCK` frames entre lesquels les paramètres avancent
+    fn process_block(&mut self, frames: &mut [[f32; 2]], input: &[[f32; 2]], taps: &SidechainTaps) {
+        let target = if self.bypass { 0.0 } else { 1.0 };
+        if self.fade == 0.0 && target == 0.0 {
+            self.advance_params(frames.len());
+            return;
+        }
+
+        self.dry.clear();
+        self.dry.extend_from_slice(frames);
+        let inputs = BlockInputs {
+            audio_input: input,
+            sidechain: taps.get(self.sidechain),
+        };
+        if self.is_smoothing() {
+            for (index, chunk) in frames.chunks_mut(SMOOTHING_BLOCK).enumerate() {
+                self.advance_params(chunk.len());
+                let inputs = inputs.from_frame(index * SMOOTHING_BLOCK);
+                self.effect.process_block(chunk, &inputs);
+            }
+        } else {
+            self.effect.process_block(frames, &inputs);
+        }
+
+        for (frame, dry) in frames.iter_mut().zip(&self.dry) {
+            self.fade = fade_toward(self.fade, target);
+            let amount = self.mix.next_value() * self.fade;
+            let gain = self.gain.next_value();
+            for (sample, dry) in frame.iter_mut().zip(dry) {
+                *sample = dry + (*sample * gain - dry) * amount;
+            }
+        }
+    }
+}
+
+/// Tranche d'un sampler : inserts propres, mute et solo. Le fader, le pan et les envois
+/// sont ceux du `Sampler`, appliqués après les inserts
+pub struct ChannelStrip {
+    pub sampler_id: u8,
+    pub effects: Vec<EffectSlot>,
+    pub mute: bool,
+    pub solo: bool,
+    level: f32, // rampe de mute / solo
+}
+
+impl ChannelStrip {
+    fn new(sampler_id: u8) -> Self {
+        Self {
+            sampler_id,
+            effects: Vec::new(),
+            mute: false,
+            solo: false,
+            level: 1.0,
+        }
+    }
+}
+
+/// Bus auxiliaire : reçoit les envois des samplers, passe par sa propre chaîne d'effets
+/// et revient sur le master avant la chaîne du master
+pub struct Bus {
+    pub effects: Vec<EffectSlot>,
+    pub return_level: SmoothedParam, // 0 à 1
+}
+
 pub struct Mixer {
-    pub effects: Vec<Box<dyn EffectTrait>>,
-    pub ECHO_DEFAULT_PRESET: EchoParams,
+    pub channels: Vec<ChannelStrip>,
+    pub effects: Vec<EffectSlot>, // inserts du master
+    pub buses: Vec<Bus>,
+    /// Limiteur après la chaîne master, garde la sortie sous 0 dBFS. Hors des chaînes (ni
+    /// déplacé ni supprimé), réglé comme les autres effets via `MASTER_LIMITER_ID` ; sa
+    /// réduction est publiée à part (`take_gain_reductions`)
+    pub master_limiter: EffectSlot,
+    pub tempo: f32,               // BPM de l'hôte, transmis aux effets synchronisables
+    pub taps: SidechainTaps,      // sources de sidechain du bloc en cours
+    channel_block: Vec<[f32; 2]>, // bloc de la tranche en cours
+    sends: [Vec<[f32; 2]>; MAX_FX_BUSES], // envois du bloc vers chaque bus
+}
+
+impl Default for Mixer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Mixer {
     pub fn new() -> Self {
-        let mut mixer = Self {
+        Self {
+            channels: Vec::new(),
             effects: Vec::new(),
-            ECHO_DEFAULT_PRESET: EchoParams {
-                delay: ToolKit::convert_ms_to_sample(300.0),
-                feedback: 0.7,
-                l_delay_offset: ToolKit::convert_ms_to_sample(10.0),
-                r_delay_offset: ToolKit::convert_ms_to_sample(50.0),
-                mix: Mix { dry: 1.0, wet: 0.7 },
-            },
+            buses: (0..MAX_FX_BUSES)
+                .map(|_| Bus {
+                    effects: Vec::new(),
+                    return_level: SmoothedParam::new(1.0),
+                })
+                .collect(),
+            master_limiter: EffectSlot::new(
+                EffectsEnum::Limiter.create(MASTER_LIMITER_ID as usize),
+            ),
+            tempo: 120.0,
+            taps: SidechainTaps::default(),
+            channel_block: Vec::new(),
+            sends: Default::default(),
+        }
+    }
+    /// Mixe un bloc entier : tranches des samplers, bus puis master. `channel_frames` :
+    /// sortie avant fader de chaque sampler, frame par frame ; `input` : entrée audio
+    /// capturée sur le même bloc
+    pub fn render_block(
+        &mut self,
+        output: &mut [[f32; 2]],
+        channel_frames: &[[f32; 2]],
+        samplers: &mut [Sampler],
+        input: &[[f32; 2]],
+    ) {
+        self.taps.advance(output.len());
+        output.fill([0.0; 2]);
+        self.mix_channels(output, channel_frames, samplers, input);
+
+        // les bus tournent même sans envoi pour laisser finir les traînes
+        for (index, (bus, block)) in self.buses.iter_mut().zip(&mut self.sends).enumerate() {
+            for slot in &mut bus.effects {
+                slot.process_block(block, input, &self.taps);
+            }
+            self.taps.set_bus(index, block);
+            for (out, frame) in output.iter_mut().zip(block.iter()) {
+                let level = bus.return_level.next_value();
+                out[0] += frame[0] * level;
+                out[1] += frame[1] * level;
+            }
+        }
+
+        for slot in &mut self.effects {
+            slot.process_block(output, input, &self.taps);
+        }
+        self.master_limiter.process_block(output, input, &self.taps);
+    }
+
+    /// Passe la sortie avant fader de chaque sampler dans sa tranche, ajoute le résultat
+    /// après fader à `output` et remplit les envois vers les bus. Le gain, le pan et les
+    /// niveaux d'envoi des samplers avancent d'un sample par frame
+    fn mix_channels(
+        &mut self,
+        output: &mut [[f32; 2]],
+        channel_frames: &[[f32; 2]],
+        samplers: &mut [Sampler],
+        input: &[[f32; 2]],
+    ) {
+        let frame_count = output.len();
+        for send in &mut self.sends {
+            send.clear();
+            send.resize(frame_count, [0.0; 2]);
+        }
+
+        let any_solo = self.channels.iter().any(|c| c.solo);
+        // Normalisation par nombre de samplers
+        let scale = 1.0 / samplers.len().max(1) as f32;
+        let sampler_count = samplers.len();
+
+        for (index, sampler) in samplers.iter_mut().enumerate() {
+            let channel_index = self.channel_index(sampler.id);
+            let channel = &mut self.channels[channel_index];
+            let block = &mut self.channel_block;
+            block.clear();
+            block.extend(
+                channel_frames
+                    .iter()
+                    .skip(index)
+                    .step_by(sampler_count)
+                    .take(frame_count)
+                    .map(|frame| [frame[0] * scale, frame[1] * scale]),
+            );
+            for slot in &mut channel.effects {
+                slot.process_block(block, input, &self.taps);
+            }
+
+            let audible = !channel.mute && (!any_solo || channel.solo);
+            let target = if audible { 1.0 } else { 0.0 };
+            for (frame_index, (frame, out)) in block.iter_mut().zip(output.iter_mut()).enumerate() {
+                channel.level = fade_toward(channel.level, target);
+                let (fader_l, fader_r) = sampler.next_fader();
+                let pre = [frame[0] * channel.level, frame[1] * channel.level];
+                let post = [pre[0] * fader_l, pre[1] * fader_r];
+                out[0] += post[0];
+                out[1] += post[1];
+
+                for (bus, send) in self.sends.iter_mut().zip(&mut sampler.sends) {
+                    let level = send.level.next_value();
+                    if level > 0.0 {
+                        let source = if send.pre_fader { pre } else { post };
+                        bus[frame_index][0] += source[0] * level;
+                        bus[frame_index][1] += source[1] * level;
+                    }
+                }
+                *frame = post;
+            }
+            self.taps.set_channel(sampler.id, block);
+        }
+    }
+
+    /// Publie la réduction de gain des effets de dynamique, puis celle du limiteur master
+    pub fn take_gain_reductions<F>(&mut self, mut publish: F) -> f32
+    where
+        F: FnMut(usize, f32),
+    {
+        for slot in self.chain_slots_mut() {
+            if let Some(reduction) = slot.effect.take_gain_reduction() {
+                publish(slot.effect.id(), reduction);
+            }
+        }
+        self.master_limiter
+            .effect
+            .take_gain_reduction()
+            .unwrap_or(0.0)
+    }
+
+    /// Ajoute un effet à la fin de la chaîne du master en lui transmettant le tempo courant
+    pub fn create_effect(&mut self, id: u32, effect: EffectsEnum) {
+        let mut effect = effect.create(id as usize);
+        effect.set_tempo(self.tempo);
+        self.effects.push(EffectSlot::new(effect));
+    }
+
+    /// Effets des chaînes : ceux du master, des bus puis des tranches
+    fn chain_slots_mut(&mut self) -> impl Iterator<Item = &mut EffectSlot> {
+        self.effects
+            .iter_mut()
+            .chain(self.buses.iter_mut().flat_map(|bus| bus.effects.iter_mut()))
+            .chain(self.channels.iter_mut().flat_map(|c| c.effects.iter_mut()))
+    }
+
+    /// Tous les effets : ceux des chaînes puis le limiteur master
+    fn slots_mut(&mut self) -> impl Iterator<Item = &mut EffectSlot> {
+        let limiter = std::iter::once(&mut self.master_limiter);
+        self.effects
+            .iter_mut()
+            .chain(self.buses.iter_mut().flat_map(|bus| bus.effects.iter_mut()))
+            .chain(self.channels.iter_mut().flat_map(|c| c.effects.iter_mut()))
+            .chain(limiter)
+    }
+
+    /// 0 : master, 1 à `MAX_FX_BUSES` : bus, au-delà : tranche du sampler
+    /// `chain - CHANNEL_CHAIN_BASE`
+    fn chain_mut(&mut self, chain: usize) -> Option<&mut Vec<EffectSlot>> {
+        match chain {
+            0 => Some(&mut self.effects),
+            CHANNEL_CHAIN_BASE.. => {
+                let sampler_id = chain - CHANNEL_CHAIN_BASE;
+                self.channels
+                    .iter_mut()
+                    .find(|c| c.sampler_id as usize == sampler_id)
+                    .map(|c| &mut c.effects)
+            }
+            bus => self.buses.get_mut(bus - 1).map(|bus| &mut bus.effects),
+        }
+    }
+
+    /// Déplace un effet à `position` dans une chaîne (voir `chain_mut`), à la fin si elle
+    /// dépasse, sans perdre son état
+    pub fn move_fx(&mut self, id: u32, position: u32, chain: usize) {
+        if self.chain_mut(chain).is_none() {
+            console::error_1(&format!("Chaîne {} inexistante", chain).into());
+            return;
+        }
+        let sources = (0..=self.buses.len())
+            .chain(
+                self.channels
+                    .iter()
+                    .map(|c| CHANNEL_CHAIN_BASE + c.sampler_id as usize),
+            )
+            .collect::<Vec<_>>();
+        let slot = sources.into_iter().find_map(|source| {
+            let effects = self.chain_mut(source)?;
+            let index = effects.iter().position(|s| s.effect.id() == id as usize)?;
+            Some(effects.remove(index))
+        });
+        if let (Some(slot), Some(effects)) = (slot, self.chain_mut(chain)) {
+            let position = (position as usize).min(effects.len());
+            effects.insert(position, slot);
+        }
+    }
+
+    pub fn add_channel(&mut self, sampler_id: u8) {
+        self.channel_index(sampler_id);
+    }
+
+    /// Tranche du sampler, créée au besoin : un sampler sans tranche reste audible
+    fn channel_index(&mut self, sampler_id: u8) -> usize {
+        match self
+            .channels
+            .iter()
+            .position(|c| c.sampler_id == sampler_id)
+        {
+            Some(index) => index,
+            None => {
+                self.channels.push(ChannelStrip::new(sampler_id));
+                self.channels.len() - 1
+            }
+        }
+    }
+
+    pub fn remove_channel(&mut self, sampler_id: u8) {
+        self.channels.retain(|c| c.sampler_id != sampler_id);
+        self.taps.remove_channel(sampler_id);
+    }
+
+    /// 0 mute, 1 solo
+    pub fn update_channel(&mut self, sampler_id: u8, param_index: u32, value: f32) {
+        let Some(channel) = self
+            .channels
+            .iter_mut()
+            .find(|c| c.sampler_id == sampler_id)
+        else {
+            return;
         };
+        match param_index {
+            0 => channel.mute = value >= 0.5,
+            1 => channel.solo = value >= 0.5,
+            _ => console::error_1(&format!("Cannot update {}", param_index).into()),
+        }
+    }
 
-        mixer
+    /// Niveau de retour d'un bus sur le master
+    pub fn set_bus_return(&mut self, bus: usize, level: f32) {
+        if let Some(bus) = self.buses.get_mut(bus) {
+            bus.return_level.set(level.clamp(0.0, 1.0));
+        }
     }
-    pub fn render(&mut self, sample_l: &mut f32, sample_r: &mut f32) {
-        for effect in &mut self.effects {
-            effect.process(sample_l, sample_r);
+
+    /// Paramètres communs à tous les effets : 0 bypass, 1 dosage, 2 gain de sortie (dB),
+    /// 3 durée des rampes de lissage (ms)
+    pub fn update_slot(&mut self, id: u32, param_index: u32, value: f32) {
+        let Some(slot) = self.slots_mut().find(|s| s.effect.id() == id as usize) else {
+            return;
+        };
+        match param_index {
+            0 => slot.bypass = value >= 0.5,
+            1 => slot.mix.set(value.clamp(0.0, 1.0)),
+            2 => slot.gain.set(10f32.powf(value.clamp(-48.0, 24.0) / 20.0)),
+            3 => slot.set_ramp(value.clamp(0.0, MAX_RAMP_MS)),
+            _ => console::error_1(&format!("Cannot update {}", param_index).into()),
         }
     }
 
-    pub fn create_echo(&mut self, id: u32) {
-        let echo = Echo::new(
-            self.ECHO_DEFAULT_PRESET.delay,
-            self.ECHO_DEFAULT_PRESET.feedback,
-            self.ECHO_DEFAULT_PRESET.r_delay_offset,
-            self.ECHO_DEFAULT_PRESET.l_delay_offset,
-            self.ECHO_DEFAULT_PRESET.mix,
-            id as usize,
-        );
+    /// Source de détection d'un effet : `kind` 0 aucune, 1 sampler, 2 bus, 3 MIDI
+    pub fn set_sidechain(&mut self, id: u32, kind: u32, index: f32) {
+        let Some(source) = SidechainSource::from_event(kind, index) else {
+            console::error_1(&format!("Sidechain {} inconnu", kind).into());
+            return;
+        };
+        if let Some(slot) = self.slots_mut().find(|s| s.effect.id() == id as usize) {
+            slot.sidechain = source;
+        }
+    }
+
+    /// 0 note (négatif : toutes), 1 attaque, 2 maintien, 3 relâchement (ms)
+    pub fn set_midi_envelope(&mut self, param_index: u32, value: f32) {
+        if !self.taps.midi.set_param(param_index, value) {
+            console::error_1(&format!("Cannot update {}", param_index).into());
+        }
+    }
 
-        self.effects.push(Box::new(echo));
+    pub fn trigger_midi_sidechain(&mut self, note: u8) {
+        self.taps.midi.trigger(note);
     }
 
+    pub fn set_tempo(&mut self, bpm: f32) {
+        self.tempo = bpm.clamp(20.0, 400.0);
+        let tempo = self.tempo;
+        for slot in self.slots_mut() {
+            slot.effect.set_tempo(tempo);
+        }
+    }
+
+    /// Paramètre d'un effet, ramené dans la plage donnée par sa description
     pub fn update_fx(&mut self, id: u32, param_index: u32, value: f32) {
-        if let Some(effect) = self.effects.iter_mut().find(|e| e.id() == id as usize) {
-            if let Some(echo) = effect.as_any_mut().downcast_mut::<Echo>() {
-                match param_index {
-                    0 => echo.delay = ToolKit::convert_ms_to_sample(value),
-                    1 => echo.feedback = value.min(1.0),
-                    2 => echo.l_delay_offset = ToolKit::convert_ms_to_sample(value),
-                    3 => echo.r_delay_offset = ToolKit::convert_ms_to_sample(value),
-                    4 => echo.mix.dry = value.min(1.0),
-                    5 => echo.mix.wet = value.min(1.0),
-                    _ => console::error_1(&format!("Cannot update {}", param_index).into()),
-                }
-            } else if let Some(filter) = effect.as_any_mut().downcast_mut::<BiquadFilter>() {
-                match param_index {
-                    0 => filter.edit(value as f32, filter.q, filter.filter_type, filter.gain),
-                    1 => filter.edit(
-                        filter.frequency,
-                        value as f32,
-                        filter.filter_type,
-                        filter.gain,
-                    ),
-                    2 => filter.edit(filter.frequency, filter.q, value as u8, filter.gain),
-                    3 => filter.edit(
-                        filter.frequency,
-                        filter.q,
-                        filter.filter_type as u8,
-                        value as f32,
-                    ),
-                    _ => console::error_1(&format!("Cannot update {}", param_index).into()),
-                }
-            }
+        let Some(slot) = self.slots_mut().find(|s| s.effect.id() == id as usize) else {
+            return;
+        };
+        if !slot.set_param(param_index, value) {
+            console::error_1(&format!("Cannot update {}", param_index).into());
+        }
+    }
+
+    /// Description du paramètre `param_index` de l'effet `id`
+    pub fn fx_param(&mut self, id: u32, param_index: u32) -> Option<&'static ParamInfo> {
+        self.slots_mut()
+            .find(|s| s.effect.id() == id as usize)
+            .and_then(|slot| slot.effect.params().get(param_index as usize))
+    }
+
+    /// Paramètre `ParamInfo::sample`, le sample étant déjà cherché par l'appelant
+    pub fn update_fx_sample(&mut self, id: u32, param_index: u32, sample: Option<Sample>) {
+        let Some(slot) = self.slots_mut().find(|s| s.effect.id() == id as usize) else {
+            return;
+        };
+        if !slot.effect.set_sample(param_index, sample) {
+            console::error_1(&format!("Cannot update {}", param_index).into());
+        }
+    }
+
+    /// Première convolution dont l'IR est à reconstruire
+    pub fn take_impulse_request(&mut self) -> Option<(usize, ImpulseRequest)> {
+        self.slots_mut().find_map(|slot| {
+            let id = slot.effect.id();
+            let convolution = slot.effect.as_any_mut().downcast_mut::<Convolution>()?;
+            convolution.take_request().map(|request| (id, request))
+        })
+    }
+
+    pub fn install_impulse(
+        &mut self,
+        id: usize,
+        generation: u32,
+        response: Option<ImpulseResponse>,
+    ) {
+        let convolution = self
+            .slots_mut()
+            .find(|s| s.effect.id() == id)
+            .and_then(|slot| slot.effect.as_any_mut().downcast_mut::<Convolution>());
+        if let Some(convolution) = convolution {
+            convolution.install(generation, response);
         }
     }
 
     pub fn remove_fx(&mut self, id: u32) {
-        self.effects.retain(|e| e.id() != id as usize);
+        self.effects.retain(|s| s.effect.id() != id as usize);
+        for bus in &mut self.buses {
+            bus.effects.retain(|s| s.effect.id() != id as usize);
+        }
+        for channel in &mut self.channels {
+            channel.effects.retain(|s| s.effect.id() != id as usize);
+        }
+    }
+
+    /// Écrit la réponse en amplitude (dB) d'un égaliseur, `false` si l'effet n'en est pas un
+    pub fn magnitude_response(
+        &mut self,
+        id: u32,
+        frequencies: &[f32],
+        magnitudes: &mut [f32],
+    ) -> bool {
+        let equalizer = self
+            .slots_mut()
+            .find(|s| s.effect.id() == id as usize)
+            .and_then(|slot| slot.effect.as_any_mut().downcast_mut::<Equalizer>());
+        match equalizer {
+            Some(equalizer) => {
+                equalizer.magnitude_response(frequencies, magnitudes);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::Any;
+
+    use super::*;
+    use crate::utils::types::{Interpolation, SamplerSend};
+
+    /// Divise par deux et compte les frames traitées
+    struct Halve {
+        frames: usize,
+    }
+
+    impl EffectTrait for Halve {
+        fn id(&self) -> usize {
+            0
+        }
+
+        fn process(&mut self, sample_l: &mut f32, sample_r: &mut f32) {
+            *sample_l *= 0.5;
+            *sample_r *= 0.5;
+            self.frames += 1;
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+
+        fn params(&self) -> &'static [ParamInfo] {
+            &[]
+        }
+
+        fn set_param(&mut self, _index: u32, _value: f32) -> bool {
+            false
+        }
+    }
+
+    fn sampler(gain: f32) -> Sampler {
+        Sampler {
+            id: 0,
+            sample_id: 0,
+            instrument_id: None,
+            attack_length: 0,
+            hold_length: 0,
+            decay_length: 0,
+            sustain_gain: 1.0,
+            release_length: 0,
+            frequency_shift: 0.0,
+            phase_shift: 0.0,
+            delay_length: 0,
+            gain: SmoothedParam::new(gain),
+            gain_l: SmoothedParam::new(1.0),
+            gain_r: SmoothedParam::new(1.0),
+            interpolation: Interpolation::Linear,
+            sends: [SamplerSend::default(); MAX_FX_BUSES],
+        }
+    }
+
+    /// Sans le limiteur de sortie et sa latence d'anticipation
+    fn mixer() -> Mixer {
+        let mut mixer = Mixer::new();
+        mixer.master_limiter.bypass = true;
+        mixer.master_limiter.fade = 0.0;
+        mixer
+    }
+
+    fn render(mixer: &mut Mixer, sampler: &mut Sampler, frames: &[[f32; 2]]) -> Vec<[f32; 2]> {
+        let mut output = vec![[0.0; 2]; frames.len()];
+        let input = vec![[0.0; 2]; frames.len()];
+        mixer.render_block(&mut output, frames, std::slice::from_mut(sampler), &input);
+        output
+    }
+
+    fn halve_on_master(mixer: &mut Mixer) {
+        mixer
+            .effects
+            .push(EffectSlot::new(Box::new(Halve { frames: 0 })));
     }
 
-    pub fn create_filter(&mut self, id: u32) {
-        let filter = BiquadFilter::new(800.0, 0.7, id as usize, 0, 5.0);
-        self.effects.push(Box::new(filter));
+    fn halved_frames(mixer: &mut Mixer) -> usize {
+        let halve = mixer.effects[0].effect.as_any_mut().downcast_mut::<Halve>();
+        halve.unwrap().frames
+    }
+
+    #[test]
+    fn moved_effect_keeps_its_state() {
+        let mut impulse = vec![[0.0; 2]; 2048];
+        impulse[0] = [1.0, 1.0];
+        let silence = vec![[0.0; 2]; 4096];
+
+        let (mut moved, mut reference) = (mixer(), mixer());
+        let (mut sampler_a, mut sampler_b) = (sampler(1.0), sampler(1.0));
+        for mixer in [&mut moved, &mut reference] {
+            mixer.add_channel(0);
+            mixer.create_effect(7, EffectsEnum::Reverb);
+        }
+        render(&mut moved, &mut sampler_a, &impulse);
+        render(&mut reference, &mut sampler_b, &impulse);
+
+        // master -> bus 1 -> tranche du sampler -> master
+        moved.move_fx(7, 0, 1);
+        assert_eq!(moved.buses[0].effects.len(), 1);
+        moved.move_fx(7, 0, CHANNEL_CHAIN_BASE);
+        assert_eq!(moved.channels[0].effects.len(), 1);
+        moved.move_fx(7, 0, 0);
+        assert_eq!(moved.effects.len(), 1);
+
+        let tail = render(&mut moved, &mut sampler_a, &silence);
+        assert_eq!(tail, render(&mut reference, &mut sampler_b, &silence));
+        assert!(tail.iter().any(|frame| frame[0].abs() > 1e-4));
+    }
+
+    #[test]
+    fn bypass_fades_then_stops_processing() {
+        let mut mixer = mixer();
+        let mut sampler = sampler(1.0);
+        halve_on_master(&mut mixer);
+        let ones = vec![[1.0; 2]; 512];
+
+        assert!(
+            render(&mut mixer, &mut sampler, &ones[..64])
+                .iter()
+                .all(|f| f[0] == 0.5)
+        );
+
+        mixer.update_slot(0, 0, 1.0);
+        let fade_frames = (FADE_MS * SAMPLE_RATE / 1000.0) as usize;
+        let output = render(&mut mixer, &mut sampler, &ones);
+        // la sortie de l'effet s'efface linéairement en `FADE_MS`
+        let half = fade_frames / 2;
+        let expected = 1.0 - 0.5 * (1.0 - (half + 1) as f32 / fade_frames as f32);
+        assert!((output[half][0] - expected).abs() < 1e-3);
+        assert!(output.windows(2).all(|w| w[1][0] >= w[0][0]));
+        assert!(output[fade_frames - 2][0] < 1.0);
+        assert_eq!(output[fade_frames + 2][0], 1.0);
+        assert_eq!(mixer.effects[0].fade, 0.0);
+
+        let processed = halved_frames(&mut mixer);
+        assert!(
+            render(&mut mixer, &mut sampler, &ones)
+                .iter()
+                .all(|f| f[0] == 1.0)
+        );
+        assert_eq!(halved_frames(&mut mixer), processed);
+    }
+
+    #[test]
+    fn slot_mix_and_gain_apply_to_the_effect_output() {
+        let mut mixer = mixer();
+        let mut sampler = sampler(1.0);
+        halve_on_master(&mut mixer);
+        let ones = vec![[1.0; 2]; 64];
+
+        mixer.update_slot(0, 3, 0.0); // sans rampe
+        mixer.update_slot(0, 1, 0.5);
+        let output = render(&mut mixer, &mut sampler, &ones);
+        assert!(output.iter().all(|f| (f[0] - 0.75).abs() < 1e-6));
+
+        mixer.update_slot(0, 2, -20.0 * 2f32.log10());
+        let output = render(&mut mixer, &mut sampler, &ones);
+        // 1 + (0,5 * 0,5 - 1) * 0,5
+        assert!(output.iter().all(|f| (f[0] - 0.625).abs() < 1e-5));
+    }
+
+    #[test]
+    fn pre_fader_send_ignores_sampler_gain() {
+        let mut mixer = mixer();
+        let mut sampler = sampler(0.25);
+        sampler.sends[0].level = SmoothedParam::new(1.0);
+        sampler.sends[0].pre_fader = true;
+        let ones = vec![[1.0; 2]; 64];
+
+        // fader 0,25 + envoi 1 sur le bus 1, retour 1
+        let output = render(&mut mixer, &mut sampler, &ones);
+        assert!(output.iter().all(|f| (f[0] - 1.25).abs() < 1e-6));
+
+        sampler.gain = SmoothedParam::new(0.0);
+        let output = render(&mut mixer, &mut sampler, &ones);
+        assert!(output.iter().all(|f| f[0] == 1.0 && f[1] == 1.0));
+
+        sampler.sends[0].pre_fader = false;
+        let output = render(&mut mixer, &mut sampler, &ones);
+        assert!(output.iter().all(|f| f[0] == 0.0));
     }
 }
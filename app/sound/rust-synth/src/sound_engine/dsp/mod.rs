@@ -1,2 +1,6 @@
+pub mod fft;
 pub mod fx;
 pub mod mixer;
+pub mod resampler;
+pub mod sidechain;
+pub mod smoothing;
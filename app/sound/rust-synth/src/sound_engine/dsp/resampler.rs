@@ -0,0 +1,146 @@
+use std::f32::consts::PI;
+
+use once_cell::sync::Lazy;
+
+use crate::utils::types::Interpolation;
+
+const PHASES: usize = 256; // positions fractionnaires précalculées, interpolées entre elles
+const BANDS: usize = 5; // coupures par demi-octave de transposition vers le haut
+
+/// Noyaux sinc fenêtrés précalculés : `table[band][phase][tap]`
+pub struct SincKernel {
+    taps: usize,
+    table: Vec<f32>,
+}
+
+static SINC_8: Lazy<SincKernel> = Lazy::new(|| SincKernel::new(8));
+static SINC_32: Lazy<SincKernel> = Lazy::new(|| SincKernel::new(32));
+
+/// Calcule les noyaux hors du chemin audio
+pub fn init_kernels() {
+    Lazy::force(&SINC_8);
+    Lazy::force(&SINC_32);
+}
+
+fn sinc(x: f32) -> f32 {
+    if x.abs() < 1e-6 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+/// Fenêtre de Blackman sur [-1, 1]
+fn blackman(t: f32) -> f32 {
+    if t.abs() >= 1.0 {
+        return 0.0;
+    }
+    0.42 + 0.5 * (PI * t).cos() + 0.08 * (2.0 * PI * t).cos()
+}
+
+impl SincKernel {
+    fn new(taps: usize) -> Self {
+        let half = (taps / 2) as f32;
+        let mut table = Vec::with_capacity(BANDS * (PHASES + 1) * taps);
+
+        for band in 0..BANDS {
+            // un peu sous Nyquist, puis divisée par √2 à chaque bande
+            let cutoff = 0.95 * 0.5f32.powf(band as f32 / 2.0);
+
+            for phase in 0..=PHASES {
+                let frac = phase as f32 / PHASES as f32;
+                let start = table.len();
+
+                for tap in 0..taps {
+                    // taps centrés sur [index - taps/2 + 1, index + taps/2]
+                    let x = tap as f32 - (half - 1.0) - frac;
+                    table.push(cutoff * sinc(cutoff * x) * blackman(x / half));
+                }
+
+                // gain unitaire en continu
+                let sum: f32 = table[start..].iter().sum();
+                if sum.abs() > 1e-9 {
+                    table[start..].iter_mut().for_each(|c| *c /= sum);
+                }
+            }
+        }
+
+        Self { taps, table }
+    }
+
+    fn row(&self, band: usize, phase: usize) -> &[f32] {
+        let start = (band * (PHASES + 1) + phase) * self.taps;
+        &self.table[start..start + self.taps]
+    }
+
+    fn band(step: f32) -> usize {
+        if step <= 1.0 {
+            0
+        } else {
+            ((2.0 * step.log2()).ceil() as usize).min(BANDS - 1)
+        }
+    }
+
+    fn frame(
+        &self,
+        index: isize,
+        frac: f32,
+        step: f32,
+        fetch: impl Fn(isize) -> (f32, f32),
+    ) -> (f32, f32) {
+        let band = Self::band(step);
+        let position = frac * PHASES as f32;
+        let phase = (position as usize).min(PHASES - 1);
+        let blend = position - phase as f32;
+        let (row_a, row_b) = (self.row(band, phase), self.row(band, phase + 1));
+
+        let first = index - (self.taps / 2) as isize + 1;
+        let (mut l, mut r) = (0.0, 0.0);
+        for tap in 0..self.taps {
+            let coefficient = row_a[tap] + (row_b[tap] - row_a[tap]) * blend;
+            let (sample_l, sample_r) = fetch(first + tap as isize);
+            l += sample_l * coefficient;
+            r += sample_r * coefficient;
+        }
+        (l, r)
+    }
+}
+
+/// Frame stéréo à `index + frac`. `fetch` lit une frame entière (boucle et bords gérés
+/// par l'appelant) ; `step` est l'avance par frame de sortie, pour l'anti-repliement.
+pub fn interpolate(
+    interpolation: Interpolation,
+    index: isize,
+    frac: f32,
+    step: f32,
+    fetch: impl Fn(isize) -> (f32, f32),
+) -> (f32, f32) {
+    match interpolation {
+        Interpolation::Linear => {
+            let (l0, r0) = fetch(index);
+            let (l1, r1) = fetch(index + 1);
+            (l0 + (l1 - l0) * frac, r0 + (r1 - r0) * frac)
+        }
+        Interpolation::Cubic => {
+            // Hermite 4 points (Catmull-Rom)
+            let points = [
+                fetch(index - 1),
+                fetch(index),
+                fetch(index + 1),
+                fetch(index + 2),
+            ];
+            let hermite = |p0: f32, p1: f32, p2: f32, p3: f32| {
+                let c1 = 0.5 * (p2 - p0);
+                let c2 = p0 - 2.5 * p1 + 2.0 * p2 - 0.5 * p3;
+                let c3 = 0.5 * (p3 - p0) + 1.5 * (p1 - p2);
+                ((c3 * frac + c2) * frac + c1) * frac + p1
+            };
+            (
+                hermite(points[0].0, points[1].0, points[2].0, points[3].0),
+                hermite(points[0].1, points[1].1, points[2].1, points[3].1),
+            )
+        }
+        Interpolation::Sinc8 => SINC_8.frame(index, frac, step, fetch),
+        Interpolation::Sinc32 => SINC_32.frame(index, frac, step, fetch),
+    }
+}
@@ -0,0 +1,141 @@
+use crate::utils::constants::{MAX_FX_BUSES, SAMPLE_RATE};
+
+/// Signal de détection d'un effet, choisi par effet dans le mixer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SidechainSource {
+    None,
+    Sampler(u8), // sortie après fader de la tranche du sampler
+    Bus(usize),  // sortie du bus auxiliaire
+    Midi,        // enveloppe déclenchée par les note on
+}
+
+impl SidechainSource {
+    /// `kind` : 0 aucune, 1 sampler, 2 bus, 3 MIDI ; `index` : id du sampler ou du bus
+    pub fn from_event(kind: u32, index: f32) -> Option<Self> {
+        match kind {
+            0 => Some(SidechainSource::None),
+            1 => Some(SidechainSource::Sampler(index as u8)),
+            2 => Some(SidechainSource::Bus(index as usize)),
+            3 => Some(SidechainSource::Midi),
+            _ => None,
+        }
+    }
+}
+
+/// Enveloppe attaque / maintien / relâchement linéaire relancée à chaque note on, pour
+/// faire pomper un effet au rythme du jeu sans signal audio
+pub struct MidiEnvelope {
+    pub note: Option<u8>, // seule cette note déclenche l'enveloppe, toutes si `None`
+    pub attack: f32,      // ms
+    pub hold: f32,
+    pub release: f32,
+    level: f32,
+    held: usize, // samples de maintien restants
+    rising: bool,
+}
+
+impl Default for MidiEnvelope {
+    fn default() -> Self {
+        Self {
+            note: None,
+            attack: 1.0,
+            hold: 50.0,
+            release: 200.0,
+            level: 0.0,
+            held: 0,
+            rising: false,
+        }
+    }
+}
+
+impl MidiEnvelope {
+    /// 0 note (négatif : toutes), 1 attaque, 2 maintien, 3 relâchement
+    pub fn set_param(&mut self, param_index: u32, value: f32) -> bool {
+        match param_index {
+            0 => self.note = (value >= 0.0).then(|| value.min(127.0) as u8),
+            1 => self.attack = value.clamp(0.0, 1000.0),
+            2 => self.hold = value.clamp(0.0, 5000.0),
+            3 => self.release = value.clamp(1.0, 5000.0),
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn trigger(&mut self, note: u8) {
+        if self.note.is_none_or(|n| n == note) {
+            self.rising = true;
+        }
+    }
+
+    /// Valeur (0 à 1) du sample courant, puis avance d'un sample
+    fn next(&mut self) -> f32 {
+        let ms_to_step = |ms: f32| 1000.0 / (ms * SAMPLE_RATE).max(1.0);
+        if self.rising {
+            self.level = (self.level + ms_to_step(self.attack)).min(1.0);
+            if self.level >= 1.0 {
+                self.rising = false;
+                self.held = (self.hold * 0.001 * SAMPLE_RATE) as usize;
+            }
+        } else if self.held > 0 {
+            self.held -= 1;
+        } else {
+            self.level = (self.level - ms_to_step(self.release)).max(0.0);
+        }
+        self.level
+    }
+}
+
+/// Derniers blocs disponibles comme source de sidechain. Une tranche qui écoute un
+/// sampler traité après elle reçoit le bloc précédent.
+#[derive(Default)]
+pub struct SidechainTaps {
+    channels: Vec<(u8, Vec<[f32; 2]>)>,
+    buses: [Vec<[f32; 2]>; MAX_FX_BUSES],
+    midi_block: Vec<[f32; 2]>,
+    pub midi: MidiEnvelope,
+}
+
+impl SidechainTaps {
+    /// Début de bloc : calcule l'enveloppe MIDI sur `frame_count` frames
+    pub fn advance(&mut self, frame_count: usize) {
+        self.midi_block.clear();
+        for _ in 0..frame_count {
+            let level = self.midi.next();
+            self.midi_block.push([level; 2]);
+        }
+    }
+
+    pub fn set_channel(&mut self, sampler_id: u8, frames: &[[f32; 2]]) {
+        match self.channels.iter_mut().find(|(id, _)| *id == sampler_id) {
+            Some((_, tap)) => {
+                tap.clear();
+                tap.extend_from_slice(frames);
+            }
+            None => self.channels.push((sampler_id, frames.to_vec())),
+        }
+    }
+
+    pub fn remove_channel(&mut self, sampler_id: u8) {
+        self.channels.retain(|(id, _)| *id != sampler_id);
+    }
+
+    pub fn set_bus(&mut self, bus: usize, frames: &[[f32; 2]]) {
+        if let Some(tap) = self.buses.get_mut(bus) {
+            tap.clear();
+            tap.extend_from_slice(frames);
+        }
+    }
+
+    pub fn get(&self, source: SidechainSource) -> Option<&[[f32; 2]]> {
+        match source {
+            SidechainSource::None => None,
+            SidechainSource::Sampler(sampler_id) => self
+                .channels
+                .iter()
+                .find(|(id, _)| *id == sampler_id)
+                .map(|(_, frames)| frames.as_slice()),
+            SidechainSource::Bus(bus) => self.buses.get(bus).map(Vec::as_slice),
+            SidechainSource::Midi => Some(&self.midi_block),
+        }
+    }
+}
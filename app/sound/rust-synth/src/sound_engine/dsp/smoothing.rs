@@ -0,0 +1,168 @@
+use crate::utils::constants::SAMPLE_RATE;
+
+pub const DEFAULT_RAMP_MS: f32 = 20.0;
+pub const MAX_RAMP_MS: f32 = 1000.0;
+// frames entre deux mises à jour des paramètres d'effet pendant une rampe
+pub const SMOOTHING_BLOCK: usize = 32;
+
+/// Paramètre continu qui rejoint chaque nouvelle cible par une rampe linéaire de durée
+/// fixe, au lieu de sauter d'un coup (bruit de zipper, clics)
+#[derive(Debug, Clone, Copy)]
+pub struct SmoothedParam {
+    current: f32,
+    target: f32,
+    step: f32,      // par sample
+    remaining: u32, // samples avant d'atteindre la cible
+    ramp: u32,      // durée d'une rampe en samples, 0 : changement immédiat
+}
+
+impl Default for SmoothedParam {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
+impl SmoothedParam {
+    pub fn new(value: f32) -> Self {
+        let mut param = Self {
+            current: value,
+            target: value,
+            step: 0.0,
+            remaining: 0,
+            ramp: 0,
+        };
+        param.set_ramp(DEFAULT_RAMP_MS);
+        param
+    }
+
+    /// Durée des prochaines rampes ; celle en cours garde sa pente
+    pub fn set_ramp(&mut self, ms: f32) {
+        self.ramp = (ms.clamp(0.0, MAX_RAMP_MS) * 0.001 * SAMPLE_RATE) as u32;
+    }
+
+    pub fn set(&mut self, target: f32) {
+        self.target = target;
+        if self.ramp == 0 {
+            self.reset(target);
+            return;
+        }
+        self.remaining = self.ramp;
+        self.step = (target - self.current) / self.ramp as f32;
+    }
+
+    /// Saute directement à `value`, sans rampe
+    pub fn reset(&mut self, value: f32) {
+        self.current = value;
+        self.target = value;
+        self.remaining = 0;
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current
+    }
+
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+
+    pub fn is_smoothing(&self) -> bool {
+        self.remaining > 0
+    }
+
+    /// Avance d'un sample et renvoie la nouvelle valeur
+    pub fn next_value(&mut self) -> f32 {
+        self.advance(1)
+    }
+
+    /// Avance de `samples` d'un coup, pour les paramètres mis à jour par sous-bloc
+    pub fn advance(&mut self, samples: usize) -> f32 {
+        if self.remaining == 0 {
+            return self.current;
+        }
+        let samples = (samples as u32).min(self.remaining);
+        self.remaining -= samples;
+        self.current = if self.remaining == 0 {
+            self.target // pas de dérive d'arrondi en fin de rampe
+        } else {
+            self.current + self.step * samples as f32
+        };
+        self.current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp_samples(ms: f32) -> usize {
+        (ms * 0.001 * SAMPLE_RATE) as usize
+    }
+
+    #[test]
+    fn ramp_reaches_its_target_in_the_configured_time() {
+        let mut param = SmoothedParam::new(0.0);
+        param.set(1.0);
+        let samples = ramp_samples(DEFAULT_RAMP_MS);
+        for _ in 0..samples - 1 {
+            let value = param.next_value();
+            assert!(value > 0.0 && value < 1.0);
+        }
+        assert_eq!(param.next_value(), 1.0);
+        assert!(!param.is_smoothing());
+        assert_eq!(param.next_value(), 1.0);
+    }
+
+    #[test]
+    fn ramp_is_linear() {
+        let mut param = SmoothedParam::new(0.0);
+        param.set_ramp(10.0);
+        param.set(-2.0);
+        let samples = ramp_samples(10.0);
+        let half = samples / 2;
+        let halfway = (0..half).map(|_| param.next_value()).last().unwrap();
+        let expected = -2.0 * half as f32 / samples as f32;
+        assert!((halfway - expected).abs() < 1e-4, "{}", halfway);
+    }
+
+    #[test]
+    fn advancing_by_blocks_matches_sample_by_sample() {
+        let mut by_sample = SmoothedParam::new(0.5);
+        let mut by_block = by_sample;
+        by_sample.set(0.8);
+        by_block.set(0.8);
+        let samples = ramp_samples(DEFAULT_RAMP_MS);
+        for block in 0..samples.div_ceil(SMOOTHING_BLOCK) {
+            for _ in 0..SMOOTHING_BLOCK {
+                by_sample.next_value();
+            }
+            let value = by_block.advance(SMOOTHING_BLOCK);
+            // seule l'accumulation d'arrondis par sample les sépare
+            assert!((value - by_sample.value()).abs() < 1e-4, "bloc {}", block);
+        }
+        assert_eq!(by_block.value(), 0.8);
+    }
+
+    #[test]
+    fn new_target_restarts_a_full_ramp_from_the_current_value() {
+        let mut param = SmoothedParam::new(0.0);
+        param.set(1.0);
+        let samples = ramp_samples(DEFAULT_RAMP_MS);
+        param.advance(samples / 2);
+        let start = param.value();
+        param.set(0.0);
+        param.advance(samples - 1);
+        assert!(param.is_smoothing());
+        assert!(param.value() > 0.0 && param.value() < start);
+        param.next_value();
+        assert_eq!(param.value(), 0.0);
+    }
+
+    #[test]
+    fn zero_ramp_jumps() {
+        let mut param = SmoothedParam::new(0.0);
+        param.set_ramp(0.0);
+        param.set(3.0);
+        assert_eq!(param.value(), 3.0);
+        assert!(!param.is_smoothing());
+    }
+}
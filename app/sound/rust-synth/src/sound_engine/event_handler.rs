@@ -1,31 +1,43 @@
 use std::{cell::RefCell, rc::Rc};
 
-use js_sys::{Atomics, Float32Array, Int32Array};
+use js_sys::Atomics;
 use web_sys::console;
 
 use crate::{
-    global::{MIXER, SAMPLE_MANAGER, SHARED_BUFFERS},
-    shared_memory::shared_buffers::{FxBuffers, MidiBuffers, SamplerBuffers},
+    global::{MIXER, SAMPLE_MANAGER},
+    shared_memory::shared_buffers::{FxBuffers, MidiBuffers, SampleUploadBuffers, SamplerBuffers},
     sound_engine::{
-        dsp::fx::EffectsEnum,
+        dsp::{
+            fx::{EffectsEnum, params::ParamInfo},
+            smoothing::SmoothedParam,
+        },
+        loaders::{sf2, sfz, wav},
         synthetizer::{
-            note_manager::{self, NoteManager},
+            instrument::Envelope, note_manager::NoteManager, sample_manager::PresetLookup,
             sampler::Sampler,
         },
     },
     utils::{
         constants::{
-            FX_EVENT_SIZE_FLOAT, FX_EVENT_SIZE_INT, FX_QUEUE_CAPACITY, OSC_QUEUE_CAPACITY,
+            BANK_SELECT_CC, FX_RESPONSE_MAX_FREQUENCY, FX_RESPONSE_MIN_FREQUENCY,
+            FX_RESPONSE_POINTS, OSC_QUEUE_CAPACITY, SAMPLE_TRANSFER_SLOT_SIZE,
+            SAMPLE_TRANSFER_SLOTS, SAMPLE_UPLOAD_STATUS_CAPACITY, SAMPLER_SEND_PARAM,
+            SAMPLER_SEND_PARAMS, SAMPLER_SMOOTHING_PARAM,
         },
         toolkit::ToolKit,
-        types::SampleEvent,
+        types::{
+            Interpolation, MidiEvent, PendingUpload, Sample, SampleUploadChunk, UploadFormat,
+            UploadStatus,
+        },
     },
 };
 
 pub struct EventHandler {
     note_manager: Rc<RefCell<NoteManager>>,
     samplers: Rc<RefCell<Vec<Sampler>>>,
-    last_sample_event: SampleEvent,
+    pending_uploads: Vec<PendingUpload>,
+    completed_uploads: Vec<PendingUpload>, // reçus en entier, décodés après le rendu
+    bank_select: u16,                      // dernier CC 0 reçu
 }
 
 impl EventHandler {
@@ -36,22 +48,94 @@ impl EventHandler {
         Self {
             note_manager,
             samplers,
-            last_sample_event: SampleEvent::default(),
+            pending_uploads: Vec::new(),
+            completed_uploads: Vec::new(),
+            bank_select: 0,
         }
     }
 
     pub fn process_midi_events(&mut self, midi: &MidiBuffers) -> u32 {
-        midi.process_all_events(|dto| {
-            if dto.velocity > 0 {
-                self.note_manager
-                    .borrow_mut()
-                    .add_note(dto, &self.samplers.borrow_mut());
-            } else {
-                self.note_manager.borrow_mut().end_note(dto);
+        midi.process_all_events(|event| match event {
+            MidiEvent::Note(dto) => {
+                if dto.velocity > 0 {
+                    MIXER.with(|m| m.lock().unwrap().trigger_midi_sidechain(dto.value));
+                    self.note_manager
+                        .borrow_mut()
+                        .add_note(dto, &self.samplers.borrow_mut());
+                } else {
+                    self.note_manager.borrow_mut().end_note(dto);
+                }
+            }
+            MidiEvent::ControlChange { controller, value } => {
+                if *controller == BANK_SELECT_CC {
+                    self.bank_select = *value as u16;
+                }
+            }
+            MidiEvent::ProgramChange(program) => {
+                let bank = self.bank_select;
+                for sampler in self.samplers.borrow_mut().iter_mut() {
+                    Self::select_preset(sampler, bank, *program);
+                }
             }
         })
     }
 
+    /// Passe un sampler joué par une banque SF2 sur un autre preset de la même banque
+    fn select_preset(sampler: &mut Sampler, bank: u16, program: u8) {
+        let Some(instrument_id) = sampler.instrument_id else {
+            return;
+        };
+        let preset =
+            SAMPLE_MANAGER.with(|sm| sm.lock().unwrap().find_preset(instrument_id, bank, program));
+        match preset {
+            PresetLookup::Found(preset_id, envelope) => {
+                sampler.change_instrument(preset_id, envelope)
+            }
+            PresetLookup::Missing => {
+                console::warn_1(&format!("Preset {}:{} absent de la banque", bank, program).into())
+            }
+            // instrument SFZ : pas de presets
+            PresetLookup::NotABank => {}
+        }
+    }
+
+    /// Nouveau sampler et sa tranche dans le mixer
+    fn add_sampler(&mut self, id: u8) {
+        self.samplers.borrow_mut().push(Sampler {
+            id,
+            sample_id: 0,
+            instrument_id: None,
+            attack_length: ToolKit::convert_ms_to_sample(0.0) as u64,
+            hold_length: 0,
+            decay_length: ToolKit::convert_ms_to_sample(10.0) as u64,
+            sustain_gain: 0.5,
+            release_length: ToolKit::convert_ms_to_sample(500.0) as u64,
+            frequency_shift: 1.0,
+            delay_length: ToolKit::convert_ms_to_sample(0.0) as u64,
+            phase_shift: 0.0,
+            gain: SmoothedParam::new(0.5),
+            gain_l: SmoothedParam::new(1.0),
+            gain_r: SmoothedParam::new(1.0),
+            interpolation: Interpolation::Linear,
+            sends: Default::default(),
+        });
+        MIXER.with(|m| m.lock().unwrap().add_channel(id));
+    }
+
+    /// Retire le sampler puis sa tranche ; un seul emprunt de `samplers` à la fois
+    fn remove_sampler(&mut self, id: u8) {
+        let removed = {
+            let mut samplers = self.samplers.borrow_mut();
+            samplers
+                .iter()
+                .position(|sampler| sampler.id == id)
+                .map(|pos| samplers.remove(pos))
+        };
+        if removed.is_some() {
+            MIXER.with(|m| m.lock().unwrap().remove_channel(id));
+        }
+    }
+
     pub fn process_osc_events(&mut self, osc_buffers: &SamplerBuffers) {
         let mut read_pos = Atomics::load(&osc_buffers.read_idx, 0).unwrap() as u32;
         let write_pos = Atomics::load(&osc_buffers.write_idx, 0).unwrap() as u32;
@@ -68,42 +152,15 @@ impl EventHandler {
 
             let value = {
                 let mut bytes = [0u8; 4];
-                for i in 0..4 {
-                    bytes[i] = osc_buffers.queue.get_index(offset + 3 + i as u32);
+                for (i, byte) in bytes.iter_mut().enumerate() {
+                    *byte = osc_buffers.queue.get_index(offset + 3 + i as u32);
                 }
                 f32::from_le_bytes(bytes)
             };
 
             match event_type {
-                0 => {
-                    // add
-                    self.samplers.borrow_mut().push(Sampler {
-                        id: osc_index,
-                        sample_id: 0,
-                        attack_length: ToolKit::convert_ms_to_sample(0.0) as u64,
-                        decay_length: ToolKit::convert_ms_to_sample(10.0) as u64,
-                        sustain_gain: 0.5,
-                        release_length: ToolKit::convert_ms_to_sample(500.0) as u64,
-                        frequency_shift: 1.0,
-                        delay_length: ToolKit::convert_ms_to_sample(0.0) as u64,
-                        phase_shift: 0.0,
-                        gain: 0.5,
-                        gain_l: 1.0,
-                        gain_r: 1.0,
-                    });
-                }
-                1 => {
-                    // remove
-                    if let Some(pos) = self
-                        .samplers
-                        .borrow_mut()
-                        .iter()
-                        .position(|osc| osc.id == osc_index)
-                    {
-                        self.samplers.borrow_mut().remove(pos);
-                    } else {
-                    }
-                }
+                0 => self.add_sampler(osc_index),
+                1 => self.remove_sampler(osc_index),
                 2 => {
                     // update
                     if let Some(osc) = self
@@ -117,14 +174,36 @@ impl EventHandler {
                             2 => osc.release_length = value as u64,
                             3 => osc.decay_length = value as u64,
                             4 => osc.sustain_gain = value * 0.1,
-                            5 => osc.gain = value * 0.1,
+                            5 => osc.gain.set(value * 0.1),
                             6 => osc.delay_length = value as u64,
                             7 => osc.frequency_shift = value,
                             8 => osc.phase_shift = value,
                             9 => osc.sample_id = value as u32,
-                            10 => {
-                                osc.gain_l = (1.0 - value) / 2.0;
-                                osc.gain_r = (1.0 + value) / 2.0
+                            10 => osc.set_pan(value),
+                            11 => {
+                                // preset SF2 : bank * 128 + program
+                                let preset = value as u32;
+                                Self::select_preset(
+                                    osc,
+                                    (preset / 128) as u16,
+                                    (preset % 128) as u8,
+                                );
+                            }
+                            12 => {
+                                if let Ok(interpolation) = Interpolation::try_from(value as u8) {
+                                    osc.interpolation = interpolation;
+                                }
+                            }
+                            SAMPLER_SMOOTHING_PARAM => osc.set_ramp(value),
+                            SAMPLER_SEND_PARAM.. => {
+                                let bus =
+                                    ((key - SAMPLER_SEND_PARAM) / SAMPLER_SEND_PARAMS) as usize;
+                                if let Some(send) = osc.sends.get_mut(bus) {
+                                    match (key - SAMPLER_SEND_PARAM) % SAMPLER_SEND_PARAMS {
+                                        0 => send.level.set(value.clamp(0.0, 1.0)),
+                                        _ => send.pre_fader = value >= 0.5,
+                                    }
+                                }
                             }
 
                             _ => {}
@@ -140,51 +219,72 @@ impl EventHandler {
         Atomics::store(&osc_buffers.read_idx, 0, read_pos as i32).unwrap();
     }
 
-    pub fn process_fx_events(&mut self, fx_buffer: &FxBuffers) {
-        let mut read_pos = Atomics::load(&fx_buffer.read_idx, 0).unwrap() as u32;
-        let write_pos = Atomics::load(&fx_buffer.write_idx, 0).unwrap() as u32;
-
-        if read_pos == write_pos {
-            return;
-        }
-
-        while read_pos != write_pos {
-            // Offsets pour les vues séparées
-            let int_offset = read_pos * FX_EVENT_SIZE_INT;
-            let float_offset = read_pos * FX_EVENT_SIZE_FLOAT;
-
-            let fx_id = fx_buffer.queue_int.get_index(int_offset) as u32;
-            let event_type = fx_buffer.queue_int.get_index(int_offset + 1) as u32;
-            let param_index = fx_buffer.queue_int.get_index(int_offset + 2) as u32;
-
-            let value = fx_buffer.queue_float.get_index(float_offset);
-
-            match event_type {
-                0 => self.add_fx(fx_id, param_index),
-                1 => self.remove_fx(fx_id),
-                2 => self.edit_fx(fx_id, param_index, value),
-                _ => {}
-            }
+    pub fn process_fx_events(&mut self, fx_buffer: &FxBuffers) -> u32 {
+        fx_buffer.process_all_events(|dto| match dto.event_type {
+            0 => self.add_fx(dto.id, dto.param_index),
+            1 => self.remove_fx(dto.id),
+            2 => self.edit_fx(dto.id, dto.param_index, dto.value),
+            3 => self.send_magnitude_response(dto.id, fx_buffer),
+            4 => MIXER.with(|m| m.lock().unwrap().set_tempo(dto.value)),
+            5 => MIXER.with(|m| {
+                m.lock()
+                    .unwrap()
+                    .move_fx(dto.id, dto.param_index, dto.value as usize)
+            }),
+            6 => MIXER.with(|m| {
+                m.lock()
+                    .unwrap()
+                    .update_slot(dto.id, dto.param_index, dto.value)
+            }),
+            7 => MIXER.with(|m| m.lock().unwrap().set_bus_return(dto.id as usize, dto.value)),
+            8 => MIXER.with(|m| {
+                m.lock()
+                    .unwrap()
+                    .update_channel(dto.id as u8, dto.param_index, dto.value)
+            }),
+            9 => MIXER.with(|m| {
+                m.lock()
+                    .unwrap()
+                    .set_sidechain(dto.id, dto.param_index, dto.value)
+            }),
+            10 => MIXER.with(|m| {
+                m.lock()
+                    .unwrap()
+                    .set_midi_envelope(dto.param_index, dto.value)
+            }),
+            _ => {}
+        })
+    }
 
-            read_pos = (read_pos + 1) % FX_QUEUE_CAPACITY;
+    /// Réponse en amplitude d'un égaliseur, sur des fréquences espacées logarithmiquement
+    fn send_magnitude_response(&mut self, fx_id: u32, fx_buffer: &FxBuffers) {
+        let ratio = FX_RESPONSE_MAX_FREQUENCY / FX_RESPONSE_MIN_FREQUENCY;
+        let frequencies: Vec<f32> = (0..FX_RESPONSE_POINTS)
+            .map(|i| {
+                FX_RESPONSE_MIN_FREQUENCY * ratio.powf(i as f32 / (FX_RESPONSE_POINTS - 1) as f32)
+            })
+            .collect();
+        let mut magnitudes = vec![0.0; FX_RESPONSE_POINTS as usize];
+
+        let found = MIXER.with(|m| {
+            m.lock()
+                .unwrap()
+                .magnitude_response(fx_id, &frequencies, &mut magnitudes)
+        });
+        if found {
+            fx_buffer.write_response(fx_id, &magnitudes);
+        } else {
+            console::error_1(&format!("No magnitude response for fx {}", fx_id).into());
         }
-
-        Atomics::store(&fx_buffer.read_idx, 0, read_pos as i32).unwrap();
     }
 
     pub fn add_fx(&mut self, fx_id: u32, param_index: u32) {
         let effect = EffectsEnum::try_from(param_index).unwrap();
 
-        match effect {
-            EffectsEnum::Echo => MIXER.with(|m| {
-                let mut mixer = m.lock().unwrap();
-                mixer.create_echo(fx_id);
-            }),
-            EffectsEnum::Filter => MIXER.with(|m| {
-                let mut mixer = m.lock().unwrap();
-                mixer.create_filter(fx_id);
-            }),
-        }
+        MIXER.with(|m| {
+            let mut mixer = m.lock().unwrap();
+            mixer.create_effect(fx_id, effect);
+        })
     }
 
     pub fn remove_fx(&mut self, fx_id: u32) {
@@ -195,61 +295,368 @@ impl EventHandler {
     }
 
     pub fn edit_fx(&mut self, fx_id: u32, param_index: u32, value: f32) {
+        // un sample (IR, modulateur) est cherché hors du verrou du mixer
+        let is_sample = MIXER.with(|m| {
+            let mut mixer = m.lock().unwrap();
+            mixer
+                .fx_param(fx_id, param_index)
+                .is_some_and(ParamInfo::is_sample)
+        });
+        if is_sample {
+            let sample = SAMPLE_MANAGER.with(|sm| sm.lock().unwrap().get(value as u32).cloned());
+            if sample.is_none() {
+                console::error_1(&format!("Sample {} introuvable", value as u32).into());
+            }
+            MIXER.with(|m| {
+                let mut mixer = m.lock().unwrap();
+                mixer.update_fx_sample(fx_id, param_index, sample);
+            });
+            return;
+        }
+
         MIXER.with(|m| {
             let mut mixer = m.lock().unwrap();
             mixer.update_fx(fx_id, param_index, value);
         })
     }
 
-    pub fn process_sample_event(&mut self, sample_event: &Int32Array) {
-        let sample_event_index = sample_event.get_index(0);
+    pub fn process_sample_uploads(&mut self, uploads: &SampleUploadBuffers) -> u32 {
+        uploads.process_all_events(|chunk| self.handle_upload_chunk(chunk, uploads))
+    }
 
-        // si on change vraiment de sampler ou sample
-        if sample_event_index as u32 != self.last_sample_event.sample_event_index {
-            let new_event = SampleEvent {
-                sample_event_index: sample_event_index as u32,
-                sampler_id: sample_event.get_index(1) as u32,
-                sample_id: sample_event.get_index(2) as u32,
-                length: sample_event.get_index(3) as u32,
-                channels: sample_event.get_index(4) as u8,
-                hq: sample_event.get_index(5) as u8,
-            };
-            self.last_sample_event = new_event;
-
-            SAMPLE_MANAGER.with(|sm| {
-                let mut sm = sm.lock().unwrap();
-
-                SHARED_BUFFERS.with(|sb| {
-                    if let Some(shared) = sb.get() {
-                        let sample_buffer: &Float32Array = &shared.sample_buffer;
-
-                        // Vérifier si sample_id existe déjà
-                        let already_exists = sm
-                            .samples
-                            .iter()
-                            .any(|s| s.id == self.last_sample_event.sample_id);
-
-                        if !already_exists {
-                            sm.add_sample(
-                                self.last_sample_event.sample_id,
-                                sample_buffer.clone(),
-                                self.last_sample_event.length,
-                                self.last_sample_event.hq,
-                            );
-                        }
-                    }
+    fn handle_upload_chunk(&mut self, chunk: &SampleUploadChunk, uploads: &SampleUploadBuffers) {
+        // pas de données : on assigne un sample déjà chargé
+        if chunk.total_length == 0 {
+            let (exists, instrument) = SAMPLE_MANAGER.with(|sm| {
+                let sm = sm.lock().unwrap();
+                // une banque SF2 se joue à partir de son premier preset
+                let instrument_id = sm
+                    .get_sound_bank(chunk.sample_id)
+                    .and_then(|bank| bank.presets.first())
+                    .map_or(chunk.sample_id, |preset| preset.instrument_id);
+                let instrument = sm
+                    .get_instrument(instrument_id)
+                    .map(|instrument| (instrument_id, instrument.envelope));
+                (sm.has_sample(chunk.sample_id), instrument)
+            });
+            if let Some(interpolation) = Self::interpolation_override(chunk.interpolation) {
+                SAMPLE_MANAGER.with(|sm| {
+                    sm.lock()
+                        .unwrap()
+                        .set_interpolation(chunk.sample_id, Some(interpolation))
                 });
+            }
+            let status = if let Some((instrument_id, envelope)) = instrument {
+                self.assign_instrument(chunk.sampler_id, instrument_id, envelope);
+                UploadStatus::Done
+            } else if exists {
+                self.assign_sample(chunk.sampler_id, chunk.sample_id);
+                UploadStatus::Done
+            } else {
+                UploadStatus::UnknownSample
+            };
+            uploads.write_status(chunk.upload_id, status, 0);
+            return;
+        }
+
+        if chunk.slot < 0 || chunk.slot as u32 >= SAMPLE_TRANSFER_SLOTS {
+            self.abort_upload(chunk, uploads, UploadStatus::InvalidSlot);
+            return;
+        }
+        let slot = chunk.slot as u32;
+
+        let Ok(format) = UploadFormat::try_from(chunk.format) else {
+            self.abort_upload(chunk, uploads, UploadStatus::InvalidFormat);
+            return;
+        };
+
+        if chunk.chunk_offset == 0 {
+            // un nouvel upload remplace une éventuelle tentative précédente (même id ou même
+            // sample) ; un upload abandonné dont le statut a été recyclé n'est plus attendu
+            self.pending_uploads.retain(|p| {
+                p.upload_id != chunk.upload_id
+                    && p.sample_id != chunk.sample_id
+                    && chunk.upload_id.wrapping_sub(p.upload_id) < SAMPLE_UPLOAD_STATUS_CAPACITY
+            });
+            self.pending_uploads.push(PendingUpload {
+                upload_id: chunk.upload_id,
+                sampler_id: chunk.sampler_id,
+                sample_id: chunk.sample_id,
+                channels: chunk.channels,
+                hq: chunk.hq,
+                format,
+                interpolation: Self::interpolation_override(chunk.interpolation),
+                total_length: chunk.total_length,
+                values: Vec::new(),
+                bytes: Vec::new(),
             });
+        }
+
+        let Some(pos) = self
+            .pending_uploads
+            .iter()
+            .position(|p| p.upload_id == chunk.upload_id)
+        else {
+            self.abort_upload(chunk, uploads, UploadStatus::OutOfOrder);
+            return;
+        };
+
+        let pending = &mut self.pending_uploads[pos];
+        let received = pending.received();
+        let slot_capacity = match pending.format {
+            UploadFormat::Pcm => SAMPLE_TRANSFER_SLOT_SIZE,
+            _ => SAMPLE_TRANSFER_SLOT_SIZE * 4,
+        };
+
+        if chunk.chunk_offset != received {
+            self.abort_upload(chunk, uploads, UploadStatus::OutOfOrder);
+            return;
+        }
+        let Some(new_length) = received
+            .checked_add(chunk.chunk_length)
+            .filter(|&length| length <= pending.total_length && chunk.chunk_length <= slot_capacity)
+            .map(|length| length as usize)
+        else {
+            self.abort_upload(chunk, uploads, UploadStatus::Overflow);
+            return;
+        };
 
-            // et ici on peut utiliser self.last_sample_event
-            if let Some(sampler) = self
-                .samplers
-                .borrow_mut()
-                .iter_mut()
-                .find(|s| s.id == self.last_sample_event.sampler_id as u8)
-            {
-                sampler.change_sample(self.last_sample_event.sample_id);
+        match pending.format {
+            UploadFormat::Pcm => {
+                pending.values.resize(new_length, 0.0);
+                uploads.copy_slot(slot, &mut pending.values[received as usize..]);
             }
+            _ => {
+                pending.bytes.resize(new_length, 0);
+                uploads.copy_slot_bytes(slot, &mut pending.bytes[received as usize..]);
+            }
+        }
+        uploads.release_slot(slot);
+
+        let received = pending.received();
+        uploads.write_status(chunk.upload_id, UploadStatus::InProgress, received);
+        if received == pending.total_length {
+            let pending = self.pending_uploads.remove(pos);
+            self.completed_uploads.push(pending);
+        }
+    }
+
+    /// Décode un upload reçu en entier. Appelé après le rendu d'un bloc, quand le ring buffer
+    /// est plein : un seul par appel pour ne pas retarder le bloc suivant
+    pub fn finish_completed_upload(&mut self, uploads: &SampleUploadBuffers) {
+        if self.completed_uploads.is_empty() {
+            return;
         }
+        let pending = self.completed_uploads.remove(0);
+        let (upload_id, received) = (pending.upload_id, pending.received());
+        let status = self.finish_upload(pending);
+        uploads.write_status(upload_id, status, received);
+    }
+
+    fn finish_upload(&mut self, pending: PendingUpload) -> UploadStatus {
+        let sample = match pending.format {
+            UploadFormat::Pcm => {
+                if pending.channels == 0
+                    || !pending.total_length.is_multiple_of(pending.channels as u32)
+                {
+                    return UploadStatus::InvalidLength;
+                }
+                Sample::new(
+                    pending.sample_id,
+                    pending.values,
+                    pending.channels,
+                    pending.hq,
+                )
+            }
+            UploadFormat::Wav => match wav::decode(&pending.bytes) {
+                Ok(wav) => wav.into_sample(pending.sample_id),
+                Err(err) => {
+                    console::error_1(&format!("WAV illisible : {}", err).into());
+                    return UploadStatus::DecodeError;
+                }
+            },
+            UploadFormat::Sfz => return self.finish_instrument_upload(pending),
+            UploadFormat::Sf2 => return self.finish_sound_bank_upload(pending),
+        };
+
+        let sample = Sample {
+            interpolation: pending.interpolation,
+            ..sample
+        };
+        SAMPLE_MANAGER.with(|sm| {
+            let mut sm = sm.lock().unwrap();
+            if !sm.has_sample(pending.sample_id) {
+                sm.add_sample(sample);
+            }
+        });
+        self.assign_sample(pending.sampler_id, pending.sample_id);
+
+        UploadStatus::Done
+    }
+
+    /// Le `sample_id` d'un upload SFZ sert d'identifiant à l'instrument
+    fn finish_instrument_upload(&mut self, pending: PendingUpload) -> UploadStatus {
+        // le décodage se fait hors du verrou, seule l'insertion le prend
+        let first_sample_id = SAMPLE_MANAGER.with(|sm| sm.lock().unwrap().next_sample_id());
+        let import = match sfz::import(&pending.bytes, pending.sample_id, first_sample_id) {
+            Ok(import) => import,
+            Err(err) => {
+                console::error_1(&format!("SFZ illisible : {}", err).into());
+                return UploadStatus::DecodeError;
+            }
+        };
+
+        let envelope = import.instrument.envelope;
+        SAMPLE_MANAGER.with(|sm| {
+            let mut sm = sm.lock().unwrap();
+            for sample in import.samples {
+                sm.add_sample(sample);
+            }
+            sm.add_instrument(import.instrument);
+        });
+        self.assign_instrument(pending.sampler_id, pending.sample_id, envelope);
+
+        for warning in &import.warnings {
+            console::warn_1(&format!("SFZ : {}", warning).into());
+        }
+        if import.warnings.is_empty() {
+            UploadStatus::Done
+        } else {
+            UploadStatus::DoneWithWarnings
+        }
+    }
+
+    /// Le `sample_id` d'un upload SF2 identifie la banque ; le sampler reçoit son premier preset
+    fn finish_sound_bank_upload(&mut self, pending: PendingUpload) -> UploadStatus {
+        // le décodage se fait hors du verrou, seule l'insertion le prend
+        let first_id = SAMPLE_MANAGER.with(|sm| sm.lock().unwrap().next_sample_id());
+        let import = match sf2::import(&pending.bytes, pending.sample_id, first_id) {
+            Ok(import) => import,
+            Err(err) => {
+                console::error_1(&format!("SF2 illisible : {}", err).into());
+                return UploadStatus::DecodeError;
+            }
+        };
+
+        let instrument_id = import.bank.presets[0].instrument_id;
+        let envelope = import
+            .instruments
+            .iter()
+            .find(|i| i.id == instrument_id)
+            .and_then(|i| i.envelope);
+        for preset in &import.bank.presets {
+            console::log_1(
+                &format!(
+                    "SF2 preset {}:{} {}",
+                    preset.bank, preset.program, preset.name
+                )
+                .into(),
+            );
+        }
+        SAMPLE_MANAGER.with(|sm| {
+            let mut sm = sm.lock().unwrap();
+            for sample in import.samples {
+                sm.add_sample(sample);
+            }
+            for instrument in import.instruments {
+                sm.add_instrument(instrument);
+            }
+            sm.add_sound_bank(import.bank);
+        });
+        self.assign_instrument(pending.sampler_id, instrument_id, envelope);
+
+        for warning in &import.warnings {
+            console::warn_1(&format!("SF2 : {}", warning).into());
+        }
+        if import.warnings.is_empty() {
+            UploadStatus::Done
+        } else {
+            UploadStatus::DoneWithWarnings
+        }
+    }
+
+    /// 0 : le sample suit l'interpolation du sampler
+    fn interpolation_override(value: u8) -> Option<Interpolation> {
+        value
+            .checked_sub(1)
+            .and_then(|v| Interpolation::try_from(v).ok())
+    }
+
+    fn abort_upload(
+        &mut self,
+        chunk: &SampleUploadChunk,
+        uploads: &SampleUploadBuffers,
+        status: UploadStatus,
+    ) {
+        console::error_1(&format!("Upload {} interrompu : {:?}", chunk.upload_id, status).into());
+        self.pending_uploads
+            .retain(|p| p.upload_id != chunk.upload_id);
+        if chunk.slot >= 0 && (chunk.slot as u32) < SAMPLE_TRANSFER_SLOTS {
+            uploads.release_slot(chunk.slot as u32);
+        }
+        uploads.write_status(chunk.upload_id, status, chunk.chunk_offset);
+    }
+
+    fn assign_sample(&mut self, sampler_id: u32, sample_id: u32) {
+        if let Some(sampler) = self
+            .samplers
+            .borrow_mut()
+            .iter_mut()
+            .find(|s| s.id as u32 == sampler_id)
+        {
+            sampler.change_sample(sample_id);
+        }
+    }
+
+    fn assign_instrument(
+        &mut self,
+        sampler_id: u32,
+        instrument_id: u32,
+        envelope: Option<Envelope>,
+    ) {
+        if let Some(sampler) = self
+            .samplers
+            .borrow_mut()
+            .iter_mut()
+            .find(|s| s.id as u32 == sampler_id)
+        {
+            sampler.change_instrument(instrument_id, envelope);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn has_channel(id: u8) -> bool {
+        MIXER.with(|m| {
+            m.lock()
+                .unwrap()
+                .channels
+                .iter()
+                .any(|c| c.sampler_id == id)
+        })
+    }
+
+    #[test]
+    fn removing_a_sampler_removes_its_channel() {
+        let samplers = Rc::new(RefCell::new(Vec::new()));
+        let mut handler =
+            EventHandler::new(Rc::new(RefCell::new(NoteManager::new())), samplers.clone());
+
+        handler.add_sampler(3);
+        handler.add_sampler(4);
+        assert_eq!(samplers.borrow().len(), 2);
+        assert!(has_channel(3) && has_channel(4));
+
+        handler.remove_sampler(3);
+        let ids: Vec<_> = samplers.borrow().iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![4]);
+        assert!(!has_channel(3) && has_channel(4));
+
+        // sampler inconnu : rien ne change
+        handler.remove_sampler(3);
+        assert_eq!(samplers.borrow().len(), 1);
     }
 }
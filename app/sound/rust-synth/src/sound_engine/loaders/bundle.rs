@@ -0,0 +1,58 @@
+use crate::sound_engine::loaders::riff::ByteReader;
+
+/// Fichier d'un paquet envoyé par le JS :
+/// `[nombre de fichiers: u32]` puis pour chacun `[taille du nom: u32][nom utf-8][taille: u32][octets]`
+pub struct BundleFile<'a> {
+    pub name: String,
+    pub data: &'a [u8],
+}
+
+pub fn unpack(bytes: &[u8]) -> Option<Vec<BundleFile<'_>>> {
+    let mut reader = ByteReader::new(bytes);
+    let count = reader.u32()?;
+    let mut files = Vec::new();
+
+    for _ in 0..count {
+        let name_length = reader.u32()? as usize;
+        let name = String::from_utf8_lossy(reader.take(name_length)?).into_owned();
+        let data_length = reader.u32()? as usize;
+        let data = reader.take(data_length)?;
+        files.push(BundleFile { name, data });
+    }
+
+    Some(files)
+}
+
+/// Chemin comparable : séparateurs unifiés, sans `./`, en minuscules
+pub fn normalize_path(path: &str) -> String {
+    let path = path.trim().replace('\\', "/").to_lowercase();
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+/// Retrouve un fichier du paquet par son chemin relatif, puis par son seul nom
+pub fn find<'b, 'a>(files: &'b [BundleFile<'a>], path: &str) -> Option<&'b BundleFile<'a>> {
+    let wanted = normalize_path(path);
+    let file_name = wanted.rsplit('/').next().unwrap_or(&wanted).to_string();
+
+    files
+        .iter()
+        .find(|f| {
+            let name = normalize_path(&f.name);
+            name == wanted || name.ends_with(&format!("/{}", wanted))
+        })
+        .or_else(|| {
+            files
+                .iter()
+                .find(|f| normalize_path(&f.name).rsplit('/').next() == Some(&file_name))
+        })
+}
@@ -0,0 +1,5 @@
+pub mod bundle;
+pub mod riff;
+pub mod sf2;
+pub mod sfz;
+pub mod wav;
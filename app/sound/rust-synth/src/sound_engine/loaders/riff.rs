@@ -0,0 +1,156 @@
+/// Lecture little-endian d'un buffer d'octets, sans jamais paniquer
+pub struct ByteReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+
+    pub fn take(&mut self, length: usize) -> Option<&'a [u8]> {
+        let end = self.pos.checked_add(length)?;
+        let slice = self.bytes.get(self.pos..end)?;
+        self.pos = end;
+        Some(slice)
+    }
+
+    pub fn skip(&mut self, length: usize) -> Option<()> {
+        self.take(length).map(|_| ())
+    }
+
+    pub fn tag(&mut self) -> Option<[u8; 4]> {
+        self.take(4)?.try_into().ok()
+    }
+
+    pub fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    pub fn i8(&mut self) -> Option<i8> {
+        self.u8().map(|b| b as i8)
+    }
+
+    pub fn u16(&mut self) -> Option<u16> {
+        self.take(2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    pub fn i16(&mut self) -> Option<i16> {
+        self.u16().map(|v| v as i16)
+    }
+
+    pub fn u32(&mut self) -> Option<u32> {
+        self.take(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    pub fn u64(&mut self) -> Option<u64> {
+        let b = self.take(8)?;
+        Some(u64::from_le_bytes([
+            b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7],
+        ]))
+    }
+}
+
+pub struct RiffChunk<'a> {
+    pub id: [u8; 4],
+    pub data: &'a [u8],
+}
+
+/// Itère sur les sous-chunks d'un corps RIFF (les tailles impaires sont paddées).
+/// Un chunk tronqué en fin de fichier est rendu avec les octets disponibles.
+pub struct RiffChunks<'a> {
+    reader: ByteReader<'a>,
+    /// Taille 64 bits du chunk `data` lue dans `ds64` (RF64)
+    pub data_size_override: Option<u64>,
+}
+
+impl<'a> RiffChunks<'a> {
+    pub fn new(body: &'a [u8]) -> Self {
+        Self {
+            reader: ByteReader::new(body),
+            data_size_override: None,
+        }
+    }
+}
+
+impl<'a> Iterator for RiffChunks<'a> {
+    type Item = RiffChunk<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.reader.remaining() < 8 {
+            return None;
+        }
+        let id = self.reader.tag()?;
+        let declared = self.reader.u32()?;
+
+        let size = match (&id, declared, self.data_size_override) {
+            (b"data", u32::MAX, Some(size)) => size as usize,
+            _ => declared as usize,
+        };
+        let size = size.min(self.reader.remaining());
+        let data = self.reader.take(size)?;
+
+        if size % 2 == 1 {
+            let _ = self.reader.skip(1);
+        }
+
+        Some(RiffChunk { id, data })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(id: &[u8; 4], declared: u32, data: &[u8]) -> Vec<u8> {
+        let mut bytes = id.to_vec();
+        bytes.extend(declared.to_le_bytes());
+        bytes.extend(data);
+        bytes
+    }
+
+    #[test]
+    fn reader_refuses_reads_past_the_end() {
+        let mut reader = ByteReader::new(&[1, 2, 3]);
+        assert_eq!(reader.u32(), None);
+        assert_eq!(reader.take(usize::MAX), None);
+        assert_eq!(reader.u16(), Some(0x0201));
+        assert_eq!(reader.u16(), None);
+        assert_eq!(reader.u8(), Some(3));
+        assert_eq!(reader.remaining(), 0);
+    }
+
+    #[test]
+    fn odd_chunks_are_padded() {
+        let mut body = chunk(b"abcd", 3, &[1, 2, 3, 0]);
+        body.extend(chunk(b"efgh", 2, &[4, 5]));
+        let chunks: Vec<_> = RiffChunks::new(&body).collect();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].data, &[1, 2, 3]);
+        assert_eq!(&chunks[1].id, b"efgh");
+        assert_eq!(chunks[1].data, &[4, 5]);
+    }
+
+    #[test]
+    fn oversized_chunk_keeps_the_available_bytes() {
+        let body = chunk(b"data", u32::MAX, &[1, 2, 3, 4]);
+        let chunks: Vec<_> = RiffChunks::new(&body).collect();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].data, &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn truncated_header_ends_the_iteration() {
+        let mut body = chunk(b"abcd", 2, &[1, 2]);
+        body.extend(b"efgh\x02\x00");
+        let chunks: Vec<_> = RiffChunks::new(&body).collect();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(&chunks[0].id, b"abcd");
+    }
+}
@@ -0,0 +1,809 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+    sync::Arc,
+};
+
+use crate::{
+    sound_engine::{
+        loaders::riff::{ByteReader, RiffChunks},
+        synthetizer::instrument::{Envelope, Instrument, InstrumentRegion, Preset, SoundBank},
+    },
+    utils::types::{LoopMode, Sample},
+};
+
+// générateurs SF2 (spécification 2.04, section 8.1.2)
+const START_ADDRS_OFFSET: usize = 0;
+const END_ADDRS_OFFSET: usize = 1;
+const STARTLOOP_ADDRS_OFFSET: usize = 2;
+const ENDLOOP_ADDRS_OFFSET: usize = 3;
+const START_ADDRS_COARSE_OFFSET: usize = 4;
+const END_ADDRS_COARSE_OFFSET: usize = 12;
+const PAN: usize = 17;
+const DELAY_VOL_ENV: usize = 33;
+const ATTACK_VOL_ENV: usize = 34;
+const HOLD_VOL_ENV: usize = 35;
+const DECAY_VOL_ENV: usize = 36;
+const SUSTAIN_VOL_ENV: usize = 37;
+const RELEASE_VOL_ENV: usize = 38;
+const INSTRUMENT: usize = 41;
+const KEY_RANGE: usize = 43;
+const VEL_RANGE: usize = 44;
+const STARTLOOP_ADDRS_COARSE_OFFSET: usize = 45;
+const INITIAL_ATTENUATION: usize = 48;
+const ENDLOOP_ADDRS_COARSE_OFFSET: usize = 50;
+const COARSE_TUNE: usize = 51;
+const FINE_TUNE: usize = 52;
+const SAMPLE_ID: usize = 53;
+const SAMPLE_MODES: usize = 54;
+const OVERRIDING_ROOT_KEY: usize = 58;
+const GENERATOR_COUNT: usize = 61;
+
+// les autres générateurs (filtre, LFO, enveloppe de modulation...) sont ignorés
+const SUPPORTED_GENERATORS: &[usize] = &[
+    START_ADDRS_OFFSET,
+    END_ADDRS_OFFSET,
+    STARTLOOP_ADDRS_OFFSET,
+    ENDLOOP_ADDRS_OFFSET,
+    START_ADDRS_COARSE_OFFSET,
+    END_ADDRS_COARSE_OFFSET,
+    PAN,
+    DELAY_VOL_ENV,
+    ATTACK_VOL_ENV,
+    HOLD_VOL_ENV,
+    DECAY_VOL_ENV,
+    SUSTAIN_VOL_ENV,
+    RELEASE_VOL_ENV,
+    INSTRUMENT,
+    KEY_RANGE,
+    VEL_RANGE,
+    STARTLOOP_ADDRS_COARSE_OFFSET,
+    INITIAL_ATTENUATION,
+    ENDLOOP_ADDRS_COARSE_OFFSET,
+    COARSE_TUNE,
+    FINE_TUNE,
+    SAMPLE_ID,
+    SAMPLE_MODES,
+    OVERRIDING_ROOT_KEY,
+];
+
+// ignorés au niveau preset (section 8.5) : les positions ne dépendent que de l'instrument
+const INSTRUMENT_ONLY_GENERATORS: &[usize] = &[
+    START_ADDRS_OFFSET,
+    END_ADDRS_OFFSET,
+    STARTLOOP_ADDRS_OFFSET,
+    ENDLOOP_ADDRS_OFFSET,
+    START_ADDRS_COARSE_OFFSET,
+    END_ADDRS_COARSE_OFFSET,
+    STARTLOOP_ADDRS_COARSE_OFFSET,
+    ENDLOOP_ADDRS_COARSE_OFFSET,
+    SAMPLE_MODES,
+    OVERRIDING_ROOT_KEY,
+];
+
+const ROM_SAMPLE: u16 = 0x8000;
+const DEFAULT_TIMECENTS: i16 = -12000;
+
+#[derive(Debug)]
+pub enum Sf2Error {
+    NotSoundFont,
+    MissingChunk(&'static str),
+    InvalidChunk(&'static str),
+    NoPresets,
+}
+
+impl fmt::Display for Sf2Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Sf2Error::NotSoundFont => write!(f, "pas un fichier RIFF sfbk"),
+            Sf2Error::MissingChunk(id) => write!(f, "chunk {} absent", id),
+            Sf2Error::InvalidChunk(id) => write!(f, "chunk {} invalide", id),
+            Sf2Error::NoPresets => write!(f, "aucun preset jouable"),
+        }
+    }
+}
+
+struct PresetHeader {
+    name: String,
+    program: u16,
+    bank: u16,
+    bag_index: usize,
+}
+
+struct InstrumentHeader {
+    bag_index: usize,
+}
+
+struct SampleHeader {
+    start: u32,
+    end: u32,
+    start_loop: u32,
+    end_loop: u32,
+    sample_rate: u32,
+    original_pitch: u8,
+    pitch_correction: i8,
+    sample_type: u16,
+}
+
+/// Générateurs d'une zone (`None` : non défini)
+#[derive(Clone, Copy)]
+struct Zone {
+    generators: [Option<i16>; GENERATOR_COUNT],
+}
+
+impl Zone {
+    fn empty() -> Self {
+        Self {
+            generators: [None; GENERATOR_COUNT],
+        }
+    }
+
+    fn get(&self, generator: usize) -> Option<i16> {
+        self.generators[generator]
+    }
+
+    /// Les valeurs de la zone locale remplacent celles de la zone globale
+    fn inherit(global: &Zone, local: &Zone) -> Zone {
+        let mut zone = *global;
+        for (value, local) in zone.generators.iter_mut().zip(local.generators) {
+            if local.is_some() {
+                *value = local;
+            }
+        }
+        zone
+    }
+
+    fn range(&self, generator: usize) -> (u8, u8) {
+        self.get(generator).map_or((0, 127), |raw| {
+            let raw = raw as u16;
+            ((raw & 0xff) as u8, (raw >> 8) as u8)
+        })
+    }
+
+    /// Valeur de l'instrument plus décalage du preset (les générateurs de preset sont relatifs),
+    /// sauf pour les générateurs réservés à l'instrument
+    fn sum(instrument: &Zone, preset: &Zone, generator: usize, default: i16) -> i32 {
+        let offset = if INSTRUMENT_ONLY_GENERATORS.contains(&generator) {
+            0
+        } else {
+            preset.get(generator).unwrap_or(0) as i32
+        };
+        instrument.get(generator).unwrap_or(default) as i32 + offset
+    }
+}
+
+/// Banque décodée, à enregistrer dans le `SampleManager` avec ses instruments et samples
+pub struct Sf2Import {
+    pub bank: SoundBank,
+    pub instruments: Vec<Instrument>,
+    pub samples: Vec<Sample>,
+    pub warnings: Vec<String>,
+}
+
+struct Pdta<'a> {
+    phdr: &'a [u8],
+    pbag: &'a [u8],
+    pgen: &'a [u8],
+    pmod: &'a [u8],
+    inst: &'a [u8],
+    ibag: &'a [u8],
+    igen: &'a [u8],
+    imod: &'a [u8],
+    shdr: &'a [u8],
+}
+
+fn records<T>(
+    data: &[u8],
+    size: usize,
+    id: &'static str,
+    mut parse: impl FnMut(&mut ByteReader) -> Option<T>,
+) -> Result<Vec<T>, Sf2Error> {
+    if !data.len().is_multiple_of(size) || data.len() < size {
+        return Err(Sf2Error::InvalidChunk(id));
+    }
+    let mut reader = ByteReader::new(data);
+    (0..data.len() / size)
+        .map(|_| parse(&mut reader).ok_or(Sf2Error::InvalidChunk(id)))
+        .collect()
+}
+
+fn name(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).trim().to_string()
+}
+
+/// Zones d'un header : `bags[i]..bags[i + 1]`, chacune avec ses générateurs
+fn zones(
+    bag_start: usize,
+    bag_end: usize,
+    bags: &[usize],
+    generators: &[(u16, i16)],
+    unsupported: &mut BTreeSet<u16>,
+) -> Vec<Zone> {
+    (bag_start..bag_end.min(bags.len().saturating_sub(1)))
+        .map(|bag| {
+            let mut zone = Zone::empty();
+            let end = bags[bag + 1].min(generators.len());
+            for &(operator, amount) in generators.get(bags[bag]..end).unwrap_or(&[]) {
+                let operator_index = operator as usize;
+                if SUPPORTED_GENERATORS.contains(&operator_index) {
+                    zone.generators[operator_index] = Some(amount);
+                } else {
+                    unsupported.insert(operator);
+                }
+            }
+            zone
+        })
+        .collect()
+}
+
+/// Sépare une éventuelle zone globale (la première, sans générateur terminal) des zones locales
+fn split_global(zones: Vec<Zone>, terminal: usize) -> (Zone, Vec<Zone>) {
+    match zones.first() {
+        Some(first) if first.get(terminal).is_none() => (zones[0], zones[1..].to_vec()),
+        _ => (Zone::empty(), zones),
+    }
+}
+
+fn timecents_to_seconds(timecents: i32) -> f32 {
+    2f32.powf(timecents.clamp(-12000, 8000) as f32 / 1200.0)
+}
+
+fn centibels_to_gain(centibels: i32) -> f32 {
+    10f32.powf(-(centibels.clamp(0, 1440) as f32) / 200.0)
+}
+
+fn intersect(a: (u8, u8), b: (u8, u8)) -> Option<(u8, u8)> {
+    let range = (a.0.max(b.0), a.1.min(b.1));
+    (range.0 <= range.1).then_some(range)
+}
+
+/// Lit une portion du pool 16 bits (`smpl`), complétée par les 8 bits de poids faible de `sm24`
+fn read_pool(smpl: &[u8], sm24: Option<&[u8]>, start: usize, end: usize) -> Vec<f32> {
+    (start..end)
+        .map(|i| {
+            let high = i16::from_le_bytes([smpl[i * 2], smpl[i * 2 + 1]]) as i32;
+            match sm24.and_then(|low| low.get(i)) {
+                Some(&low) => ((high << 8) | low as i32) as f32 / 8_388_608.0,
+                None => high as f32 / 32_768.0,
+            }
+        })
+        .collect()
+}
+
+fn parse_pdta(body: &[u8]) -> Result<Pdta<'_>, Sf2Error> {
+    let mut found: [Option<&[u8]>; 9] = [None; 9];
+    const IDS: [&[u8; 4]; 9] = [
+        b"phdr", b"pbag", b"pgen", b"pmod", b"inst", b"ibag", b"igen", b"imod", b"shdr",
+    ];
+    for chunk in RiffChunks::new(body) {
+        if let Some(index) = IDS.iter().position(|id| **id == chunk.id) {
+            found[index] = Some(chunk.data);
+        }
+    }
+    let get = |index: usize, id: &'static str| found[index].ok_or(Sf2Error::MissingChunk(id));
+
+    Ok(Pdta {
+        phdr: get(0, "phdr")?,
+        pbag: get(1, "pbag")?,
+        pgen: get(2, "pgen")?,
+        pmod: found[3].unwrap_or(&[]),
+        inst: get(4, "inst")?,
+        ibag: get(5, "ibag")?,
+        igen: get(6, "igen")?,
+        imod: found[7].unwrap_or(&[]),
+        shdr: get(8, "shdr")?,
+    })
+}
+
+/// Importe une banque SF2 : un `Instrument` par preset (zones preset × zones instrument),
+/// les samples partageant leurs données quand ils pointent sur la même portion du pool.
+/// Instruments et samples sont numérotés à partir de `first_id`.
+pub fn import(bytes: &[u8], bank_id: u32, first_id: u32) -> Result<Sf2Import, Sf2Error> {
+    let mut reader = ByteReader::new(bytes);
+    let riff = reader.tag().ok_or(Sf2Error::NotSoundFont)?;
+    reader.skip(4).ok_or(Sf2Error::NotSoundFont)?;
+    let form = reader.tag().ok_or(Sf2Error::NotSoundFont)?;
+    if &riff != b"RIFF" || &form != b"sfbk" {
+        return Err(Sf2Error::NotSoundFont);
+    }
+
+    let mut smpl = None;
+    let mut sm24 = None;
+    let mut pdta = None;
+
+    let body = reader
+        .take(reader.remaining())
+        .ok_or(Sf2Error::NotSoundFont)?;
+    for chunk in RiffChunks::new(body) {
+        if &chunk.id != b"LIST" || chunk.data.len() < 4 {
+            continue;
+        }
+        let list_body = &chunk.data[4..];
+        match &chunk.data[..4] {
+            b"sdta" => {
+                for sub in RiffChunks::new(list_body) {
+                    match &sub.id {
+                        b"smpl" => smpl = Some(sub.data),
+                        b"sm24" => sm24 = Some(sub.data),
+                        _ => {}
+                    }
+                }
+            }
+            b"pdta" => pdta = Some(parse_pdta(list_body)?),
+            _ => {}
+        }
+    }
+
+    let smpl = smpl.ok_or(Sf2Error::MissingChunk("smpl"))?;
+    let pool_length = smpl.len() / 2;
+    // sm24 n'est valable que s'il couvre tout le pool
+    let sm24 = sm24.filter(|low| low.len() >= pool_length);
+    let pdta = pdta.ok_or(Sf2Error::MissingChunk("pdta"))?;
+
+    let preset_headers = records(pdta.phdr, 38, "phdr", |r| {
+        let name = name(r.take(20)?);
+        let program = r.u16()?;
+        let bank = r.u16()?;
+        let bag_index = r.u16()? as usize;
+        r.skip(12)?;
+        Some(PresetHeader {
+            name,
+            program,
+            bank,
+            bag_index,
+        })
+    })?;
+    let instrument_headers = records(pdta.inst, 22, "inst", |r| {
+        r.skip(20)?;
+        Some(InstrumentHeader {
+            bag_index: r.u16()? as usize,
+        })
+    })?;
+    let bag = |r: &mut ByteReader| {
+        let gen_index = r.u16()? as usize;
+        r.skip(2)?;
+        Some(gen_index)
+    };
+    let preset_bags = records(pdta.pbag, 4, "pbag", bag)?;
+    let instrument_bags = records(pdta.ibag, 4, "ibag", bag)?;
+    let generator = |r: &mut ByteReader| Some((r.u16()?, r.i16()?));
+    let preset_generators = records(pdta.pgen, 4, "pgen", generator)?;
+    let instrument_generators = records(pdta.igen, 4, "igen", generator)?;
+    let sample_headers = records(pdta.shdr, 46, "shdr", |r| {
+        r.skip(20)?;
+        Some(SampleHeader {
+            start: r.u32()?,
+            end: r.u32()?,
+            start_loop: r.u32()?,
+            end_loop: r.u32()?,
+            sample_rate: r.u32()?,
+            original_pitch: r.u8()?,
+            pitch_correction: r.i8()?,
+            sample_type: {
+                r.skip(2)?; // sample_link
+                r.u16()?
+            },
+        })
+    })?;
+
+    let mut warnings = Vec::new();
+    let mut unsupported = BTreeSet::new();
+    // un enregistrement terminal seulement = aucun modulateur
+    if pdta.pmod.len() > 10 || pdta.imod.len() > 10 {
+        warnings.push("modulateurs ignorés".to_string());
+    }
+
+    let mut shared: HashMap<(usize, usize), Arc<[f32]>> = HashMap::new();
+    let mut next_id = first_id;
+    let mut presets = Vec::new();
+    let mut instruments = Vec::new();
+    let mut samples = Vec::new();
+
+    // le dernier header (EOP/EOI) ne sert qu'à borner les zones
+    for (index, header) in preset_headers
+        .iter()
+        .enumerate()
+        .take(preset_headers.len() - 1)
+    {
+        let preset_zones = zones(
+            header.bag_index,
+            preset_headers[index + 1].bag_index,
+            &preset_bags,
+            &preset_generators,
+            &mut unsupported,
+        );
+        let (preset_global, preset_zones) = split_global(preset_zones, INSTRUMENT);
+
+        let instrument_id = next_id;
+        next_id += 1;
+        let mut regions = Vec::new();
+
+        for preset_local in &preset_zones {
+            let preset_zone = Zone::inherit(&preset_global, preset_local);
+            let Some(instrument_index) = preset_zone.get(INSTRUMENT).map(|i| i as u16 as usize)
+            else {
+                continue;
+            };
+            if instrument_index + 1 >= instrument_headers.len() {
+                warnings.push(format!(
+                    "{} : instrument {} introuvable",
+                    header.name, instrument_index
+                ));
+                continue;
+            }
+
+            let instrument_zones = zones(
+                instrument_headers[instrument_index].bag_index,
+                instrument_headers[instrument_index + 1].bag_index,
+                &instrument_bags,
+                &instrument_generators,
+                &mut unsupported,
+            );
+            let (instrument_global, instrument_zones) = split_global(instrument_zones, SAMPLE_ID);
+
+            for instrument_local in &instrument_zones {
+                let zone = Zone::inherit(&instrument_global, instrument_local);
+                let Some(sample_index) = zone.get(SAMPLE_ID).map(|i| i as u16 as usize) else {
+                    continue;
+                };
+                let Some(sample_header) = sample_headers.get(sample_index) else {
+                    continue;
+                };
+                if sample_header.sample_type & ROM_SAMPLE != 0 {
+                    warnings.push("samples ROM ignorés".to_string());
+                    continue;
+                }
+
+                let (Some(key_range), Some(velocity_range)) = (
+                    intersect(zone.range(KEY_RANGE), preset_zone.range(KEY_RANGE)),
+                    intersect(zone.range(VEL_RANGE), preset_zone.range(VEL_RANGE)),
+                ) else {
+                    continue;
+                };
+
+                let offset = |fine: usize, coarse: usize| {
+                    Zone::sum(&zone, &preset_zone, fine, 0) as i64
+                        + Zone::sum(&zone, &preset_zone, coarse, 0) as i64 * 32_768
+                };
+                let clamp = |position: i64| position.clamp(0, pool_length as i64) as usize;
+                let start = clamp(
+                    sample_header.start as i64
+                        + offset(START_ADDRS_OFFSET, START_ADDRS_COARSE_OFFSET),
+                );
+                let end = clamp(
+                    sample_header.end as i64 + offset(END_ADDRS_OFFSET, END_ADDRS_COARSE_OFFSET),
+                );
+                if end <= start {
+                    continue;
+                }
+                let loop_start = sample_header.start_loop as i64
+                    + offset(STARTLOOP_ADDRS_OFFSET, STARTLOOP_ADDRS_COARSE_OFFSET);
+                let loop_end = sample_header.end_loop as i64
+                    + offset(ENDLOOP_ADDRS_OFFSET, ENDLOOP_ADDRS_COARSE_OFFSET);
+
+                let values = shared
+                    .entry((start, end))
+                    .or_insert_with(|| read_pool(smpl, sm24, start, end).into())
+                    .clone();
+
+                let mut sample = Sample::new(next_id, values, 1, 0);
+                let frames = sample.frames();
+                sample.sample_rate = sample_header.sample_rate as f32;
+                sample.key_range = key_range;
+                sample.velocity_range = velocity_range;
+                sample.root_key = match zone.get(OVERRIDING_ROOT_KEY) {
+                    Some(key) if (0..=127).contains(&key) => key as u8,
+                    _ if sample_header.original_pitch <= 127 => sample_header.original_pitch,
+                    _ => 60,
+                };
+                // coarse/fine tune et la correction montent la hauteur jouée
+                sample.fine_tune = -(sample_header.pitch_correction as f32
+                    + Zone::sum(&zone, &preset_zone, COARSE_TUNE, 0) as f32 * 100.0
+                    + Zone::sum(&zone, &preset_zone, FINE_TUNE, 0) as f32);
+                sample.gain =
+                    centibels_to_gain(Zone::sum(&zone, &preset_zone, INITIAL_ATTENUATION, 0));
+                sample.pan =
+                    (Zone::sum(&zone, &preset_zone, PAN, 0) as f32 / 500.0).clamp(-1.0, 1.0);
+                sample.loop_mode = match zone.get(SAMPLE_MODES).unwrap_or(0) & 3 {
+                    1 => LoopMode::Continuous,
+                    3 => LoopMode::Sustain,
+                    _ => LoopMode::NoLoop,
+                };
+                sample.loop_start = (loop_start - start as i64).clamp(0, frames as i64) as usize;
+                sample.loop_end = (loop_end - start as i64).clamp(0, frames as i64) as usize;
+
+                let zone_envelope = Envelope {
+                    delay: timecents_to_seconds(Zone::sum(
+                        &zone,
+                        &preset_zone,
+                        DELAY_VOL_ENV,
+                        DEFAULT_TIMECENTS,
+                    )),
+                    attack: timecents_to_seconds(Zone::sum(
+                        &zone,
+                        &preset_zone,
+                        ATTACK_VOL_ENV,
+                        DEFAULT_TIMECENTS,
+                    )),
+                    hold: timecents_to_seconds(Zone::sum(
+                        &zone,
+                        &preset_zone,
+                        HOLD_VOL_ENV,
+                        DEFAULT_TIMECENTS,
+                    )),
+                    decay: timecents_to_seconds(Zone::sum(
+                        &zone,
+                        &preset_zone,
+                        DECAY_VOL_ENV,
+                        DEFAULT_TIMECENTS,
+                    )),
+                    sustain: centibels_to_gain(Zone::sum(&zone, &preset_zone, SUSTAIN_VOL_ENV, 0)),
+                    release: timecents_to_seconds(Zone::sum(
+                        &zone,
+                        &preset_zone,
+                        RELEASE_VOL_ENV,
+                        DEFAULT_TIMECENTS,
+                    )),
+                };
+                regions.push(InstrumentRegion {
+                    sample_id: next_id,
+                    key_range,
+                    velocity_range,
+                    seq_length: 1,
+                    seq_position: 1,
+                    seq_counter: 0,
+                    envelope: Some(zone_envelope),
+                });
+                samples.push(sample);
+                next_id += 1;
+            }
+        }
+
+        if regions.is_empty() {
+            continue;
+        }
+
+        instruments.push(Instrument::new(instrument_id, regions));
+        presets.push(Preset {
+            name: header.name.clone(),
+            bank: header.bank,
+            program: header.program.min(127) as u8,
+            instrument_id,
+        });
+    }
+
+    if presets.is_empty() {
+        return Err(Sf2Error::NoPresets);
+    }
+
+    for generator in unsupported {
+        warnings.push(format!("générateur non supporté : {}", generator));
+    }
+    warnings.dedup();
+
+    presets.sort_by_key(|p| (p.bank, p.program));
+
+    Ok(Sf2Import {
+        bank: SoundBank {
+            id: bank_id,
+            presets,
+        },
+        instruments,
+        samples,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAMES: usize = 100;
+
+    fn chunk(id: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut bytes = id.to_vec();
+        bytes.extend((data.len() as u32).to_le_bytes());
+        bytes.extend(data);
+        if data.len() % 2 == 1 {
+            bytes.push(0);
+        }
+        bytes
+    }
+
+    fn list(kind: &[u8; 4], chunks: &[Vec<u8>]) -> Vec<u8> {
+        let mut data = kind.to_vec();
+        data.extend(chunks.concat());
+        chunk(b"LIST", &data)
+    }
+
+    fn named(name: &str) -> Vec<u8> {
+        let mut bytes = name.as_bytes().to_vec();
+        bytes.resize(20, 0);
+        bytes
+    }
+
+    /// Générateurs d'une zone suivis du générateur terminal, puis l'enregistrement de fin
+    fn generators(zone: &[(usize, i16)], terminal: usize) -> Vec<u8> {
+        zone.iter()
+            .chain(&[(terminal, 0), (0, 0)])
+            .flat_map(|&(operator, amount)| {
+                let mut record = (operator as u16).to_le_bytes().to_vec();
+                record.extend(amount.to_le_bytes());
+                record
+            })
+            .collect()
+    }
+
+    /// Une zone de bag, puis le bag de fin
+    fn bags(generator_count: usize) -> Vec<u8> {
+        [0u16, 0, generator_count as u16 + 1, 0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect()
+    }
+
+    /// Banque d'un preset → un instrument → un sample de `FRAMES` frames (racine 60,
+    /// boucle 10..90)
+    fn bank(preset: &[(usize, i16)], instrument: &[(usize, i16)]) -> Vec<u8> {
+        let pool: Vec<u8> = (0..FRAMES as i16)
+            .flat_map(|i| (i * 100).to_le_bytes())
+            .collect();
+
+        let mut phdr = Vec::new();
+        for (name, bag) in [("Piano", 0u16), ("EOP", 1)] {
+            phdr.extend(named(name));
+            phdr.extend([0u16, 0, bag].iter().flat_map(|v| v.to_le_bytes()));
+            phdr.extend([0; 12]);
+        }
+        let mut inst = Vec::new();
+        for (name, bag) in [("Inst", 0u16), ("EOI", 1)] {
+            inst.extend(named(name));
+            inst.extend(bag.to_le_bytes());
+        }
+        let mut shdr = Vec::new();
+        for (name, end) in [("Sample", FRAMES as u32), ("EOS", 0)] {
+            shdr.extend(named(name));
+            let loop_end = end.saturating_sub(10);
+            for value in [0, end, 10.min(end), loop_end, 44_100] {
+                shdr.extend(value.to_le_bytes());
+            }
+            shdr.extend([60, 0, 0, 0, 1, 0]); // hauteur, correction, lien, type mono
+        }
+
+        let body = [
+            b"sfbk".to_vec(),
+            list(b"INFO", &[chunk(b"ifil", &[2, 0, 4, 0])]),
+            list(b"sdta", &[chunk(b"smpl", &pool)]),
+            list(
+                b"pdta",
+                &[
+                    chunk(b"phdr", &phdr),
+                    chunk(b"pbag", &bags(preset.len())),
+                    chunk(b"pgen", &generators(preset, INSTRUMENT)),
+                    chunk(b"inst", &inst),
+                    chunk(b"ibag", &bags(instrument.len())),
+                    chunk(b"igen", &generators(instrument, SAMPLE_ID)),
+                    chunk(b"shdr", &shdr),
+                ],
+            ),
+        ]
+        .concat();
+        chunk(b"RIFF", &body)
+    }
+
+    #[test]
+    fn imports_a_minimal_bank() {
+        let imported = import(&bank(&[], &[]), 7, 100).unwrap();
+        assert_eq!(imported.bank.id, 7);
+        assert_eq!(imported.bank.presets.len(), 1);
+        assert_eq!(imported.bank.presets[0].name, "Piano");
+        assert_eq!(imported.bank.presets[0].instrument_id, 100);
+        assert_eq!(imported.instruments.len(), 1);
+
+        let sample = &imported.samples[0];
+        assert_eq!(sample.id, 101);
+        assert_eq!(sample.frames(), FRAMES);
+        assert_eq!(sample.root_key, 60);
+        assert_eq!((sample.loop_start, sample.loop_end), (10, 90));
+    }
+
+    #[test]
+    fn preset_generators_add_to_the_instrument() {
+        let mut instrument = Zone::empty();
+        instrument.generators[FINE_TUNE] = Some(10);
+        let mut preset = Zone::empty();
+        preset.generators[FINE_TUNE] = Some(5);
+        preset.generators[PAN] = Some(-100);
+        assert_eq!(Zone::sum(&instrument, &preset, FINE_TUNE, 0), 15);
+        // sans valeur d'instrument, le décalage s'applique à la valeur par défaut
+        assert_eq!(Zone::sum(&instrument, &preset, PAN, 0), -100);
+        assert_eq!(
+            Zone::sum(&instrument, &preset, ATTACK_VOL_ENV, DEFAULT_TIMECENTS),
+            DEFAULT_TIMECENTS as i32
+        );
+
+        let imported = import(
+            &bank(&[(FINE_TUNE, 5)], &[(FINE_TUNE, 10), (COARSE_TUNE, 1)]),
+            0,
+            0,
+        )
+        .unwrap();
+        assert_eq!(imported.samples[0].fine_tune, -115.0);
+    }
+
+    #[test]
+    fn preset_level_instrument_generators_are_ignored() {
+        let mut preset = Zone::empty();
+        preset.generators[START_ADDRS_OFFSET] = Some(10);
+        assert_eq!(Zone::sum(&Zone::empty(), &preset, START_ADDRS_OFFSET, 0), 0);
+
+        let imported = import(
+            &bank(
+                &[
+                    (START_ADDRS_OFFSET, 10),
+                    (END_ADDRS_OFFSET, -10),
+                    (OVERRIDING_ROOT_KEY, 40),
+                    (SAMPLE_MODES, 1),
+                ],
+                &[],
+            ),
+            0,
+            0,
+        )
+        .unwrap();
+        let sample = &imported.samples[0];
+        assert_eq!(sample.frames(), FRAMES);
+        assert_eq!(sample.root_key, 60);
+        assert_eq!(sample.loop_mode, LoopMode::NoLoop);
+
+        // les mêmes générateurs au niveau instrument s'appliquent
+        let imported = import(
+            &bank(&[], &[(START_ADDRS_OFFSET, 10), (OVERRIDING_ROOT_KEY, 40)]),
+            0,
+            0,
+        )
+        .unwrap();
+        assert_eq!(imported.samples[0].frames(), FRAMES - 10);
+        assert_eq!(imported.samples[0].root_key, 40);
+    }
+
+    #[test]
+    fn truncated_banks_never_panic() {
+        let bytes = bank(&[(PAN, 100)], &[(START_ADDRS_OFFSET, 4)]);
+        for length in 0..bytes.len() {
+            let _ = import(&bytes[..length], 0, 0);
+        }
+        assert!(matches!(
+            import(&bytes[..8], 0, 0),
+            Err(Sf2Error::NotSoundFont)
+        ));
+        // pdta coupé au milieu de shdr : enregistrement incomplet
+        assert!(matches!(
+            import(&bytes[..bytes.len() - 10], 0, 0),
+            Err(Sf2Error::InvalidChunk("shdr"))
+        ));
+        assert!(import(&bytes, 0, 0).is_ok());
+    }
+
+    #[test]
+    fn oversized_chunks_are_clamped() {
+        let mut bytes = bank(&[], &[]);
+        // tailles déclarées du RIFF et de la liste pdta (dernier chunk) bien trop grandes
+        bytes[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+        let pdta = bytes.windows(4).position(|w| w == b"pdta").unwrap();
+        bytes[pdta - 4..pdta].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(import(&bytes, 0, 0).is_ok());
+
+        // un enregistrement incomplet invalide le chunk
+        let generator = |r: &mut ByteReader| Some((r.u16()?, r.i16()?));
+        assert!(matches!(
+            records(&[0; 6], 4, "pgen", generator),
+            Err(Sf2Error::InvalidChunk("pgen"))
+        ));
+        assert!(matches!(
+            records(&[], 4, "pgen", generator),
+            Err(Sf2Error::InvalidChunk("pgen"))
+        ));
+    }
+}
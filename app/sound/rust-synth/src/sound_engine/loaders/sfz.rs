@@ -0,0 +1,626 @@
+use std::{collections::BTreeSet, fmt, sync::Arc};
+
+use crate::{
+    sound_engine::{
+        loaders::{
+            bundle::{self, BundleFile},
+            wav::{self, WavFile},
+        },
+        synthetizer::instrument::{Envelope, Instrument, InstrumentRegion},
+    },
+    utils::types::{LoopMode, Sample},
+};
+
+const SUPPORTED_OPCODES: &[&str] = &[
+    "sample",
+    "default_path",
+    "key",
+    "lokey",
+    "hikey",
+    "pitch_keycenter",
+    "lovel",
+    "hivel",
+    "loop_mode",
+    "loopmode",
+    "loop_start",
+    "loopstart",
+    "loop_end",
+    "loopend",
+    "ampeg_delay",
+    "ampeg_attack",
+    "ampeg_hold",
+    "ampeg_decay",
+    "ampeg_sustain",
+    "ampeg_release",
+    "tune",
+    "transpose",
+    "volume",
+    "pan",
+    "seq_length",
+    "seq_position",
+];
+
+#[derive(Debug)]
+pub enum SfzError {
+    InvalidBundle,
+    MissingSfzFile,
+    NoRegions,
+}
+
+impl fmt::Display for SfzError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SfzError::InvalidBundle => write!(f, "paquet de fichiers invalide"),
+            SfzError::MissingSfzFile => write!(f, "aucun fichier .sfz dans le paquet"),
+            SfzError::NoRegions => write!(f, "aucune région jouable"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Header(String),
+    Opcode(String, String),
+}
+
+type Opcodes = Vec<(String, String)>;
+
+/// Résultat du parsing : les opcodes de chaque région, hérités de
+/// `<global>`, `<master>` et `<group>`
+pub struct SfzFile {
+    pub control: Opcodes,
+    pub regions: Vec<Opcodes>,
+    pub warnings: Vec<String>,
+}
+
+pub struct SfzRegion {
+    pub sample: String,
+    pub key_range: (u8, u8),
+    pub pitch_keycenter: Option<u8>, // None : hauteur lue dans le fichier (`sample`)
+    pub velocity_range: (u8, u8),
+    pub loop_mode: Option<LoopMode>,
+    pub loop_start: Option<usize>,
+    pub loop_end: Option<usize>, // inclusif, comme dans le fichier
+    pub envelope: Option<Envelope>,
+    pub tune: f32, // cents
+    pub transpose: i32,
+    pub volume: f32, // dB
+    pub pan: f32,    // -100 à 100
+    pub seq_length: u32,
+    pub seq_position: u32,
+}
+
+/// Instrument décodé, à enregistrer dans le `SampleManager` avec ses samples
+pub struct SfzImport {
+    pub instrument: Instrument,
+    pub samples: Vec<Sample>,
+    pub warnings: Vec<String>,
+}
+
+/// Retire les commentaires et applique les `#define`
+fn preprocess(text: &str, warnings: &mut Vec<String>) -> String {
+    let mut without_blocks = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("/*") {
+        without_blocks.push_str(&rest[..start]);
+        rest = rest[start + 2..]
+            .find("*/")
+            .map_or("", |end| &rest[start + 2 + end + 2..]);
+    }
+    without_blocks.push_str(rest);
+
+    let mut defines: Vec<(String, String)> = Vec::new();
+    let mut output = String::with_capacity(without_blocks.len());
+
+    for line in without_blocks.lines() {
+        let mut line = line.split("//").next().unwrap_or("").to_string();
+        let trimmed = line.trim_start();
+
+        if let Some(define) = trimmed.strip_prefix("#define") {
+            let mut parts = define.split_whitespace();
+            if let (Some(name), Some(value)) = (parts.next(), parts.next()) {
+                defines.push((name.to_string(), value.to_string()));
+                // les noms les plus longs d'abord pour que $A ne remplace pas le début de $AB
+                defines.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+            }
+            continue;
+        }
+        if trimmed.starts_with("#include") {
+            warnings.push(format!("directive non supportée : {}", trimmed.trim()));
+            continue;
+        }
+
+        for (name, value) in &defines {
+            line = line.replace(name.as_str(), value);
+        }
+
+        output.push_str(&line);
+        output.push('\n');
+    }
+
+    output
+}
+
+fn is_opcode_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_'
+}
+
+/// `nom=` commence à cette position ?
+fn opcode_starts_at(bytes: &[u8], pos: usize) -> bool {
+    let mut end = pos;
+    while end < bytes.len() && is_opcode_char(bytes[end]) {
+        end += 1;
+    }
+    end > pos && end < bytes.len() && bytes[end] == b'='
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+
+    for line in text.lines() {
+        let bytes = line.as_bytes();
+        let mut pos = 0;
+
+        while pos < bytes.len() {
+            if bytes[pos].is_ascii_whitespace() {
+                pos += 1;
+                continue;
+            }
+
+            if bytes[pos] == b'<' {
+                let end = line[pos..].find('>').map_or(bytes.len(), |e| pos + e);
+                tokens.push(Token::Header(line[pos + 1..end].trim().to_lowercase()));
+                pos = end + 1;
+                continue;
+            }
+
+            if !opcode_starts_at(bytes, pos) {
+                // mot isolé : on passe au suivant
+                while pos < bytes.len() && !bytes[pos].is_ascii_whitespace() {
+                    pos += 1;
+                }
+                continue;
+            }
+
+            let equal = pos + line[pos..].find('=').unwrap_or(0);
+            let name = line[pos..equal].to_lowercase();
+
+            // la valeur s'étend jusqu'au prochain opcode ou header (les chemins
+            // de `sample` peuvent contenir des espaces)
+            let value_start = equal + 1;
+            let mut value_end = value_start;
+            while value_end < bytes.len() {
+                if bytes[value_end] == b'<' {
+                    break;
+                }
+                if bytes[value_end].is_ascii_whitespace()
+                    && value_end + 1 < bytes.len()
+                    && (opcode_starts_at(bytes, value_end + 1) || bytes[value_end + 1] == b'<')
+                {
+                    break;
+                }
+                value_end += 1;
+            }
+
+            tokens.push(Token::Opcode(
+                name,
+                line[value_start..value_end].trim().to_string(),
+            ));
+            pos = value_end;
+        }
+    }
+
+    tokens
+}
+
+pub fn parse(text: &str) -> SfzFile {
+    let mut warnings = Vec::new();
+    let text = preprocess(text, &mut warnings);
+
+    #[derive(PartialEq)]
+    enum Level {
+        None,
+        Control,
+        Global,
+        Master,
+        Group,
+        Region,
+        Unsupported,
+    }
+
+    let mut control = Opcodes::new();
+    let mut global = Opcodes::new();
+    let mut master = Opcodes::new();
+    let mut group = Opcodes::new();
+    let mut region: Option<Opcodes> = None;
+    let mut regions = Vec::new();
+    let mut level = Level::None;
+    let mut unsupported_headers = BTreeSet::new();
+
+    for token in tokenize(&text) {
+        match token {
+            Token::Header(header) => {
+                if let Some(finished) = region.take() {
+                    regions.push(finished);
+                }
+                level = match header.as_str() {
+                    "control" => Level::Control,
+                    "global" => {
+                        global.clear();
+                        master.clear();
+                        group.clear();
+                        Level::Global
+                    }
+                    "master" => {
+                        master.clear();
+                        group.clear();
+                        Level::Master
+                    }
+                    "group" => {
+                        group.clear();
+                        Level::Group
+                    }
+                    "region" => {
+                        let mut inherited = global.clone();
+                        inherited.extend(master.iter().cloned());
+                        inherited.extend(group.iter().cloned());
+                        region = Some(inherited);
+                        Level::Region
+                    }
+                    _ => {
+                        unsupported_headers.insert(header);
+                        Level::Unsupported
+                    }
+                };
+            }
+            Token::Opcode(name, value) => match level {
+                Level::Control => control.push((name, value)),
+                Level::Global => global.push((name, value)),
+                Level::Master => master.push((name, value)),
+                Level::Group => group.push((name, value)),
+                Level::Region => {
+                    if let Some(region) = region.as_mut() {
+                        region.push((name, value));
+                    }
+                }
+                Level::None | Level::Unsupported => {}
+            },
+        }
+    }
+    if let Some(finished) = region.take() {
+        regions.push(finished);
+    }
+
+    for header in unsupported_headers {
+        warnings.push(format!("header non supporté : <{}>", header));
+    }
+
+    SfzFile {
+        control,
+        regions,
+        warnings,
+    }
+}
+
+/// Note MIDI en nombre ou en nom (`c4` = 60, `f#3`, `eb2`...)
+fn parse_key(value: &str) -> Option<u8> {
+    if let Ok(number) = value.parse::<i32>() {
+        return (0..=127).contains(&number).then_some(number as u8);
+    }
+
+    let lower = value.to_lowercase();
+    let mut chars = lower.chars().peekable();
+    let mut pitch = match chars.next()? {
+        'c' => 0,
+        'd' => 2,
+        'e' => 4,
+        'f' => 5,
+        'g' => 7,
+        'a' => 9,
+        'b' => 11,
+        _ => return None,
+    };
+    match chars.peek() {
+        Some('#') => {
+            pitch += 1;
+            chars.next();
+        }
+        Some('b') => {
+            pitch -= 1;
+            chars.next();
+        }
+        _ => {}
+    }
+    let octave: i32 = chars.collect::<String>().parse().ok()?;
+    let note = (octave + 1) * 12 + pitch;
+    (0..=127).contains(&note).then_some(note as u8)
+}
+
+fn lookup<'a>(opcodes: &'a Opcodes, names: &[&str]) -> Option<&'a str> {
+    // la dernière définition l'emporte (région > groupe > master > global)
+    opcodes
+        .iter()
+        .rev()
+        .find(|(name, _)| names.contains(&name.as_str()))
+        .map(|(_, value)| value.as_str())
+}
+
+fn number<T: std::str::FromStr>(opcodes: &Opcodes, names: &[&str]) -> Option<T> {
+    lookup(opcodes, names).and_then(|v| v.parse().ok())
+}
+
+impl SfzRegion {
+    pub fn from_opcodes(opcodes: &Opcodes, unsupported: &mut BTreeSet<String>) -> Self {
+        for (name, _) in opcodes {
+            if !SUPPORTED_OPCODES.contains(&name.as_str()) {
+                unsupported.insert(name.clone());
+            }
+        }
+
+        let key = lookup(opcodes, &["key"]).and_then(parse_key);
+        // `key` fixe les deux bornes : la définition la plus récente l'emporte
+        let lokey = lookup(opcodes, &["lokey", "key"]).and_then(parse_key);
+        let hikey = lookup(opcodes, &["hikey", "key"]).and_then(parse_key);
+        let pitch_keycenter = match lookup(opcodes, &["pitch_keycenter"]) {
+            Some("sample") => None,
+            Some(value) => parse_key(value).or(Some(60)),
+            None => key.or(Some(60)),
+        };
+
+        let loop_mode = lookup(opcodes, &["loop_mode", "loopmode"]).and_then(|v| match v {
+            "no_loop" => Some(LoopMode::NoLoop),
+            "one_shot" => Some(LoopMode::OneShot),
+            "loop_continuous" => Some(LoopMode::Continuous),
+            "loop_sustain" => Some(LoopMode::Sustain),
+            _ => None,
+        });
+
+        let has_envelope = opcodes.iter().any(|(name, _)| name.starts_with("ampeg_"));
+        let envelope = has_envelope.then(|| Envelope {
+            delay: number(opcodes, &["ampeg_delay"]).unwrap_or(0.0),
+            attack: number(opcodes, &["ampeg_attack"]).unwrap_or(0.0),
+            hold: number(opcodes, &["ampeg_hold"]).unwrap_or(0.0),
+            decay: number(opcodes, &["ampeg_decay"]).unwrap_or(0.0),
+            sustain: number::<f32>(opcodes, &["ampeg_sustain"]).unwrap_or(100.0) / 100.0,
+            release: number(opcodes, &["ampeg_release"]).unwrap_or(0.001),
+        });
+
+        SfzRegion {
+            sample: lookup(opcodes, &["sample"]).unwrap_or("").to_string(),
+            key_range: (lokey.unwrap_or(0), hikey.unwrap_or(127)),
+            pitch_keycenter,
+            velocity_range: (
+                number(opcodes, &["lovel"]).unwrap_or(0).min(127),
+                number(opcodes, &["hivel"]).unwrap_or(127).min(127),
+            ),
+            loop_mode,
+            loop_start: number(opcodes, &["loop_start", "loopstart"]),
+            loop_end: number(opcodes, &["loop_end", "loopend"]),
+            envelope,
+            tune: number(opcodes, &["tune"]).unwrap_or(0.0),
+            transpose: number(opcodes, &["transpose"]).unwrap_or(0),
+            volume: number(opcodes, &["volume"]).unwrap_or(0.0),
+            pan: number::<f32>(opcodes, &["pan"])
+                .unwrap_or(0.0)
+                .clamp(-100.0, 100.0),
+            seq_length: number(opcodes, &["seq_length"]).unwrap_or(1).max(1),
+            seq_position: number(opcodes, &["seq_position"]).unwrap_or(1).max(1),
+        }
+    }
+
+    /// Sample configuré pour cette région, à partir du fichier décodé
+    pub fn to_sample(&self, id: u32, wav: &WavFile, values: &Arc<[f32]>) -> Sample {
+        let mut sample = Sample::new(id, values.clone(), wav.channels as u8, 0);
+        let frames = sample.frames();
+
+        sample.sample_rate = wav.sample_rate as f32;
+        sample.key_range = self.key_range;
+        sample.velocity_range = self.velocity_range;
+        sample.gain = 10f32.powf((self.volume + wav.gain_db) / 20.0);
+        sample.pan = self.pan / 100.0;
+
+        match self.pitch_keycenter {
+            Some(key) => sample.root_key = key,
+            None => {
+                sample.root_key = wav.root_key.unwrap_or(60);
+                sample.fine_tune = wav.fine_tune;
+            }
+        }
+        // tune/transpose montent la hauteur jouée : la référence descend d'autant
+        sample.fine_tune -= self.tune + self.transpose as f32 * 100.0;
+
+        let file_loop = wav.loop_points;
+        sample.loop_mode = self.loop_mode.unwrap_or(if file_loop.is_some() {
+            LoopMode::Continuous
+        } else {
+            LoopMode::NoLoop
+        });
+
+        let (file_start, file_end) = file_loop.unwrap_or((0, frames));
+        sample.loop_start = self.loop_start.unwrap_or(file_start).min(frames);
+        sample.loop_end = self.loop_end.map_or(file_end, |end| end + 1).min(frames);
+
+        sample
+    }
+}
+
+/// Importe un paquet SFZ : décode les fichiers référencés et crée un sample par région,
+/// numérotés à partir de `first_sample_id`, pour l'instrument `instrument_id`
+pub fn import(
+    bytes: &[u8],
+    instrument_id: u32,
+    first_sample_id: u32,
+) -> Result<SfzImport, SfzError> {
+    let files = bundle::unpack(bytes).ok_or(SfzError::InvalidBundle)?;
+    let sfz_file = files
+        .iter()
+        .find(|f| f.name.to_lowercase().ends_with(".sfz"))
+        .ok_or(SfzError::MissingSfzFile)?;
+
+    let parsed = parse(&String::from_utf8_lossy(sfz_file.data));
+    let mut warnings = parsed.warnings;
+
+    let sfz_dir = sfz_file
+        .name
+        .replace('\\', "/")
+        .rsplit_once('/')
+        .map_or(String::new(), |(dir, _)| format!("{}/", dir));
+    let default_path = lookup(&parsed.control, &["default_path"])
+        .unwrap_or("")
+        .replace('\\', "/");
+
+    let mut unsupported = BTreeSet::new();
+    let mut decoded: Vec<(String, WavFile, Arc<[f32]>)> = Vec::new();
+    let mut regions = Vec::new();
+    let mut samples = Vec::new();
+    let mut next_id = first_sample_id;
+
+    for opcodes in &parsed.regions {
+        let region = SfzRegion::from_opcodes(opcodes, &mut unsupported);
+        if region.sample.is_empty() {
+            warnings.push("région sans opcode sample ignorée".to_string());
+            continue;
+        }
+
+        let path = format!(
+            "{}{}{}",
+            sfz_dir,
+            default_path,
+            region.sample.replace('\\', "/")
+        );
+        let path = bundle::normalize_path(&path);
+
+        if !decoded.iter().any(|(p, _, _)| *p == path) {
+            match load_wav(&files, &path) {
+                Ok(mut wav) => {
+                    // un seul buffer partagé par toutes les régions du même fichier
+                    let values: Arc<[f32]> = std::mem::take(&mut wav.values).into();
+                    decoded.push((path.clone(), wav, values));
+                }
+                Err(message) => {
+                    warnings.push(message);
+                    continue;
+                }
+            }
+        }
+        let Some((_, wav, values)) = decoded.iter().find(|(p, _, _)| *p == path) else {
+            continue;
+        };
+
+        let sample = region.to_sample(next_id, wav, values);
+        regions.push(InstrumentRegion {
+            sample_id: next_id,
+            key_range: sample.key_range,
+            velocity_range: sample.velocity_range,
+            seq_length: region.seq_length,
+            seq_position: region.seq_position,
+            seq_counter: 0,
+            envelope: region.envelope,
+        });
+        samples.push(sample);
+        next_id += 1;
+    }
+
+    for opcode in unsupported {
+        warnings.push(format!("opcode non supporté : {}", opcode));
+    }
+    warnings.dedup();
+
+    if regions.is_empty() {
+        return Err(SfzError::NoRegions);
+    }
+
+    Ok(SfzImport {
+        instrument: Instrument::new(instrument_id, regions),
+        samples,
+        warnings,
+    })
+}
+
+fn load_wav(files: &[BundleFile], path: &str) -> Result<WavFile, String> {
+    let file = bundle::find(files, path).ok_or(format!("fichier introuvable : {}", path))?;
+    wav::decode(file.data).map_err(|err| format!("{} : {}", file.name, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regions(text: &str) -> Vec<SfzRegion> {
+        let mut unsupported = BTreeSet::new();
+        parse(text)
+            .regions
+            .iter()
+            .map(|opcodes| SfzRegion::from_opcodes(opcodes, &mut unsupported))
+            .collect()
+    }
+
+    #[test]
+    fn regions_inherit_global_and_group() {
+        let regions = regions(
+            "<global> volume=-6 lokey=10
+             <group> lokey=20 pan=50
+             <region> sample=a.wav
+             <region> sample=b.wav lokey=30
+             <group> tune=5
+             <region> sample=c.wav",
+        );
+        assert_eq!(regions.len(), 3);
+
+        assert_eq!(regions[0].sample, "a.wav");
+        assert_eq!(regions[0].volume, -6.0);
+        assert_eq!(regions[0].key_range, (20, 127));
+        assert_eq!(regions[0].pan, 50.0);
+
+        // la région l'emporte sur son groupe
+        assert_eq!(regions[1].key_range, (30, 127));
+        assert_eq!(regions[1].pan, 50.0);
+
+        // un nouveau groupe remplace le précédent, le global reste
+        assert_eq!(regions[2].volume, -6.0);
+        assert_eq!(regions[2].key_range, (10, 127));
+        assert_eq!(regions[2].pan, 0.0);
+        assert_eq!(regions[2].tune, 5.0);
+    }
+
+    #[test]
+    fn global_resets_master_and_group() {
+        let regions = regions(
+            "<master> transpose=12
+             <group> pan=-30
+             <region> sample=a.wav
+             <global> volume=3
+             <region> sample=b.wav",
+        );
+        assert_eq!(regions[0].transpose, 12);
+        assert_eq!(regions[0].pan, -30.0);
+        assert_eq!(regions[1].transpose, 0);
+        assert_eq!(regions[1].pan, 0.0);
+        assert_eq!(regions[1].volume, 3.0);
+    }
+
+    #[test]
+    fn key_sets_both_bounds_and_the_pitch_center() {
+        let regions = regions("<group> lokey=c3 hikey=c5 <region> sample=a.wav key=e4");
+        assert_eq!(regions[0].key_range, (64, 64));
+        assert_eq!(regions[0].pitch_keycenter, Some(64));
+    }
+
+    #[test]
+    fn envelope_is_per_region() {
+        let regions = regions(
+            "<group> ampeg_release=0.5
+             <region> sample=a.wav
+             <region> sample=b.wav ampeg_attack=0.1
+             <global>
+             <region> sample=c.wav",
+        );
+        let first = regions[0].envelope.unwrap();
+        assert_eq!(first.release, 0.5);
+        assert_eq!(first.attack, 0.0);
+        let second = regions[1].envelope.unwrap();
+        assert_eq!(second.release, 0.5);
+        assert_eq!(second.attack, 0.1);
+        assert!(regions[2].envelope.is_none());
+    }
+}
@@ -0,0 +1,526 @@
+use std::fmt;
+
+use crate::{
+    sound_engine::loaders::riff::{ByteReader, RiffChunks},
+    utils::types::Sample,
+};
+
+const FORMAT_PCM: u16 = 1;
+const FORMAT_FLOAT: u16 = 3;
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+#[derive(Debug)]
+pub enum WavError {
+    NotRiff,
+    MissingFormat,
+    MissingData,
+    UnsupportedEncoding {
+        format_tag: u16,
+        bits_per_sample: u16,
+    },
+    UnsupportedChannels(u16),
+    Truncated,
+}
+
+impl fmt::Display for WavError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WavError::NotRiff => write!(f, "pas un fichier RIFF/RF64 WAVE"),
+            WavError::MissingFormat => write!(f, "chunk fmt absent"),
+            WavError::MissingData => write!(f, "chunk data absent"),
+            WavError::UnsupportedEncoding {
+                format_tag,
+                bits_per_sample,
+            } => write!(
+                f,
+                "encodage non supporté (format {}, {} bits)",
+                format_tag, bits_per_sample
+            ),
+            WavError::UnsupportedChannels(channels) => {
+                write!(f, "{} canaux non supportés (mono ou stéréo)", channels)
+            }
+            WavError::Truncated => write!(f, "fichier tronqué"),
+        }
+    }
+}
+
+struct WavFormat {
+    format_tag: u16,
+    channels: u16,
+    sample_rate: u32,
+    block_align: u16,
+    bits_per_sample: u16,
+}
+
+pub struct WavFile {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub values: Vec<f32>, // planar : canal 0 puis canal 1
+    pub root_key: Option<u8>,
+    pub fine_tune: f32, // cents
+    pub gain_db: f32,
+    pub loop_points: Option<(usize, usize)>, // fin exclusive
+    pub key_range: Option<(u8, u8)>,
+    pub velocity_range: Option<(u8, u8)>,
+}
+
+/// Décode un fichier WAV (RIFF ou RF64) avec ses chunks `smpl` et `inst`
+pub fn decode(bytes: &[u8]) -> Result<WavFile, WavError> {
+    let mut reader = ByteReader::new(bytes);
+    let container = reader.tag().ok_or(WavError::NotRiff)?;
+    reader.skip(4).ok_or(WavError::NotRiff)?;
+    let wave = reader.tag().ok_or(WavError::NotRiff)?;
+
+    if (&container != b"RIFF" && &container != b"RF64") || &wave != b"WAVE" {
+        return Err(WavError::NotRiff);
+    }
+
+    let body = reader.take(reader.remaining()).ok_or(WavError::Truncated)?;
+    let mut chunks = RiffChunks::new(body);
+
+    let mut format = None;
+    let mut data = None;
+    let mut wav = WavFile {
+        sample_rate: 0,
+        channels: 0,
+        values: Vec::new(),
+        root_key: None,
+        fine_tune: 0.0,
+        gain_db: 0.0,
+        loop_points: None,
+        key_range: None,
+        velocity_range: None,
+    };
+    let mut smpl_found = false;
+
+    while let Some(chunk) = chunks.next() {
+        match &chunk.id {
+            b"ds64" => {
+                let mut ds64 = ByteReader::new(chunk.data);
+                ds64.skip(8).ok_or(WavError::Truncated)?; // taille RIFF
+                chunks.data_size_override = Some(ds64.u64().ok_or(WavError::Truncated)?);
+            }
+            b"fmt " => format = Some(parse_format(chunk.data)?),
+            b"data" => data = Some(chunk.data),
+            b"smpl" => {
+                parse_smpl(chunk.data, &mut wav);
+                smpl_found = true;
+            }
+            b"inst" => parse_inst(chunk.data, &mut wav, smpl_found),
+            _ => {}
+        }
+    }
+
+    let format = format.ok_or(WavError::MissingFormat)?;
+    let data = data.ok_or(WavError::MissingData)?;
+
+    if format.channels == 0 || format.channels > 2 {
+        return Err(WavError::UnsupportedChannels(format.channels));
+    }
+
+    wav.sample_rate = format.sample_rate;
+    wav.channels = format.channels;
+    wav.values = decode_pcm(data, &format)?;
+
+    // les boucles hors du sample sont ignorées
+    let frames = wav.values.len() / format.channels as usize;
+    wav.loop_points = wav
+        .loop_points
+        .filter(|&(start, end)| start < end && end <= frames);
+
+    Ok(wav)
+}
+
+fn parse_format(data: &[u8]) -> Result<WavFormat, WavError> {
+    let mut reader = ByteReader::new(data);
+    let mut format_tag = reader.u16().ok_or(WavError::Truncated)?;
+    let channels = reader.u16().ok_or(WavError::Truncated)?;
+    let sample_rate = reader.u32().ok_or(WavError::Truncated)?;
+    reader.skip(4).ok_or(WavError::Truncated)?; // octets par seconde
+    let block_align = reader.u16().ok_or(WavError::Truncated)?;
+    let bits_per_sample = reader.u16().ok_or(WavError::Truncated)?;
+
+    if format_tag == FORMAT_EXTENSIBLE {
+        // cbSize, bits valides, masque des canaux, puis le GUID du sous-format
+        reader.skip(8).ok_or(WavError::Truncated)?;
+        format_tag = reader.u16().ok_or(WavError::Truncated)?;
+    }
+
+    let supported = matches!(
+        (format_tag, bits_per_sample),
+        (FORMAT_PCM, 8 | 16 | 24 | 32) | (FORMAT_FLOAT, 32 | 64)
+    );
+    if !supported || block_align == 0 {
+        return Err(WavError::UnsupportedEncoding {
+            format_tag,
+            bits_per_sample,
+        });
+    }
+
+    Ok(WavFormat {
+        format_tag,
+        channels,
+        sample_rate,
+        block_align,
+        bits_per_sample,
+    })
+}
+
+fn decode_pcm(data: &[u8], format: &WavFormat) -> Result<Vec<f32>, WavError> {
+    let channels = format.channels as usize;
+    let block_align = format.block_align as usize;
+    let bytes_per_sample = block_align / channels;
+
+    if bytes_per_sample * 8 < format.bits_per_sample as usize {
+        return Err(WavError::UnsupportedEncoding {
+            format_tag: format.format_tag,
+            bits_per_sample: format.bits_per_sample,
+        });
+    }
+
+    let frames = data.len() / block_align;
+    let mut values = vec![0.0; frames * channels];
+
+    for (frame, block) in data.chunks_exact(block_align).enumerate() {
+        for channel in 0..channels {
+            let b = &block[channel * bytes_per_sample..];
+            let value = match (format.format_tag, format.bits_per_sample) {
+                (FORMAT_PCM, 8) => (b[0] as f32 - 128.0) / 128.0,
+                (FORMAT_PCM, 16) => i16::from_le_bytes([b[0], b[1]]) as f32 / 32_768.0,
+                (FORMAT_PCM, 24) => {
+                    // extension de signe via le décalage arithmétique
+                    let raw = i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8;
+                    raw as f32 / 8_388_608.0
+                }
+                (FORMAT_PCM, _) => {
+                    i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0
+                }
+                (_, 32) => f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+                _ => f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]) as f32,
+            };
+            values[channel * frames + frame] = value;
+        }
+    }
+
+    Ok(values)
+}
+
+fn parse_smpl(data: &[u8], wav: &mut WavFile) {
+    let mut reader = ByteReader::new(data);
+    let mut parse = || -> Option<()> {
+        reader.skip(12)?; // fabricant, produit, période
+        let unity_note = reader.u32()?;
+        let pitch_fraction = reader.u32()?;
+        reader.skip(8)?; // format et offset SMPTE
+        let loop_count = reader.u32()?;
+        reader.skip(4)?;
+
+        if unity_note < 128 {
+            wav.root_key = Some(unity_note as u8);
+        }
+        // fraction de demi-ton sur 32 bits
+        wav.fine_tune = (pitch_fraction as f64 / 4_294_967_296.0 * 100.0) as f32;
+
+        if loop_count > 0 {
+            reader.skip(8)?; // identifiant du cue et type de boucle
+            let start = reader.u32()? as usize;
+            let end = reader.u32()? as usize;
+            wav.loop_points = Some((start, end + 1)); // fin inclusive dans le fichier
+        }
+        Some(())
+    };
+    let _ = parse();
+}
+
+fn parse_inst(data: &[u8], wav: &mut WavFile, smpl_found: bool) {
+    let mut reader = ByteReader::new(data);
+    let mut parse = || -> Option<()> {
+        let unshifted_note = reader.u8()?;
+        let fine_tune = reader.i8()?;
+        let gain_db = reader.i8()?;
+        let low_note = reader.u8()?;
+        let high_note = reader.u8()?;
+        let low_velocity = reader.u8()?;
+        let high_velocity = reader.u8()?;
+
+        // `smpl` fait foi pour la hauteur quand il est présent
+        if !smpl_found || wav.root_key.is_none() {
+            wav.root_key = Some(unshifted_note.min(127));
+            wav.fine_tune = fine_tune as f32;
+        }
+        wav.gain_db = gain_db as f32;
+        wav.key_range = Some((low_note.min(127), high_note.min(127)));
+        wav.velocity_range = Some((low_velocity.min(127), high_velocity.min(127)));
+        Some(())
+    };
+    let _ = parse();
+}
+
+impl WavFile {
+    pub fn into_sample(self, id: u32) -> Sample {
+        let mut sample = Sample::new(id, self.values, self.channels as u8, 0);
+        sample.sample_rate = self.sample_rate as f32;
+        sample.fine_tune = self.fine_tune;
+        sample.gain = 10f32.powf(self.gain_db / 20.0);
+
+        if let Some(root_key) = self.root_key {
+            sample.root_key = root_key;
+        }
+        if let Some((start, end)) = self.loop_points {
+            sample.loop_start = start;
+            sample.loop_end = end;
+        }
+        if let Some(range) = self.key_range {
+            sample.key_range = range;
+        }
+        if let Some(range) = self.velocity_range {
+            sample.velocity_range = range;
+        }
+        sample
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(id: &[u8; 4], declared: u32, data: &[u8]) -> Vec<u8> {
+        let mut bytes = id.to_vec();
+        bytes.extend(declared.to_le_bytes());
+        bytes.extend(data);
+        if data.len() % 2 == 1 {
+            bytes.push(0);
+        }
+        bytes
+    }
+
+    fn file(container: &[u8; 4], chunks: &[Vec<u8>]) -> Vec<u8> {
+        let body = chunks.concat();
+        let mut bytes = container.to_vec();
+        bytes.extend((4 + body.len() as u32).to_le_bytes());
+        bytes.extend(b"WAVE");
+        bytes.extend(body);
+        bytes
+    }
+
+    /// Chunk `fmt ` à 44,1 kHz, échantillons entrelacés sur `bits` bits
+    fn format(format_tag: u16, channels: u16, bits: u16) -> Vec<u8> {
+        let block_align = channels * bits / 8;
+        let mut data = format_tag.to_le_bytes().to_vec();
+        data.extend(channels.to_le_bytes());
+        data.extend(44_100u32.to_le_bytes());
+        data.extend((44_100 * block_align as u32).to_le_bytes());
+        data.extend(block_align.to_le_bytes());
+        data.extend(bits.to_le_bytes());
+        chunk(b"fmt ", data.len() as u32, &data)
+    }
+
+    fn data(bytes: &[u8]) -> Vec<u8> {
+        chunk(b"data", bytes.len() as u32, bytes)
+    }
+
+    /// WAV PCM 16 bits mono ; `data_size` remplace la taille déclarée du chunk `data`
+    fn wav(values: &[i16], data_size: Option<u32>) -> Vec<u8> {
+        let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let declared = data_size.unwrap_or(bytes.len() as u32);
+        file(
+            b"RIFF",
+            &[format(FORMAT_PCM, 1, 16), chunk(b"data", declared, &bytes)],
+        )
+    }
+
+    #[test]
+    fn decodes_pcm16() {
+        let decoded = decode(&wav(&[0, 16_384, -32_768], None)).unwrap();
+        assert_eq!(decoded.channels, 1);
+        assert_eq!(decoded.sample_rate, 44_100);
+        assert_eq!(decoded.values, vec![0.0, 0.5, -1.0]);
+    }
+
+    #[test]
+    fn truncated_files_never_panic() {
+        let bytes = wav(&[1, 2, 3, 4], None);
+        for length in 0..bytes.len() {
+            let _ = decode(&bytes[..length]);
+        }
+        assert!(matches!(decode(&bytes[..8]), Err(WavError::NotRiff)));
+        assert!(matches!(decode(&bytes[..20]), Err(WavError::Truncated)));
+    }
+
+    #[test]
+    fn truncated_data_keeps_whole_frames() {
+        let bytes = wav(&[1, 2, 3, 4], None);
+        let decoded = decode(&bytes[..bytes.len() - 3]).unwrap();
+        assert_eq!(decoded.values.len(), 2);
+    }
+
+    #[test]
+    fn oversized_data_chunk_is_clamped() {
+        let decoded = decode(&wav(&[1, 2], Some(u32::MAX))).unwrap();
+        assert_eq!(decoded.values.len(), 2);
+    }
+
+    #[test]
+    fn decodes_unsigned_pcm8() {
+        let bytes = file(
+            b"RIFF",
+            &[format(FORMAT_PCM, 1, 8), data(&[128, 192, 0, 255])],
+        );
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(decoded.values, vec![0.0, 0.5, -1.0, 127.0 / 128.0]);
+    }
+
+    #[test]
+    fn decodes_negative_pcm24() {
+        let samples = [0x40_0000, -0x40_0000, -1, -0x80_0000];
+        let bytes: Vec<u8> = samples
+            .iter()
+            .flat_map(|v: &i32| v.to_le_bytes()[..3].to_vec())
+            .collect();
+        let decoded = decode(&file(b"RIFF", &[format(FORMAT_PCM, 1, 24), data(&bytes)])).unwrap();
+        assert_eq!(decoded.values, vec![0.5, -0.5, -1.0 / 8_388_608.0, -1.0]);
+    }
+
+    #[test]
+    fn decodes_float32_and_float64() {
+        let values = [0.25f32, -0.75, 1.5];
+        let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let decoded = decode(&file(b"RIFF", &[format(FORMAT_FLOAT, 1, 32), data(&bytes)])).unwrap();
+        assert_eq!(decoded.values, values);
+
+        let bytes: Vec<u8> = values
+            .iter()
+            .flat_map(|&v| (v as f64).to_le_bytes())
+            .collect();
+        let decoded = decode(&file(b"RIFF", &[format(FORMAT_FLOAT, 1, 64), data(&bytes)])).unwrap();
+        assert_eq!(decoded.values, values);
+    }
+
+    #[test]
+    fn extensible_format_uses_its_subformat() {
+        let mut fmt = format(FORMAT_EXTENSIBLE, 1, 32)[8..].to_vec();
+        fmt.extend(22u16.to_le_bytes()); // cbSize
+        fmt.extend(32u16.to_le_bytes()); // bits valides
+        fmt.extend(4u32.to_le_bytes()); // masque des canaux
+        fmt.extend(FORMAT_FLOAT.to_le_bytes()); // début du GUID
+        fmt.extend([0; 14]);
+        let bytes: Vec<u8> = [0.5f32, -0.25]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+
+        let decoded = decode(&file(
+            b"RIFF",
+            &[chunk(b"fmt ", fmt.len() as u32, &fmt), data(&bytes)],
+        ))
+        .unwrap();
+        assert_eq!(decoded.values, vec![0.5, -0.25]);
+    }
+
+    #[test]
+    fn stereo_is_decoded_planar() {
+        let frames: [i16; 6] = [16_384, -16_384, 8_192, -8_192, 0, -32_768];
+        let bytes: Vec<u8> = frames.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let decoded = decode(&file(b"RIFF", &[format(FORMAT_PCM, 2, 16), data(&bytes)])).unwrap();
+        assert_eq!(decoded.channels, 2);
+        assert_eq!(decoded.values, vec![0.5, 0.25, 0.0, -0.5, -0.25, -1.0]);
+    }
+
+    #[test]
+    fn rf64_data_size_comes_from_ds64() {
+        let mut ds64 = 0u64.to_le_bytes().to_vec(); // taille RIFF
+        ds64.extend(4u64.to_le_bytes()); // taille data
+        ds64.extend(0u64.to_le_bytes()); // nombre d'échantillons
+        ds64.extend(0u32.to_le_bytes()); // entrées de table
+        let inst = [60, 0, 0, 10, 90, 1, 127];
+
+        let bytes = file(
+            b"RF64",
+            &[
+                chunk(b"ds64", ds64.len() as u32, &ds64),
+                format(FORMAT_PCM, 1, 16),
+                chunk(b"data", u32::MAX, &[0x00, 0x40, 0x00, 0xC0]),
+                chunk(b"inst", inst.len() as u32, &inst),
+            ],
+        );
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(decoded.values, vec![0.5, -0.5]);
+        // le chunk suivant n'a pas été avalé par `data`
+        assert_eq!(decoded.key_range, Some((10, 90)));
+    }
+
+    /// Chunk `smpl` avec une boucle, `end` inclus comme dans le fichier
+    fn smpl(root_key: u32, pitch_fraction: u32, start: u32, end: u32) -> Vec<u8> {
+        let mut smpl = vec![0; 12]; // fabricant, produit, période
+        smpl.extend(root_key.to_le_bytes());
+        smpl.extend(pitch_fraction.to_le_bytes());
+        smpl.extend([0; 8]); // SMPTE
+        smpl.extend(1u32.to_le_bytes()); // nombre de boucles
+        smpl.extend(0u32.to_le_bytes());
+        smpl.extend([0; 8]); // cue et type
+        smpl.extend(start.to_le_bytes());
+        smpl.extend(end.to_le_bytes());
+        smpl.extend([0; 8]); // fraction et nombre de lectures
+        chunk(b"smpl", smpl.len() as u32, &smpl)
+    }
+
+    #[test]
+    fn smpl_sets_loop_and_root_key() {
+        let bytes = file(
+            b"RIFF",
+            &[
+                format(FORMAT_PCM, 1, 16),
+                data(&[0; 20]),
+                smpl(67, 1 << 31, 2, 7),
+            ],
+        );
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(decoded.root_key, Some(67));
+        assert_eq!(decoded.fine_tune, 50.0);
+        assert_eq!(decoded.loop_points, Some((2, 8)));
+
+        // boucle au-delà des 10 frames : ignorée
+        let bytes = file(
+            b"RIFF",
+            &[
+                format(FORMAT_PCM, 1, 16),
+                data(&[0; 20]),
+                smpl(67, 0, 2, 10),
+            ],
+        );
+        assert_eq!(decode(&bytes).unwrap().loop_points, None);
+    }
+
+    #[test]
+    fn inst_sets_key_and_velocity_ranges() {
+        let inst = [62, -12i8 as u8, -6i8 as u8, 48, 200, 20, 100];
+        let bytes = file(
+            b"RIFF",
+            &[
+                format(FORMAT_PCM, 1, 16),
+                data(&[0; 4]),
+                chunk(b"inst", inst.len() as u32, &inst),
+            ],
+        );
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(decoded.root_key, Some(62));
+        assert_eq!(decoded.fine_tune, -12.0);
+        assert_eq!(decoded.gain_db, -6.0);
+        assert_eq!(decoded.key_range, Some((48, 127)));
+        assert_eq!(decoded.velocity_range, Some((20, 100)));
+
+        // la hauteur de `smpl` l'emporte
+        let bytes = file(
+            b"RIFF",
+            &[
+                format(FORMAT_PCM, 1, 16),
+                data(&[0; 4]),
+                smpl(70, 0, 0, 1),
+                chunk(b"inst", inst.len() as u32, &inst),
+            ],
+        );
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(decoded.root_key, Some(70));
+        assert_eq!(decoded.key_range, Some((48, 127)));
+    }
+}
@@ -1,4 +1,5 @@
 pub mod dsp;
 pub mod event_handler;
+pub mod loaders;
 pub mod processor;
 pub mod synthetizer;
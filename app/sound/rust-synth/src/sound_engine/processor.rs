@@ -1,16 +1,14 @@
 use std::{cell::RefCell, rc::Rc};
 
+use js_sys::Int32Array;
 use web_sys::console;
 
 use crate::{
     global::MIXER,
     shared_memory::ring_buffer_manager::RingBufferManager,
     sound_engine::{
-        event_handler::{self, EventHandler},
-        synthetizer::{
-            note_manager::{self, NoteManager},
-            sampler::{self, Sampler},
-        },
+        event_handler::EventHandler,
+        synthetizer::{note_manager::NoteManager, sampler::Sampler},
     },
     utils::constants::PROCESSING_BUFFER_SIZE,
 };
@@ -20,8 +18,15 @@ pub struct AudioProcessor {
     pub samplers: Rc<RefCell<Vec<Sampler>>>,
     pub event_handler: EventHandler,
     pub global_sample_index: u64,
-    pub processing_buffer: Vec<f32>, // Alloué une seule fois
-    pub processing_buffer_size: usize,
+    pub processing_buffer: Vec<f32>,   // Alloué une seule fois
+    pub input_buffer: Vec<f32>,        // entrée audio du bloc en cours, entrelacée
+    pub channel_buffer: Vec<[f32; 2]>, // sortie de chaque sampler avant sa tranche, par frame
+}
+
+impl Default for AudioProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AudioProcessor {
@@ -36,10 +41,22 @@ impl AudioProcessor {
             event_handler,
             global_sample_index: 0,
             processing_buffer: vec![0.0; PROCESSING_BUFFER_SIZE * 2],
-            processing_buffer_size: PROCESSING_BUFFER_SIZE,
+            input_buffer: vec![0.0; PROCESSING_BUFFER_SIZE * 2],
+            channel_buffer: Vec::new(),
         }
     }
 
+    /// Récupère l'entrée audio du prochain bloc, du silence si rien n'est branché
+    pub fn capture_audio_input(
+        &mut self,
+        frame_count: i32,
+        input_manager: &RingBufferManager,
+        input_read_idx: &Int32Array,
+    ) {
+        let num_elements_f32 = (frame_count as usize * 2).min(self.input_buffer.len());
+        input_manager.read_samples(input_read_idx, &mut self.input_buffer[..num_elements_f32]);
+    }
+
     pub fn process_and_fill_audio_buffer(
         &mut self,
         frame_count: i32, // Renommé pour la clarté : c'est le nombre de frames stéréo
@@ -61,40 +78,47 @@ impl AudioProcessor {
         self.global_sample_index += frame_count as u64; // C'est le nombre de frames
         let samples_slice = &mut self.processing_buffer[0..num_elements_f32 as usize];
 
+        // les rampes de gain et de pan des samplers avancent pendant le mixage
+        let mut samplers = self.samplers.borrow_mut();
+        // agrandi seulement quand des samplers sont ajoutés
+        let channel_len = frame_count as usize * samplers.len();
+        if self.channel_buffer.len() < channel_len {
+            self.channel_buffer.resize(channel_len, [0.0; 2]);
+        }
+        let channel_slice = &mut self.channel_buffer[..channel_len];
+
         self.note_manager.borrow_mut().generate_raw_samples(
-            samples_slice,
+            channel_slice,
             frame_count as usize, // Passe le nombre de frames à generate_raw_samples
-            &self.samplers.borrow(),
+            &samplers,
         );
 
-        AudioProcessor::apply_final_mixing(samples_slice, &self.samplers);
+        let input_slice = &self.input_buffer[0..num_elements_f32 as usize];
+        AudioProcessor::apply_final_mixing(
+            samples_slice,
+            input_slice,
+            channel_slice,
+            &mut samplers,
+        );
 
         ring_buffer_manager.write_samples(samples_slice);
     }
 
-    pub fn apply_final_mixing(raw_samples: &mut [f32], samplers: &RefCell<Vec<Sampler>>) {
-        // Option 1: Boucle for classique (recommandée pour l'indexation par pas de 2)
-        for i in (0..raw_samples.len()).step_by(2) {
-            let mut mixed_l = raw_samples[i];
-            let mut mixed_r = raw_samples[i + 1];
-
-            // Normalisation par nombre d'samplers
-            if !samplers.borrow().is_empty() {
-                let osc_count = samplers.borrow().len() as f32;
-                mixed_l /= osc_count;
-                mixed_r /= osc_count;
-            }
-
-            MIXER.with(|mix| {
-                mix.lock().unwrap().render(&mut mixed_l, &mut mixed_r);
-            });
-
-            // mixed_l *= 0.1;
-            // mixed_r *= 0.1;
+    /// Passe chaque sampler dans sa tranche, puis les bus et le master, bloc par bloc
+    pub fn apply_final_mixing(
+        raw_samples: &mut [f32],
+        input_samples: &[f32],
+        channel_frames: &[[f32; 2]],
+        samplers: &mut [Sampler],
+    ) {
+        let (output, _) = raw_samples.as_chunks_mut::<2>();
+        let (input, _) = input_samples.as_chunks::<2>();
 
-            // Réécrire dans le Vec
-            raw_samples[i] = mixed_l;
-            raw_samples[i + 1] = mixed_r;
-        }
+        // un seul verrou pour tout le bloc
+        MIXER.with(|mix| {
+            mix.lock()
+                .unwrap()
+                .render_block(output, channel_frames, samplers, input)
+        });
     }
 }
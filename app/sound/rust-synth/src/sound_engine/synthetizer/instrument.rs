@@ -0,0 +1,228 @@
+use crate::{sound_engine::synthetizer::note::Voice, utils::toolkit::ToolKit};
+
+/// Enveloppe d'amplitude en secondes (sustain entre 0 et 1)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Envelope {
+    pub delay: f32,
+    pub attack: f32,
+    pub hold: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub release: f32,
+}
+
+impl Envelope {
+    pub fn to_samples(seconds: f32) -> u64 {
+        ToolKit::convert_ms_to_sample(seconds.max(0.0) * 1000.0) as u64
+    }
+
+    pub fn lengths(&self) -> EnvelopeLengths {
+        EnvelopeLengths {
+            delay: Self::to_samples(self.delay),
+            attack: Self::to_samples(self.attack),
+            hold: Self::to_samples(self.hold),
+            decay: Self::to_samples(self.decay),
+            sustain: self.sustain.clamp(0.0, 1.0),
+            release: Self::to_samples(self.release),
+        }
+    }
+}
+
+/// Enveloppe d'amplitude en samples, telle que lue pendant la note
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnvelopeLengths {
+    pub delay: u64,
+    pub attack: u64,
+    pub hold: u64,
+    pub decay: u64,
+    pub sustain: f32,
+    pub release: u64,
+}
+
+impl EnvelopeLengths {
+    /// Gain `start` samples après le note on et `end` samples après le note off,
+    /// `None` une fois le release terminé
+    pub fn gain(&self, start: u64, end: u64, released: bool) -> Option<f32> {
+        let mut value = 1.0;
+        if released {
+            if end >= self.release + self.delay {
+                return None;
+            }
+            value *= (self.release as f32 - end as f32) / self.release as f32;
+        }
+
+        if start <= self.delay {
+            value = 0.0
+        } else if start <= self.attack + self.delay {
+            value *= (start as f32 - self.delay as f32) / self.attack as f32;
+        } else if start <= self.attack + self.hold + self.delay {
+            // plateau du hold
+        } else if start <= self.attack + self.hold + self.decay + self.delay {
+            value *= 1.0
+                + ((start as f32 - self.attack as f32 - self.hold as f32 - self.delay as f32)
+                    * (self.sustain - 1.0)
+                    / self.decay as f32);
+        } else {
+            value *= self.sustain;
+        }
+        Some(value)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InstrumentRegion {
+    pub sample_id: u32, // porte les plages clavier/vélocité, la hauteur et la boucle
+    pub key_range: (u8, u8),
+    pub velocity_range: (u8, u8),
+    pub seq_length: u32,
+    pub seq_position: u32, // à partir de 1
+    pub seq_counter: u32,
+    pub envelope: Option<Envelope>, // None : celle du sampler
+}
+
+impl InstrumentRegion {
+    pub fn matches(&self, note: u8, velocity: u8) -> bool {
+        (self.key_range.0..=self.key_range.1).contains(&note)
+            && (self.velocity_range.0..=self.velocity_range.1).contains(&velocity)
+    }
+}
+
+/// Ensemble de régions jouées ensemble par un sampler (import SFZ, preset SF2)
+pub struct Instrument {
+    pub id: u32,
+    pub regions: Vec<InstrumentRegion>,
+    pub envelope: Option<Envelope>,
+}
+
+impl Instrument {
+    /// L'enveloppe de la première région devient celle de l'instrument, reprise par le sampler ;
+    /// les régions qui la partagent suivent ensuite les réglages du sampler, les autres
+    /// gardent la leur
+    pub fn new(id: u32, mut regions: Vec<InstrumentRegion>) -> Self {
+        let envelope = regions.iter().find_map(|r| r.envelope);
+        for region in &mut regions {
+            if region.envelope == envelope {
+                region.envelope = None;
+            }
+        }
+        Self {
+            id,
+            regions,
+            envelope,
+        }
+    }
+
+    /// Ajoute à `voices` les régions déclenchées par la note, en avançant les compteurs de
+    /// round robin
+    pub fn select_regions(&mut self, note: u8, velocity: u8, voices: &mut Vec<Voice>) {
+        for region in self
+            .regions
+            .iter_mut()
+            .filter(|r| r.matches(note, velocity))
+        {
+            let seq_length = region.seq_length.max(1);
+            if region.seq_counter % seq_length + 1 == region.seq_position {
+                voices.push(Voice::new(
+                    region.sample_id,
+                    region.envelope.map(|e| e.lengths()),
+                ));
+            }
+            region.seq_counter = region.seq_counter.wrapping_add(1);
+        }
+    }
+}
+
+/// Preset d'une banque SF2, sélectionnable par bank/program
+#[derive(Debug, Clone)]
+pub struct Preset {
+    pub name: String,
+    pub bank: u16,
+    pub program: u8,
+    pub instrument_id: u32,
+}
+
+/// Banque SF2 importée : chaque preset est un `Instrument` du `SampleManager`
+pub struct SoundBank {
+    pub id: u32,
+    pub presets: Vec<Preset>, // triés par bank puis program
+}
+
+impl SoundBank {
+    pub fn find(&self, bank: u16, program: u8) -> Option<&Preset> {
+        self.presets
+            .iter()
+            .find(|p| p.bank == bank && p.program == program)
+    }
+
+    pub fn contains_instrument(&self, instrument_id: u32) -> bool {
+        self.presets
+            .iter()
+            .any(|p| p.instrument_id == instrument_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn envelope(release: f32) -> Envelope {
+        Envelope {
+            delay: 0.0,
+            attack: 0.01,
+            hold: 0.0,
+            decay: 0.1,
+            sustain: 0.5,
+            release,
+        }
+    }
+
+    fn region(sample_id: u32, key_range: (u8, u8), envelope: Option<Envelope>) -> InstrumentRegion {
+        InstrumentRegion {
+            sample_id,
+            key_range,
+            velocity_range: (0, 127),
+            seq_length: 1,
+            seq_position: 1,
+            seq_counter: 0,
+            envelope,
+        }
+    }
+
+    #[test]
+    fn regions_keep_only_the_envelopes_that_differ() {
+        let mut instrument = Instrument::new(
+            1,
+            vec![
+                region(10, (0, 63), Some(envelope(0.2))),
+                region(11, (64, 127), Some(envelope(1.0))),
+                region(12, (60, 70), Some(envelope(0.2))),
+            ],
+        );
+        assert_eq!(instrument.envelope, Some(envelope(0.2)));
+
+        let mut voices = Vec::with_capacity(4);
+        instrument.select_regions(65, 100, &mut voices);
+        let ids: Vec<_> = voices.iter().map(|v| v.sample_id).collect();
+        assert_eq!(ids, vec![11, 12]);
+        assert_eq!(voices[0].envelope, Some(envelope(1.0).lengths()));
+        assert_eq!(voices[1].envelope, None);
+    }
+
+    #[test]
+    fn round_robin_alternates_regions() {
+        let mut first = region(1, (0, 127), None);
+        first.seq_length = 2;
+        let mut second = region(2, (0, 127), None);
+        second.seq_length = 2;
+        second.seq_position = 2;
+        let mut instrument = Instrument::new(1, vec![first, second]);
+
+        let mut played = Vec::new();
+        for _ in 0..4 {
+            let mut voices = Vec::new();
+            instrument.select_regions(60, 100, &mut voices);
+            played.extend(voices.iter().map(|v| v.sample_id));
+        }
+        assert_eq!(played, vec![1, 2, 1, 2]);
+    }
+}
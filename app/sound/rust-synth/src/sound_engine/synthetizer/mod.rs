@@ -1,3 +1,4 @@
+pub mod instrument;
 pub mod note;
 pub mod note_manager;
 pub mod sample_manager;
@@ -1,8 +1,33 @@
-use crate::sound_engine::synthetizer::sampler::Sampler;
+use crate::sound_engine::synthetizer::{instrument::EnvelopeLengths, sampler::Sampler};
+
+// voix réservées par note : des régions superposées ne réallouent pas au note on
+const NOTE_VOICES: usize = 8;
+
+/// Un sample joué par la note (plusieurs quand les régions d'un instrument se superposent)
+#[derive(Debug, Clone)]
+pub struct Voice {
+    pub sample_id: u32,
+    pub position: f64, // tête de lecture dans le sample, en frames
+    pub envelope: Option<EnvelopeLengths>, // None : celle du sampler
+    pub finished: bool,
+}
+
+impl Voice {
+    pub fn new(sample_id: u32, envelope: Option<EnvelopeLengths>) -> Self {
+        Self {
+            sample_id,
+            position: 0.0,
+            envelope,
+            finished: false,
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct NoteOscState {
     pub current_phase: f32,
+    pub voices: Vec<Voice>,
+    pub one_shot: bool,
     pub start_sample_index: u64,
     pub end_sample_index: u64,
     pub finished: bool,
@@ -12,6 +37,8 @@ impl NoteOscState {
     pub fn new(phase_shift: f32) -> Self {
         Self {
             current_phase: phase_shift % 1.0,
+            voices: Vec::with_capacity(NOTE_VOICES),
+            one_shot: false,
             start_sample_index: 0,
             end_sample_index: 0,
             finished: false,
@@ -20,6 +47,8 @@ impl NoteOscState {
 
     pub fn reset(&mut self, phase_shift: f32) {
         self.current_phase = phase_shift % 1.0;
+        self.voices.clear();
+        self.one_shot = false;
         self.start_sample_index = 0;
         self.end_sample_index = 0;
         self.finished = false;
@@ -34,7 +63,6 @@ pub struct Note {
     pub to_remove: bool,
     pub start_sample_index: u64,
     pub end_sample_index: u64,
-    pub current_phase: f32,
     pub osc_states: Vec<NoteOscState>,
 }
 
@@ -52,7 +80,6 @@ impl Note {
             to_remove: false,
             start_sample_index: 0,
             end_sample_index: 0,
-            current_phase: 0.0,
             osc_states,
         }
     }
@@ -83,23 +110,21 @@ impl Note {
         self.osc_states.iter().all(|s| s.finished)
     }
 
-    pub fn generate_samples_of_all_samplers(&mut self, samplers: &[Sampler]) -> (f32, f32) {
+    /// Ajoute la sortie avant fader de chaque sampler à sa tranche, `channels` contient une
+    /// entrée par sampler
+    pub fn generate_samples_of_all_samplers(
+        &mut self,
+        samplers: &[Sampler],
+        channels: &mut [[f32; 2]],
+    ) {
         if self.to_remove {
-            return (0.0, 0.0);
+            return;
         }
 
-        let mut note_sum_l = 0.0;
-        let mut note_sum_r = 0.0;
-
-        for (osc_index, sampler) in samplers.iter().enumerate() {
-            if let Some(state) = self.osc_states.get_mut(osc_index) {
-                let (l, r) =
-                    sampler.generate_sample(self.value, self.velocity, state, self.has_ended);
-                note_sum_l += l;
-                note_sum_r += r;
-            }
+        for ((state, sampler), channel) in self.osc_states.iter_mut().zip(samplers).zip(channels) {
+            let (l, r) = sampler.generate_sample(self.value, self.velocity, state, self.has_ended);
+            channel[0] += l;
+            channel[1] += r;
         }
-
-        (note_sum_l, note_sum_r)
     }
 }
@@ -1,7 +1,6 @@
 use web_sys::console;
 
 use crate::{
-    global::MIXER,
     sound_engine::synthetizer::{note::Note, sampler::Sampler},
     utils::types::NoteDTO,
 };
@@ -10,6 +9,12 @@ pub struct NoteManager {
     notes: Vec<Note>,
 }
 
+impl Default for NoteManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl NoteManager {
     pub fn new() -> Self {
         Self { notes: Vec::new() }
@@ -44,18 +49,21 @@ impl NoteManager {
         });
     }
 
+    /// Remplit `channel_buffer` avec, pour chaque frame, la sortie avant fader de chaque
+    /// sampler (`samplers.len()` entrées consécutives par frame)
     pub fn generate_raw_samples(
         &mut self,
-        output_buffer: &mut [f32],
+        channel_buffer: &mut [[f32; 2]],
         frame_count: usize, // C'est le nombre de frames stéréo
         samplers: &[Sampler],
     ) {
-        output_buffer.fill(0.0);
+        channel_buffer.fill([0.0; 2]);
 
-        if output_buffer.len() < frame_count * 2 {
+        let channel_count = samplers.len();
+        if channel_buffer.len() < frame_count * channel_count {
             console::error_1(
                 &format!(
-                    "Output buffer in generate_raw_samples is too small for {} frames!",
+                    "Channel buffer in generate_raw_samples is too small for {} frames!",
                     frame_count
                 )
                 .into(),
@@ -64,22 +72,10 @@ impl NoteManager {
         }
 
         for i in 0..frame_count {
-            let mut mixed_l = 0.0;
-            let mut mixed_r = 0.0;
-
-            if self.notes.is_empty() {
-            } else {
-                for note in self.notes.iter_mut() {
-                    let (l, r) = note.generate_samples_of_all_samplers(samplers);
-                    mixed_l += l;
-                    mixed_r += r;
-                }
+            let channels = &mut channel_buffer[i * channel_count..(i + 1) * channel_count];
+            for note in self.notes.iter_mut() {
+                note.generate_samples_of_all_samplers(samplers, channels);
             }
-
-            output_buffer[i * 2] = mixed_l;
-            output_buffer[i * 2 + 1] = mixed_r;
-
-            continue;
         }
 
         self.cleanup_finished_notes();
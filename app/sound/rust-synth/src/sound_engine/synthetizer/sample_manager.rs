@@ -1,61 +1,238 @@
-use js_sys::Float32Array;
 use web_sys::console;
 
-use crate::utils::{toolkit::ToolKit, types::Sample};
+use crate::{
+    sound_engine::{
+        dsp::resampler,
+        synthetizer::{
+            instrument::{Envelope, Instrument, SoundBank},
+            note::Voice,
+        },
+    },
+    utils::{
+        constants::{INTERNAL_SAMPLE_ID_BASE, SAMPLE_RATE},
+        toolkit::ToolKit,
+        types::{Interpolation, LoopMode, Sample},
+    },
+};
+
+/// Preset cherché par un changement de programme
+#[derive(Debug, PartialEq)]
+pub enum PresetLookup {
+    Found(u32, Option<Envelope>), // instrument du preset et son enveloppe
+    Missing,                      // absent de la banque
+    NotABank,                     // instrument hors de toute banque SF2 (SFZ...)
+}
 
 pub struct SampleManager {
     pub samples: Vec<Sample>,
+    pub instruments: Vec<Instrument>,
+    pub sound_banks: Vec<SoundBank>,
+}
+
+impl Default for SampleManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SampleManager {
     pub fn new() -> Self {
         Self {
             samples: Vec::new(),
+            instruments: Vec::new(),
+            sound_banks: Vec::new(),
         }
     }
 
-    pub fn add_sample(&mut self, id: u32, raw_values: Float32Array, length: u32, hq: u8) {
-        // Récupérer uniquement la portion utile du Float32Array
-        console::log_1(&"Création d'un sample".into());
-        let useful_slice = raw_values.subarray(0, length);
+    /// Premier id libre pour un sample ou un instrument créé par le moteur
+    pub fn next_sample_id(&self) -> u32 {
+        self.samples
+            .iter()
+            .map(|s| s.id + 1)
+            .chain(self.instruments.iter().map(|i| i.id + 1))
+            .filter(|&id| id > INTERNAL_SAMPLE_ID_BASE)
+            .max()
+            .unwrap_or(INTERNAL_SAMPLE_ID_BASE)
+    }
 
-        // Convertir directement en Vec<f32>
-        let values: Vec<f32> = useful_slice.to_vec();
+    pub fn add_instrument(&mut self, instrument: Instrument) {
+        self.instruments.retain(|i| i.id != instrument.id);
+        self.instruments.push(instrument);
+    }
+
+    pub fn get_instrument(&self, id: u32) -> Option<&Instrument> {
+        self.instruments.iter().find(|i| i.id == id)
+    }
 
-        // Boxer pour stockage
-        let boxed_values = values.into_boxed_slice();
+    pub fn add_sound_bank(&mut self, bank: SoundBank) {
+        self.sound_banks.retain(|b| b.id != bank.id);
+        self.sound_banks.push(bank);
+    }
+
+    pub fn get_sound_bank(&self, id: u32) -> Option<&SoundBank> {
+        self.sound_banks.iter().find(|b| b.id == id)
+    }
 
-        let sample = Sample {
-            id,
-            values: boxed_values,
-            hq: hq,
+    /// Preset `bank`/`program` de la banque qui contient l'instrument courant d'un sampler
+    pub fn find_preset(&self, instrument_id: u32, bank: u16, program: u8) -> PresetLookup {
+        let Some(sound_bank) = self
+            .sound_banks
+            .iter()
+            .find(|b| b.contains_instrument(instrument_id))
+        else {
+            return PresetLookup::NotABank;
         };
+        sound_bank
+            .find(bank, program)
+            .and_then(|preset| self.get_instrument(preset.instrument_id))
+            .map_or(PresetLookup::Missing, |instrument| {
+                PresetLookup::Found(instrument.id, instrument.envelope)
+            })
+    }
+
+    pub fn select_regions(
+        &mut self,
+        instrument_id: u32,
+        note: u8,
+        velocity: u8,
+        voices: &mut Vec<Voice>,
+    ) {
+        if let Some(instrument) = self.instruments.iter_mut().find(|i| i.id == instrument_id) {
+            instrument.select_regions(note, velocity, voices);
+        }
+    }
+
+    pub fn loop_mode(&self, sample_id: u32) -> Option<LoopMode> {
+        self.get(sample_id).map(|s| s.loop_mode)
+    }
+
+    pub fn set_interpolation(&mut self, sample_id: u32, interpolation: Option<Interpolation>) {
+        if let Some(sample) = self.samples.iter_mut().find(|s| s.id == sample_id) {
+            sample.interpolation = interpolation;
+        }
+    }
+
+    pub fn add_sample(&mut self, sample: Sample) {
+        console::log_1(&"Création d'un sample".into());
         self.samples.push(sample);
     }
 
-    pub fn get_value(&self, sample_id: u32, index: u64, frequency: f32) -> f32 {
-        if let Some(sample) = self.samples.iter().find(|s| s.id == sample_id) {
-            let table = &sample.values;
-            if table.is_empty() {
-                return 0.0;
-            }
-            let table_len = table.len() as f32;
-            let base_frequency: f32 = if sample.hq == 0 {
-                ToolKit::midi_to_freq(60) // C4
-            } else {
-                ToolKit::midi_to_freq(12) // C0
+    pub fn has_sample(&self, id: u32) -> bool {
+        self.samples.iter().any(|s| s.id == id)
+    }
+
+    pub fn get(&self, id: u32) -> Option<&Sample> {
+        self.samples.iter().find(|s| s.id == id)
+    }
+
+    /// Le sample doit-il jouer pour cette note ? (plages `inst`)
+    pub fn accepts(&self, sample_id: u32, note: u8, velocity: u8) -> bool {
+        self.get(sample_id).is_none_or(|s| {
+            (s.key_range.0..=s.key_range.1).contains(&note)
+                && (s.velocity_range.0..=s.velocity_range.1).contains(&velocity)
+        })
+    }
+
+    /// Lit la frame stéréo à `position` (en frames du sample) puis avance la tête de
+    /// lecture selon `frequency`. L'interpolation du sample, si définie, remplace celle
+    /// du sampler. Renvoie `None` une fois la fin d'un sample non bouclé atteinte.
+    pub fn read(
+        &self,
+        sample_id: u32,
+        position: &mut f64,
+        frequency: f32,
+        releasing: bool,
+        interpolation: Interpolation,
+    ) -> Option<(f32, f32)> {
+        let Some(sample) = self.get(sample_id) else {
+            return Some((0.0, 0.0));
+        };
+        let frames = sample.frames();
+        if frames == 0 {
+            return Some((0.0, 0.0));
+        }
+
+        let looping = sample.loop_start < sample.loop_end
+            && sample.loop_end <= frames
+            && match sample.loop_mode {
+                LoopMode::Continuous => true,
+                LoopMode::Sustain => !releasing,
+                LoopMode::NoLoop | LoopMode::OneShot => false,
             };
-            let step = frequency / base_frequency; // combien de cycles par index ?
-            let pos_in_table = (index as f32 * step) % table_len;
-            let i0 = pos_in_table.floor() as usize;
-            let i1 = (i0 + 1) % table.len();
-
-            let frac = pos_in_table - i0 as f32;
-            let v0 = table[i0];
-            let v1 = table[i1];
-            (v0 * (1.0 - frac)) + (v1 * frac)
+        if !looping && *position >= frames as f64 {
+            return None;
+        }
+
+        // combien de frames du sample par frame de sortie ?
+        let base_frequency =
+            ToolKit::midi_to_freq(sample.root_key) * 2f32.powf(sample.fine_tune / 1200.0);
+        let step = frequency / base_frequency * sample.sample_rate / SAMPLE_RATE;
+
+        let left = &sample.values[..frames];
+        let right = if sample.channels == 2 {
+            &sample.values[frames..frames * 2]
         } else {
-            0.0
+            left
+        };
+        let (loop_start, loop_end) = (sample.loop_start as isize, sample.loop_end as isize);
+        let fetch = |mut index: isize| {
+            if looping && index >= loop_end {
+                index = loop_start + (index - loop_start) % (loop_end - loop_start);
+            }
+            let i = index.clamp(0, frames as isize - 1) as usize;
+            (left[i], right[i])
+        };
+
+        let index = position.floor();
+        let frac = (*position - index) as f32;
+        let (l, r) = resampler::interpolate(
+            sample.interpolation.unwrap_or(interpolation),
+            index as isize,
+            frac,
+            step,
+            fetch,
+        );
+
+        *position += step as f64;
+
+        if looping && *position >= sample.loop_end as f64 {
+            let loop_start = sample.loop_start as f64;
+            let loop_length = (sample.loop_end - sample.loop_start) as f64;
+            *position = loop_start + (*position - loop_start) % loop_length;
+        }
+
+        let gain_l = sample.gain * (1.0 - sample.pan).min(1.0);
+        let gain_r = sample.gain * (1.0 + sample.pan).min(1.0);
+        Some((l * gain_l, r * gain_r))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sound_engine::synthetizer::instrument::Preset;
+
+    #[test]
+    fn find_preset_tells_a_missing_preset_from_an_instrument_outside_banks() {
+        let mut manager = SampleManager::new();
+        for id in [1, 2, 3] {
+            manager.add_instrument(Instrument::new(id, Vec::new()));
         }
+        let preset = |program, instrument_id| Preset {
+            name: String::new(),
+            bank: 0,
+            program,
+            instrument_id,
+        };
+        // l'instrument 3 vient d'un SFZ
+        manager.add_sound_bank(SoundBank {
+            id: 10,
+            presets: vec![preset(0, 1), preset(5, 2)],
+        });
+
+        assert_eq!(manager.find_preset(1, 0, 5), PresetLookup::Found(2, None));
+        assert_eq!(manager.find_preset(1, 0, 6), PresetLookup::Missing);
+        assert_eq!(manager.find_preset(1, 1, 0), PresetLookup::Missing);
+        assert_eq!(manager.find_preset(3, 0, 5), PresetLookup::NotABank);
     }
 }
@@ -2,8 +2,18 @@ use wasm_bindgen::prelude::*;
 
 use crate::{
     global::SAMPLE_MANAGER,
-    sound_engine::synthetizer::note::NoteOscState,
-    utils::{constants::SAMPLE_RATE, toolkit::ToolKit},
+    sound_engine::{
+        dsp::smoothing::SmoothedParam,
+        synthetizer::{
+            instrument::{Envelope, EnvelopeLengths},
+            note::{NoteOscState, Voice},
+        },
+    },
+    utils::{
+        constants::{MAX_FX_BUSES, SAMPLE_RATE},
+        toolkit::ToolKit,
+        types::{Interpolation, LoopMode, SamplerSend},
+    },
 };
 
 #[wasm_bindgen]
@@ -11,47 +21,61 @@ use crate::{
 pub struct Sampler {
     pub id: u8,
     pub sample_id: u32,
+    pub instrument_id: Option<u32>, // prioritaire sur sample_id quand défini
     pub attack_length: u64,
+    pub hold_length: u64,
     pub decay_length: u64,
     pub sustain_gain: f32,
     pub release_length: u64,
     pub frequency_shift: f32,
     pub phase_shift: f32,
     pub delay_length: u64,
-    pub gain: f32,
-    pub gain_l: f32,
-    pub gain_r: f32,
+    #[wasm_bindgen(skip)]
+    pub gain: SmoothedParam,
+    #[wasm_bindgen(skip)]
+    pub gain_l: SmoothedParam,
+    #[wasm_bindgen(skip)]
+    pub gain_r: SmoothedParam,
+    pub interpolation: Interpolation,
+    #[wasm_bindgen(skip)]
+    pub sends: [SamplerSend; MAX_FX_BUSES],
 }
 
 impl Sampler {
-    pub fn apply_adsr(&self, state: &mut NoteOscState, note_has_ended: bool, value: &mut f32) {
-        if note_has_ended {
-            if state.end_sample_index >= self.release_length + self.delay_length {
-                state.finished = true;
-                *value = 0.0;
-                return;
-            }
+    /// Gain et pan du sampler : (gauche, droite), puis avance leurs rampes d'un sample
+    pub fn next_fader(&mut self) -> (f32, f32) {
+        let gain = self.gain.next_value();
+        (
+            gain * self.gain_l.next_value(),
+            gain * self.gain_r.next_value(),
+        )
+    }
 
-            *value *= (self.release_length as f32 - state.end_sample_index as f32)
-                / self.release_length as f32;
+    /// Pan de -1 (gauche) à 1 (droite)
+    pub fn set_pan(&mut self, pan: f32) {
+        self.gain_l.set((1.0 - pan) / 2.0);
+        self.gain_r.set((1.0 + pan) / 2.0);
+    }
+
+    /// Durée des rampes du gain, du pan et des niveaux d'envoi
+    pub fn set_ramp(&mut self, ms: f32) {
+        for param in [&mut self.gain, &mut self.gain_l, &mut self.gain_r] {
+            param.set_ramp(ms);
+        }
+        for send in &mut self.sends {
+            send.level.set_ramp(ms);
         }
+    }
 
-        if state.start_sample_index <= self.delay_length {
-            *value = 0.0
-        } else if state.start_sample_index <= self.attack_length + self.delay_length {
-            *value *= (state.start_sample_index as f32 - self.delay_length as f32)
-                / self.attack_length as f32;
-        } else if state.start_sample_index
-            <= self.attack_length + self.decay_length + self.delay_length
-        {
-            *value *= 1.0
-                + ((state.start_sample_index as f32
-                    - self.attack_length as f32
-                    - self.delay_length as f32)
-                    * (self.sustain_gain - 1.0)
-                    / self.decay_length as f32);
-        } else {
-            *value *= self.sustain_gain;
+    /// Enveloppe réglée sur le sampler, utilisée par les voix qui n'ont pas la leur
+    pub fn envelope(&self) -> EnvelopeLengths {
+        EnvelopeLengths {
+            delay: self.delay_length,
+            attack: self.attack_length,
+            hold: self.hold_length,
+            decay: self.decay_length,
+            sustain: self.sustain_gain,
+            release: self.release_length,
         }
     }
 
@@ -67,33 +91,102 @@ impl Sampler {
         }
 
         let freq: f32 = ToolKit::midi_to_freq(note_value) * self.frequency_shift;
+        let sampler_envelope = self.envelope();
+
+        let frame = SAMPLE_MANAGER.with(|sm| {
+            let mut sm = sm.lock().unwrap();
+
+            // choix des samples au déclenchement de la note, dans le buffer de la note
+            if state.start_sample_index == 0 {
+                state.voices.clear();
+                match self.instrument_id {
+                    Some(instrument_id) => sm.select_regions(
+                        instrument_id,
+                        note_value,
+                        note_velocity,
+                        &mut state.voices,
+                    ),
+                    None if sm.accepts(self.sample_id, note_value, note_velocity) => {
+                        state.voices.push(Voice::new(self.sample_id, None));
+                    }
+                    None => {}
+                }
+                state.one_shot = !state.voices.is_empty()
+                    && state
+                        .voices
+                        .iter()
+                        .all(|v| sm.loop_mode(v.sample_id) == Some(LoopMode::OneShot));
+            }
 
-        let mut value = SAMPLE_MANAGER.with(|sm| {
-            sm.lock()
-                .unwrap()
-                .get_value(self.sample_id, state.start_sample_index, freq)
-        }) * note_velocity as f32
-            * self.gain
-            / 127.0;
+            // un one shot ignore le note off et joue jusqu'au bout du sample
+            let releasing = note_has_ended && !state.one_shot;
+            let mut frame = None;
+            for voice in state.voices.iter_mut().filter(|v| !v.finished) {
+                let Some(gain) = voice.envelope.unwrap_or(sampler_envelope).gain(
+                    state.start_sample_index,
+                    state.end_sample_index,
+                    releasing,
+                ) else {
+                    voice.finished = true;
+                    continue;
+                };
+                match sm.read(
+                    voice.sample_id,
+                    &mut voice.position,
+                    freq,
+                    releasing,
+                    self.interpolation,
+                ) {
+                    Some((l, r)) => {
+                        let (sum_l, sum_r) = frame.unwrap_or((0.0, 0.0));
+                        frame = Some((sum_l + l * gain, sum_r + r * gain));
+                    }
+                    None => voice.finished = true,
+                }
+            }
+            frame
+        });
+
+        let Some((sample_l, sample_r)) = frame else {
+            state.finished = true;
+            return (0.0, 0.0);
+        };
 
-        self.apply_adsr(state, note_has_ended, &mut value);
+        let value = note_velocity as f32 / 127.0;
 
         // Mise à jour de l'état
         state.current_phase += freq / SAMPLE_RATE;
         state.current_phase %= 1.0;
         state.start_sample_index += 1;
 
-        if note_has_ended {
+        if note_has_ended && !state.one_shot {
             state.end_sample_index += 1;
         }
 
-        let left = value * self.gain_l;
-        let right = value * self.gain_r;
-
-        (left, right)
+        // avant fader : gain et pan sont appliqués par `Mixer::mix_channels` (`next_fader`),
+        // après les envois pré-fader
+        (sample_l * value, sample_r * value)
     }
 
     pub fn change_sample(&mut self, sample_id: u32) {
-        self.sample_id = sample_id
+        self.sample_id = sample_id;
+        self.instrument_id = None;
+    }
+
+    pub fn change_instrument(&mut self, instrument_id: u32, envelope: Option<Envelope>) {
+        self.instrument_id = Some(instrument_id);
+        if let Some(envelope) = envelope {
+            self.apply_envelope(&envelope);
+        }
+    }
+
+    pub fn apply_envelope(&mut self, envelope: &Envelope) {
+        let lengths = envelope.lengths();
+        self.delay_length = lengths.delay;
+        self.attack_length = lengths.attack;
+        self.hold_length = lengths.hold;
+        self.decay_length = lengths.decay;
+        self.sustain_gain = lengths.sustain;
+        self.release_length = lengths.release;
     }
 }
@@ -2,21 +2,57 @@ pub const FLAG_INDEX: u32 = 0;
 pub const READ_INDEX: u32 = 1;
 pub const WRITE_INDEX: u32 = 2;
 pub const HEADERS_SIZE_BYTES: u32 = 3 * 4;
+// après le ring buffer de sortie : [write, read] puis le ring buffer de l'entrée audio
+pub const INPUT_WRITE_INDEX: u32 = 0;
+pub const INPUT_READ_INDEX: u32 = 1;
+pub const INPUT_HEADERS_SIZE_BYTES: u32 = 2 * 4;
 
 pub const MIDI_EVENT_SIZE: u32 = 4;
 pub const MIDI_QUEUE_CAPACITY: u32 = 64;
 pub const MIDI_WRITE_INDEX: u32 = 0;
 pub const MIDI_READ_INDEX: u32 = 1;
+pub const BANK_SELECT_CC: u8 = 0;
 
 pub const FX_QUEUE_CAPACITY: u32 = 64;
 pub const FX_EVENT_SIZE_INT: u32 = 3;
 pub const FX_EVENT_SIZE_FLOAT: u32 = 1;
 pub const FX_WRITE_INDEX: u32 = 0;
 pub const FX_READ_INDEX: u32 = 1;
+// vumètres après la file : [master, (fx_id, réduction)...], en millièmes de dB
+pub const FX_METER_CAPACITY: u32 = 16;
+pub const FX_METER_SIZE: u32 = 2;
+// réponse en amplitude demandée par le JS : [séquence, fx_id] puis les points (dB)
+pub const FX_RESPONSE_POINTS: u32 = 128;
+pub const FX_RESPONSE_MIN_FREQUENCY: f32 = 20.0;
+pub const FX_RESPONSE_MAX_FREQUENCY: f32 = 20000.0;
 
 pub const SAMPLE_RATE: f32 = 44100.0;
 pub const FREQ_A4: f32 = 440.0;
 
 pub const OSC_QUEUE_CAPACITY: u32 = 100;
 
+// Upload des samples : file d'événements + slots de transfert + table de statuts
+pub const SAMPLE_UPLOAD_WRITE_INDEX: u32 = 0;
+pub const SAMPLE_UPLOAD_READ_INDEX: u32 = 1;
+pub const SAMPLE_UPLOAD_QUEUE_CAPACITY: u32 = 32;
+pub const SAMPLE_UPLOAD_EVENT_SIZE: u32 = 11;
+pub const SAMPLE_UPLOAD_STATUS_CAPACITY: u32 = 32;
+pub const SAMPLE_UPLOAD_STATUS_SIZE: u32 = 3;
+pub const SAMPLE_TRANSFER_SLOTS: u32 = 4;
+pub const SAMPLE_TRANSFER_SLOT_SIZE: u32 = 262_144; // en f32
+
+// ids des samples créés par le moteur (régions SFZ...), hors de la plage du JS
+pub const INTERNAL_SAMPLE_ID_BASE: u32 = 1 << 20;
+
 pub const PROCESSING_BUFFER_SIZE: usize = 1024;
+pub const MAX_FX_BUSES: usize = 4;
+// id du limiteur de sortie pour les réglages d'effet, hors de la plage des ids du JS
+pub const MASTER_LIMITER_ID: u32 = 1 << 20;
+// chaînes d'effets : 0 master, 1 à MAX_FX_BUSES bus, puis une par sampler (id ajouté à la base)
+pub const CHANNEL_CHAIN_BASE: usize = MAX_FX_BUSES + 1;
+// réglages OSC des envois : SAMPLER_SEND_PARAM + bus * SAMPLER_SEND_PARAMS + (0 niveau, 1 pré-fader)
+pub const SAMPLER_SEND_PARAM: u8 = 13;
+pub const SAMPLER_SEND_PARAMS: u8 = 2;
+// durée des rampes du gain, du pan et des envois d'un sampler (ms), après les envois
+pub const SAMPLER_SMOOTHING_PARAM: u8 =
+    SAMPLER_SEND_PARAM + MAX_FX_BUSES as u8 * SAMPLER_SEND_PARAMS;
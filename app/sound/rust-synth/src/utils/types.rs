@@ -1,10 +1,16 @@
+use std::sync::Arc;
+
 use wasm_bindgen::prelude::*;
 
+use crate::{sound_engine::dsp::smoothing::SmoothedParam, utils::constants::SAMPLE_RATE};
+
 #[wasm_bindgen]
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum EventType {
     NoteOff = 0,
     NoteOn = 1,
+    ControlChange = 2,
+    ProgramChange = 3,
 }
 
 impl TryFrom<u8> for EventType {
@@ -14,6 +20,8 @@ impl TryFrom<u8> for EventType {
         match value {
             0 => Ok(EventType::NoteOff),
             1 => Ok(EventType::NoteOn),
+            2 => Ok(EventType::ControlChange),
+            3 => Ok(EventType::ProgramChange),
             _ => Err("Valeur d'événement MIDI inconnue"),
         }
     }
@@ -26,30 +34,175 @@ pub struct NoteDTO {
     pub velocity: u8,
 }
 
+/// Événement lu dans la file MIDI : `[type, data1, data2, 0]`
+#[derive(Debug, Clone, Copy)]
+pub enum MidiEvent {
+    Note(NoteDTO), // vélocité nulle : note off
+    ControlChange { controller: u8, value: u8 },
+    ProgramChange(u8),
+}
+
+/// Envoi d'un sampler vers un bus auxiliaire
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SamplerSend {
+    pub level: SmoothedParam, // 0 à 1, 0 : pas d'envoi
+    pub pre_fader: bool,      // avant le gain et le pan du sampler
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Mix {
     pub dry: f32,
     pub wet: f32,
 }
 
-pub struct FxEventDto {
-    pub id: u32,
-    pub event_type: u32,
-    pub params: Vec<f32>,
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UploadFormat {
+    Pcm = 0, // f32 planaires
+    Wav = 1, // fichier .wav brut, décodé par le moteur
+    Sfz = 2, // paquet : le .sfz et les fichiers qu'il référence
+    Sf2 = 3, // banque SoundFont 2 brute
+}
+
+impl TryFrom<u8> for UploadFormat {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(UploadFormat::Pcm),
+            1 => Ok(UploadFormat::Wav),
+            2 => Ok(UploadFormat::Sfz),
+            3 => Ok(UploadFormat::Sf2),
+            _ => Err(()),
+        }
+    }
 }
 
-#[derive(Default)]
-pub struct SampleEvent {
-    pub sample_event_index: u32,
+pub struct SampleUploadChunk {
+    pub upload_id: u32,
     pub sampler_id: u32,
     pub sample_id: u32,
-    pub length: u32,
+    pub total_length: u32,
+    pub chunk_offset: u32,
+    pub chunk_length: u32,
+    pub slot: i32, // -1 : pas de données, on assigne un sample existant
     pub channels: u8,
     pub hq: u8,
+    pub format: u8,
+    pub interpolation: u8, // 0 : celle du sampler, sinon `Interpolation` + 1
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UploadStatus {
+    InProgress = 1,
+    Done = 2,
+    DoneWithWarnings = 3,
+    UnknownSample = -1,
+    OutOfOrder = -2,
+    InvalidSlot = -3,
+    Overflow = -4,
+    InvalidLength = -5,
+    InvalidFormat = -6,
+    DecodeError = -7,
+}
+
+pub struct PendingUpload {
+    pub upload_id: u32,
+    pub sampler_id: u32,
+    pub sample_id: u32,
+    pub channels: u8,
+    pub hq: u8,
+    pub format: UploadFormat,
+    pub interpolation: Option<Interpolation>,
+    pub total_length: u32,
+    pub values: Vec<f32>,
+    pub bytes: Vec<u8>,
+}
+
+impl PendingUpload {
+    pub fn received(&self) -> u32 {
+        match self.format {
+            UploadFormat::Pcm => self.values.len() as u32,
+            _ => self.bytes.len() as u32,
+        }
+    }
+}
+
+/// Interpolation de lecture des samples (du moins coûteux au plus fidèle)
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interpolation {
+    Linear = 0,
+    Cubic = 1,
+    Sinc8 = 2,
+    Sinc32 = 3,
+}
+
+impl TryFrom<u8> for Interpolation {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Interpolation::Linear),
+            1 => Ok(Interpolation::Cubic),
+            2 => Ok(Interpolation::Sinc8),
+            3 => Ok(Interpolation::Sinc32),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoopMode {
+    NoLoop,
+    OneShot,    // joué jusqu'au bout, le note off est ignoré
+    Continuous, // boucle même pendant le release
+    Sustain,    // boucle tant que la note est tenue
+}
+
+#[derive(Clone)]
 pub struct Sample {
     pub id: u32,
-    pub values: Box<[f32]>,
-    pub hq: u8,
+    pub values: Arc<[f32]>, // planaire : canal 0 puis canal 1, partagé entre régions
+    pub channels: u8,
+    pub sample_rate: f32,
+    pub root_key: u8,
+    pub fine_tune: f32, // cents
+    pub gain: f32,
+    pub pan: f32, // -1.0 (gauche) à 1.0 (droite)
+    pub loop_mode: LoopMode,
+    pub loop_start: usize,
+    pub loop_end: usize, // exclusif
+    pub key_range: (u8, u8),
+    pub velocity_range: (u8, u8),
+    pub interpolation: Option<Interpolation>, // None : celle du sampler
+}
+
+impl Sample {
+    /// Sample bouclé sur toute sa longueur, joué à sa hauteur d'origine en C4
+    /// (ou C0 pour les tables haute qualité)
+    pub fn new(id: u32, values: impl Into<Arc<[f32]>>, channels: u8, hq: u8) -> Self {
+        let values = values.into();
+        let channels = channels.clamp(1, 2);
+        let frames = values.len() / channels as usize;
+        Self {
+            id,
+            values,
+            channels,
+            sample_rate: SAMPLE_RATE,
+            root_key: if hq == 0 { 60 } else { 12 },
+            fine_tune: 0.0,
+            gain: 1.0,
+            pan: 0.0,
+            loop_mode: LoopMode::Continuous,
+            loop_start: 0,
+            loop_end: frames,
+            key_range: (0, 127),
+            velocity_range: (0, 127),
+            interpolation: None,
+        }
+    }
+
+    pub fn frames(&self) -> usize {
+        self.values.len() / self.channels as usize
+    }
 }
@@ -24,8 +24,8 @@ self.onmessage = (e: MessageEvent) => {
       ringBufferSize,
       osc_queue_buffer,
       fx_queue_buffer,
-      sample_event_buffer,
-      sample_buffer,
+      sample_upload_buffer,
+      sample_transfer_buffer,
     } = e.data;
 
     const buffers = [
@@ -33,8 +33,8 @@ self.onmessage = (e: MessageEvent) => {
       midi_queue_buffer,
       osc_queue_buffer,
       fx_queue_buffer,
-      sample_event_buffer,
-      sample_buffer,
+      sample_upload_buffer,
+      sample_transfer_buffer,
     ];
 
     const all_valid =
@@ -52,10 +52,10 @@ self.onmessage = (e: MessageEvent) => {
         osc_queue_buffer instanceof SharedArrayBuffer,
         "ring buffer size:",
         typeof ringBufferSize === "number",
-        "sample_event_buffer",
-        sample_event_buffer instanceof SharedArrayBuffer,
-        "sample_buffer: ",
-        sample_buffer instanceof SharedArrayBuffer
+        "sample_upload_buffer",
+        sample_upload_buffer instanceof SharedArrayBuffer,
+        "sample_transfer_buffer: ",
+        sample_transfer_buffer instanceof SharedArrayBuffer
       );
 
       return;
@@ -70,8 +70,8 @@ self.onmessage = (e: MessageEvent) => {
       midi_queue_buffer,
       osc_queue_buffer,
       fx_queue_buffer,
-      sample_event_buffer,
-      sample_buffer
+      sample_upload_buffer,
+      sample_transfer_buffer
     );
 
     console.log("[RUST WORKER] initialisation done, processing loop...");
@@ -1,8 +1,6 @@
 import init, { generate_c0_table } from "./rust-sample-processor/build/rust_sample_processor.js";
 import type { InitInput } from "./rust-synth/build/rust_synth.js";
 
-let sample_buffer: SharedArrayBuffer;
-let sample_array: Float32Array;
 let wasm_ready: Promise<InitInput> | null = null;
 // fonction d'initialisation unique
 async function init_wasm() {
@@ -17,29 +15,29 @@ self.onmessage = async (e: MessageEvent) => {
     self.postMessage({ type: "log", message: "[SAMPLER PROCESSOR] wasm not init !" });
     return;
   } else if (e.data.type === "init" && !wasm_ready) {
-    sample_buffer = e.data.sample_buffer;
-    if (!(sample_buffer instanceof SharedArrayBuffer)) {
-      self.postMessage({
-        type: "log",
-        message: "[SAMPLER PROCESSOR] : an error occured, invalid sharredArrayByffer provided!",
-      });
-      return;
-    }
-    sample_array = new Float32Array(sample_buffer);
     await init_wasm();
     self.postMessage({
       type: "log",
       message: "[SAMPLER PROCESSOR] : wasm initialized sucessfully !",
     });
   } else {
-    const samples = e.data.samples as Float32Array;
+    const channels = e.data.channels as Float32Array[];
     const rate = e.data.sampleRate as number;
 
-    const output: Float32Array = generate_c0_table(samples, rate);
-    sample_array.set(output);
+    // une table par canal, mises bout à bout (format planaire attendu par le moteur) ;
+    // la table est renvoyée au thread principal, qui l'uploade par chunks
+    const tables = channels.map((channel) => generate_c0_table(channel, rate));
+    const output = new Float32Array(tables.reduce((length, table) => length + table.length, 0));
+    let offset = 0;
+    for (const table of tables) {
+      output.set(table, offset);
+      offset += table.length;
+    }
 
     e.data.event.length = output.length;
 
-    self.postMessage({ type: "sampler update", event: e.data.event });
+    self.postMessage({ type: "sampler update", event: e.data.event, samples: output }, [
+      output.buffer,
+    ]);
   }
 };
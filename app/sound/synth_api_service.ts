@@ -1,6 +1,7 @@
 import type { noteDTO } from "~/types/note";
 import { AudioEngineOrchestrator } from "./audio_engine_orchestrator";
 import type { SampleDataWithChannels } from "~/types/sampler";
+import init_rust_synth, { describe_effect } from "./rust-synth/build/rust_synth.js";
 
 const MIDI_EVENT_SIZE = 4;
 const MIDI_QUEUE_CAPACITY = 64;
@@ -22,44 +23,472 @@ export enum OscKey {
   PHASE,
   SAMPLE_ID,
   PAN,
+  PRESET, // bank * 128 + program (banques SF2)
+  INTERPOLATION,
+  SEND, // premier envoi : SEND + bus * 2 + SendParams
+  SMOOTHING = 21, // SEND + FX_BUSES * 2 : rampes du gain, du pan et des envois (ms)
+}
+
+export const FX_BUSES = 4;
+
+// chaînes d'effets : 0 master, 1 à FX_BUSES bus, puis CHANNEL_CHAIN_BASE + id du sampler
+export const CHANNEL_CHAIN_BASE = FX_BUSES + 1;
+
+// limiteur de sortie, réglé par edit_fx (LimiterParams) et edit_fx_slot sans être ajouté
+export const MASTER_LIMITER_ID = 1 << 20;
+
+export enum ChannelParams {
+  MUTE, // 0 ou 1
+  SOLO, // 0 ou 1
+}
+
+export enum SendParams {
+  LEVEL, // 0 à 1
+  PRE_FADER, // 0 ou 1, avant le gain et le pan du sampler
+}
+
+export enum Interpolation {
+  LINEAR,
+  CUBIC,
+  SINC_8,
+  SINC_32,
 }
 
 const FX_EVENT_SIZE = 16;
 const FX_QUEUE_CAPACITY = 64;
 const FX_BUFFER_SIZE = FX_EVENT_SIZE * FX_QUEUE_CAPACITY;
-
-const MAX_SAMPLE_LENGTH = 2 * 8_000_000;
-const SAMPLE_EVENT_SIZE = 6 * Int32Array.BYTES_PER_ELEMENT;
+// vumètres après la file : [master, (fx_id, réduction)...] en Int32, millièmes de dB
+const FX_METER_CAPACITY = 16;
+const FX_METER_SIZE = 2;
+const FX_METER_LENGTH = 1 + FX_METER_CAPACITY * FX_METER_SIZE;
+// réponse en amplitude d'un égaliseur : [séquence, fx_id] puis les points en dB
+const FX_RESPONSE_POINTS = 128;
+const FX_RESPONSE_MIN_FREQUENCY = 20;
+const FX_RESPONSE_MAX_FREQUENCY = 20000;
+const FX_RESPONSE_TIMEOUT_MS = 200;
+
+// upload des samples : [write_idx, read_idx, slots..., file d'événements..., statuts...]
+const SAMPLE_UPLOAD_QUEUE_CAPACITY = 32;
+const SAMPLE_UPLOAD_EVENT_SIZE = 11;
+const SAMPLE_UPLOAD_STATUS_CAPACITY = 32;
+const SAMPLE_UPLOAD_STATUS_SIZE = 3;
+const SAMPLE_TRANSFER_SLOTS = 4;
+const SAMPLE_TRANSFER_SLOT_SIZE = 262_144;
+
+const SAMPLE_UPLOAD_SLOTS_START = 2;
+const SAMPLE_UPLOAD_QUEUE_START = SAMPLE_UPLOAD_SLOTS_START + SAMPLE_TRANSFER_SLOTS;
+const SAMPLE_UPLOAD_STATUS_START =
+  SAMPLE_UPLOAD_QUEUE_START + SAMPLE_UPLOAD_QUEUE_CAPACITY * SAMPLE_UPLOAD_EVENT_SIZE;
+const SAMPLE_UPLOAD_BUFFER_LENGTH =
+  SAMPLE_UPLOAD_STATUS_START + SAMPLE_UPLOAD_STATUS_CAPACITY * SAMPLE_UPLOAD_STATUS_SIZE;
+
+const SAMPLE_UPLOAD_POLL_MS = 5;
 
 export type EffectParams = { index: number; value: number };
 
+export enum ParamCurve {
+  LINEAR,
+  LOGARITHMIC, // min > 0
+  STEPPED, // valeurs entières
+}
+
+// description d'un paramètre d'effet, son index est sa position dans describe_fx
+export type ParamInfo = {
+  name: string;
+  unit: string;
+  min: number;
+  max: number;
+  default: number;
+  curve: ParamCurve;
+  options: string[]; // libellés des valeurs 0, 1, 2... d'un choix, vide sinon
+};
+
+/** Valeur d'un paramètre pour une position de knob (0 à 100) selon sa courbe */
+export function knob_to_param(info: ParamInfo, position: number): number {
+  const ratio = Math.min(Math.max(position / 100, 0), 1);
+  switch (info.curve) {
+    case ParamCurve.LOGARITHMIC:
+      return info.min * Math.pow(info.max / info.min, ratio);
+    case ParamCurve.STEPPED:
+      return Math.round(info.min + ratio * (info.max - info.min));
+    default:
+      return info.min + ratio * (info.max - info.min);
+  }
+}
+
+/** Position de knob (0 à 100) d'une valeur, inverse de knob_to_param */
+export function param_to_knob(info: ParamInfo, value: number): number {
+  const ratio =
+    info.curve === ParamCurve.LOGARITHMIC
+      ? Math.log(value / info.min) / Math.log(info.max / info.min)
+      : (value - info.min) / (info.max - info.min);
+  return Math.min(Math.max(ratio, 0), 1) * 100;
+}
+
 export enum Effects {
   ECHO,
   FILTER,
+  REVERB,
+  CHORUS,
+  FLANGER,
+  PHASER,
+  DISTORTION,
+  COMPRESSOR,
+  LIMITER,
+  GATE,
+  EQUALIZER,
+  SVF,
+  LADDER,
+  CONVOLUTION,
+  TREMOLO,
+  AUTO_PAN,
+  RING_MOD,
+  PITCH_SHIFTER,
+  STEREO_TOOL,
+  VOCODER,
+  DUCKER,
 }
 
 export enum EchoParams {
-  DELAY,
+  DELAY, // ms, ignoré si DIVISION n'est pas OFF
   FEEDBACK,
   R_DELAY_OFFSET,
   L_DELAY_OFFSET,
   DRY,
   WET,
+  DIVISION, // NoteDivision
+  MODIFIER, // NoteModifier
+  PING_PONG, // 0 ou 1
+  LOW_CUT, // Hz, dans la boucle de feedback
+  HIGH_CUT, // Hz
+  SATURATION, // 0 à 1
+}
+
+export enum NoteDivision {
+  OFF,
+  WHOLE,
+  HALF,
+  QUARTER,
+  EIGHTH,
+  SIXTEENTH,
+  THIRTY_SECOND,
+}
+
+export enum NoteModifier {
+  STRAIGHT,
+  DOTTED,
+  TRIPLET,
 }
 
 export enum FilterParams {
   FREQUENCY,
   Q,
-  TYPE,
-  GAIN,
+  TYPE, // FilterType
+  GAIN, // dB, cloche, shelves et tilt
+  SLOPE, // 12, 24, 36 ou 48 dB/oct, passe-bas et passe-haut
+}
+
+export enum FilterType {
+  LOWPASS,
+  HIGHPASS,
+  BELL,
+  BANDPASS,
+  NOTCH,
+  ALLPASS,
+  LOW_SHELF,
+  HIGH_SHELF,
+  TILT,
+}
+
+export enum ReverbParams {
+  SIZE, // 0 à 1
+  DECAY, // secondes
+  PRE_DELAY, // ms
+  DAMPING, // 0 à 1
+  DIFFUSION, // 0 à 1
+  WIDTH, // 0 à 1
+  DRY,
+  WET,
+}
+
+export enum ChorusParams {
+  RATE, // Hz
+  DEPTH, // ms
+  DELAY, // ms
+  VOICES, // 1 à 4
+  SPREAD, // 0 à 1
+  DRY,
+  WET,
+}
+
+export enum FlangerParams {
+  RATE, // Hz
+  DEPTH, // ms
+  DELAY, // ms
+  FEEDBACK, // -0.95 à 0.95
+  SPREAD, // 0 à 1
+  DRY,
+  WET,
+}
+
+export enum PhaserParams {
+  RATE, // Hz
+  DEPTH, // 0 à 1
+  FREQUENCY, // Hz
+  FEEDBACK, // -0.95 à 0.95
+  STAGES, // 2 à 12
+  SPREAD, // 0 à 1
+  DRY,
+  WET,
+}
+
+export enum CompressorParams {
+  THRESHOLD, // -60 à 0 dB
+  RATIO, // 1 à 100
+  KNEE, // 0 à 24 dB
+  ATTACK, // ms
+  RELEASE, // ms
+  MAKEUP, // 0 à 24 dB
+  LINK, // 0 à 1
+  DRY,
+  WET,
+}
+
+export enum LimiterParams {
+  CEILING, // -24 à 0 dB
+  RELEASE, // ms
+  LOOKAHEAD, // 0.1 à 10 ms
+  INPUT_GAIN, // 0 à 24 dB
+  LINK, // 0 à 1
+}
+
+export enum GateParams {
+  THRESHOLD, // -96 à 0 dB
+  RATIO, // 1 à 100
+  KNEE, // 0 à 24 dB
+  ATTACK, // ms, ouverture
+  RELEASE, // ms, fermeture
+  RANGE, // atténuation max, 0 à 96 dB
+  LINK, // 0 à 1
+}
+
+export enum DuckerParams {
+  THRESHOLD, // -96 à 0 dB, niveau du sidechain qui déclenche
+  DEPTH, // 0 à 96 dB
+  ATTACK, // ms
+  HOLD, // ms
+  RELEASE, // ms
+}
+
+// signal de détection d'un effet de dynamique, voir set_sidechain
+export enum SidechainSource {
+  NONE, // le signal traité lui-même
+  SAMPLER, // sortie après fader d'un sampler, index : id du sampler
+  BUS, // sortie d'un bus, index : 0 à FX_BUSES - 1
+  MIDI, // enveloppe déclenchée par les note on, voir set_midi_envelope
+}
+
+export enum MidiEnvelopeParams {
+  NOTE, // -1 : toutes les notes
+  ATTACK, // ms
+  HOLD, // ms
+  RELEASE, // ms
+}
+
+export enum SvfParams {
+  CUTOFF, // Hz
+  RESONANCE, // Q, 0.5 à 40
+  LOW, // niveau de chaque sortie, -1 à 1
+  BAND,
+  HIGH,
+  DRY,
+  WET,
+}
+
+export enum LadderParams {
+  CUTOFF, // Hz
+  RESONANCE, // 0 à 1.1, auto-oscillation à partir de 1
+  DRIVE, // 0 à 24 dB
+  POLES, // 2 ou 4
+  DRY,
+  WET,
 }
 
+export enum ConvolutionParams {
+  IR_SAMPLE, // id du sample chargé comme réponse impulsionnelle
+  TRIM_START, // ms retirées au début de l'IR
+  LENGTH, // ms, 0 : IR entière (10 s max)
+  PRE_DELAY, // 0 à 500 ms
+  DRY,
+  WET,
+}
+
+export enum LfoWaveform {
+  SINE,
+  TRIANGLE,
+  SQUARE,
+  SAW_UP,
+  SAW_DOWN,
+}
+
+export enum TremoloParams {
+  RATE, // Hz, hors synchro
+  DEPTH, // 0 à 1
+  WAVEFORM, // LfoWaveform
+  PHASE_OFFSET, // déphasage du canal droit, 0 à 1
+  DIVISION, // NoteDivision
+  MODIFIER, // NoteModifier
+}
+
+export enum AutoPanParams {
+  RATE, // Hz, hors synchro
+  DEPTH, // 0 à 1, amplitude du balayage
+  WAVEFORM,
+  PHASE_OFFSET,
+  DIVISION,
+  MODIFIER,
+}
+
+export enum RingModParams {
+  FREQUENCY, // Hz de la porteuse, 0.1 à 5000
+  DEPTH, // 0 à 1
+  WAVEFORM,
+  PHASE_OFFSET,
+  DIVISION,
+  MODIFIER,
+}
+
+export const SHIFTER_VOICES = 4;
+
+// paramètre d'une voix : index de la voix × 4 + ShifterVoiceParams, globaux après les voix
+export enum ShifterVoiceParams {
+  SEMITONES, // -24 à 24
+  CENTS, // -100 à 100
+  LEVEL, // 0 à 1, 0 coupe la voix
+  PAN, // -1 à 1
+}
+
+export enum PitchShifterParams {
+  WINDOW = SHIFTER_VOICES * 4, // 10 à 100 ms
+  DRY,
+  WET,
+}
+
+export enum StereoToolParams {
+  WIDTH, // 0 (mono) à 2, 1 : inchangé
+  HAAS, // -30 à 30 ms, négatif retarde le canal gauche
+  BASS_MONO, // Hz, 0 : désactivé
+  SWAP, // 0 ou 1
+  INVERT_LEFT, // 0 ou 1
+  INVERT_RIGHT, // 0 ou 1
+  MONO, // 0 ou 1
+}
+
+export enum BandSpacing {
+  LOGARITHMIC,
+  MEL,
+  LINEAR,
+}
+
+export enum ModulatorSource {
+  SAMPLE, // sample chargé, joué en boucle
+  AUDIO_INPUT, // entrée audio, voir enable_audio_input
+}
+
+export enum VocoderParams {
+  SOURCE, // ModulatorSource
+  SAMPLE, // sample_id du modulateur
+  BANDS, // 4 à 32
+  SPACING, // BandSpacing
+  LOW, // Hz, borne basse de la première bande
+  HIGH, // Hz, borne haute de la dernière
+  ATTACK, // ms
+  RELEASE, // ms
+  NOISE, // 0 à 1, bruit ajouté à la porteuse
+  OUTPUT, // dB
+  DRY,
+  WET,
+}
+
+// paramètres appliqués par le mixer autour de n'importe quel effet
+export enum SlotParams {
+  BYPASS, // 0 ou 1, en fondu
+  MIX, // 0 (signal entrant) à 1 (sortie de l'effet)
+  GAIN, // -48 à 24 dB, sortie de l'effet
+  SMOOTHING, // 0 à 1000 ms, rampe des paramètres continus, du dosage et du gain
+}
+
+export const EQ_BANDS = 8;
+
+// paramètre d'une bande : index de la bande × 5 + EqBandParams, sortie après les bandes
+export enum EqBandParams {
+  TYPE, // FilterType
+  FREQUENCY, // Hz
+  GAIN, // -24 à 24 dB
+  Q,
+  ENABLED, // 0 ou 1
+}
+
+export const EQ_OUTPUT_PARAM = EQ_BANDS * 5;
+
+export type MagnitudeResponse = { frequencies: Float32Array; magnitudes: Float32Array };
+
+export enum DistortionCurve {
+  SOFT_CLIP, // tanh
+  HARD_CLIP,
+  FOLDBACK,
+  TUBE, // asymétrique
+  BITCRUSH,
+}
+
+export enum DistortionParams {
+  CURVE, // DistortionCurve
+  DRIVE, // 0 à 48 dB
+  OUTPUT, // -48 à 12 dB
+  TONE, // Hz
+  OVERSAMPLING, // 1, 2 ou 4
+  BITS, // 1 à 16
+  DOWNSAMPLE, // 1 à 64
+  DRY,
+  WET,
+}
+
+// sampler_id d'un upload qui n'est assigné à aucun sampler (réponse impulsionnelle...)
+export const NO_SAMPLER = -1;
+
+export enum UploadFormat {
+  PCM, // f32 planaires
+  WAV, // fichier brut, décodé par le moteur
+  SFZ, // paquet : le .sfz et les fichiers qu'il référence
+  SF2, // banque SoundFont 2 brute
+}
+
+export enum UploadStatus {
+  QUEUED = 0,
+  IN_PROGRESS = 1,
+  DONE = 2,
+  DONE_WITH_WARNINGS = 3, // détails dans la console du worker audio
+  UNKNOWN_SAMPLE = -1,
+  OUT_OF_ORDER = -2,
+  INVALID_SLOT = -3,
+  OVERFLOW = -4,
+  INVALID_LENGTH = -5,
+  INVALID_FORMAT = -6,
+  DECODE_ERROR = -7,
+}
+
+export type UploadState = {
+  status: UploadStatus;
+  received: number;
+};
+
 export type SampleEvent = {
   sampler_id: number;
   sample_id: number;
   length: number;
   channels: number;
   hq: number;
+  interpolation?: Interpolation; // absent : celle du sampler
 };
 
 export type SampleData = {
@@ -81,19 +510,25 @@ export class SynthApi {
   private static osc_write_index: Int32Array;
 
   public loaded_samples: SampleData[] = [];
-  private static sampler_event_buffer: SharedArrayBuffer;
-  private static sample_buffer: SharedArrayBuffer;
+  private static sample_upload_buffer: SharedArrayBuffer;
+  private static sample_upload_array: Int32Array;
+  private static sample_transfer_buffer: SharedArrayBuffer;
 
   private static fx_queue_buffer: SharedArrayBuffer;
   private static fx_queue_int_array: Int32Array;
   private static fx_queue_float_array: Float32Array;
   private static fx_write_index: Int32Array;
+  private static fx_meter_array: Int32Array;
+  private static fx_response_header: Int32Array;
+  private static fx_response_array: Float32Array;
 
   private nmbr_of_samplers = 0;
   private nmbr_of_fx = 0;
-  private static sample_event_index = 0;
+  private static upload_index = 0;
 
   private static sample_processor_worker: Worker;
+  // module wasm chargé sur ce thread pour les descriptions d'effets
+  private static descriptions_ready: Promise<unknown> | null = null;
 
   constructor() {
     console.log("[SYNTH API INIT]");
@@ -101,8 +536,7 @@ export class SynthApi {
 
     SynthApi.midi_queue_buffer = new SharedArrayBuffer(MIDI_BUFFER_SIZE);
 
-    SynthApi.init_sample_event();
-    SynthApi.init_sample_buffer();
+    SynthApi.init_sample_upload();
     SynthApi.init_sample_processor_worker();
 
     SynthApi.init_midi_queue();
@@ -128,7 +562,11 @@ export class SynthApi {
 
   private static init_fx_queue() {
     const control_size = 2 * Int32Array.BYTES_PER_ELEMENT;
-    SynthApi.fx_queue_buffer = new SharedArrayBuffer(control_size + FX_BUFFER_SIZE);
+    SynthApi.fx_queue_buffer = new SharedArrayBuffer(
+      control_size +
+        FX_BUFFER_SIZE +
+        (FX_METER_LENGTH + 2 + FX_RESPONSE_POINTS) * Int32Array.BYTES_PER_ELEMENT
+    );
 
     SynthApi.fx_write_index = new Int32Array(SynthApi.fx_queue_buffer, 0, 2);
 
@@ -143,6 +581,21 @@ export class SynthApi {
       control_size + 12 * FX_QUEUE_CAPACITY,
       FX_QUEUE_CAPACITY
     );
+
+    SynthApi.fx_meter_array = new Int32Array(
+      SynthApi.fx_queue_buffer,
+      control_size + FX_BUFFER_SIZE,
+      FX_METER_LENGTH
+    );
+
+    const response_offset =
+      control_size + FX_BUFFER_SIZE + FX_METER_LENGTH * Int32Array.BYTES_PER_ELEMENT;
+    SynthApi.fx_response_header = new Int32Array(SynthApi.fx_queue_buffer, response_offset, 2);
+    SynthApi.fx_response_array = new Float32Array(
+      SynthApi.fx_queue_buffer,
+      response_offset + 2 * Int32Array.BYTES_PER_ELEMENT,
+      FX_RESPONSE_POINTS
+    );
   }
 
   async init() {
@@ -150,8 +603,8 @@ export class SynthApi {
       SynthApi.midi_queue_buffer,
       SynthApi.osc_queue_buffer,
       SynthApi.fx_queue_buffer,
-      SynthApi.sampler_event_buffer,
-      SynthApi.sample_buffer
+      SynthApi.sample_upload_buffer,
+      SynthApi.sample_transfer_buffer
     );
   }
 
@@ -164,6 +617,15 @@ export class SynthApi {
     SynthApi.writeToMidiQueue(1, value, 0);
   }
 
+  static controlChange(controller: number, value: number) {
+    SynthApi.writeToMidiQueue(2, controller, value);
+  }
+
+  /** Change le preset des samplers joués par une banque SF2 (bank : dernier CC 0) */
+  static programChange(program: number) {
+    SynthApi.writeToMidiQueue(3, program, 0);
+  }
+
   private static writeToMidiQueue(event_type: number, note: number, velocity: number) {
     const write_pos = Atomics.load(SynthApi.midi_write_index, 0);
     const read_pos = Atomics.load(SynthApi.midi_write_index, 1);
@@ -241,6 +703,14 @@ export class SynthApi {
     SynthApi.writeToOscQueue(2, osc_index, key, value);
   }
 
+  public set_sampler_send(osc_index: number, bus: number, param: SendParams, value: number) {
+    SynthApi.writeToOscQueue(2, osc_index, OscKey.SEND + bus * 2 + param, value);
+  }
+
+  public select_preset(osc_index: number, bank: number, program: number) {
+    SynthApi.writeToOscQueue(2, osc_index, OscKey.PRESET, bank * 128 + program);
+  }
+
   private static convert_ms_to_sample(ms: number) {
     return Math.floor((ms / 1000) * 44100);
   }
@@ -284,7 +754,31 @@ export class SynthApi {
     return id;
   }
 
-  edit_fx(id: number, param_index: EchoParams | FilterParams, param_value: number) {
+  edit_fx(
+    id: number,
+    param_index:
+      | EchoParams
+      | FilterParams
+      | ReverbParams
+      | ChorusParams
+      | FlangerParams
+      | PhaserParams
+      | DistortionParams
+      | CompressorParams
+      | LimiterParams
+      | GateParams
+      | SvfParams
+      | LadderParams
+      | ConvolutionParams
+      | TremoloParams
+      | AutoPanParams
+      | RingModParams
+      | PitchShifterParams
+      | StereoToolParams
+      | VocoderParams
+      | DuckerParams,
+    param_value: number
+  ) {
     SynthApi.write_to_fx_queue(id, 2, param_index, param_value);
   }
 
@@ -292,9 +786,108 @@ export class SynthApi {
     SynthApi.write_to_fx_queue(id, 1, 0, 0);
   }
 
-  private static init_sample_buffer() {
-    SynthApi.sample_buffer = new SharedArrayBuffer(
-      Float32Array.BYTES_PER_ELEMENT * MAX_SAMPLE_LENGTH
+  /**
+   * Déplace un effet à `position` dans une chaîne (0 : master, n : bus n,
+   * CHANNEL_CHAIN_BASE + id : tranche d'un sampler) en conservant son état
+   */
+  move_fx(id: number, position: number, chain = 0) {
+    SynthApi.write_to_fx_queue(id, 5, position, chain);
+  }
+
+  /** Mute et solo de la tranche d'un sampler */
+  update_channel(sampler_id: number, param: ChannelParams, value: number) {
+    SynthApi.write_to_fx_queue(sampler_id, 8, param, value);
+  }
+
+  /** Niveau (0 à 1) du retour d'un bus auxiliaire sur le master */
+  set_bus_return(bus: number, level: number) {
+    SynthApi.write_to_fx_queue(bus, 7, 0, level);
+  }
+
+  /** Paramètres d'un type d'effet, dans l'ordre des index attendus par edit_fx */
+  async describe_fx(effect: Effects): Promise<ParamInfo[]> {
+    SynthApi.descriptions_ready ??= init_rust_synth();
+    await SynthApi.descriptions_ready;
+    return describe_effect(effect) as ParamInfo[];
+  }
+
+  edit_fx_slot(id: number, param: SlotParams, value: number) {
+    SynthApi.write_to_fx_queue(id, 6, param, value);
+  }
+
+  /** Source de détection d'un compresseur, gate ou ducker */
+  set_sidechain(id: number, source: SidechainSource, index = 0) {
+    SynthApi.write_to_fx_queue(id, 9, source, index);
+  }
+
+  /** Enveloppe partagée par les effets dont le sidechain est SidechainSource.MIDI */
+  set_midi_envelope(param: MidiEnvelopeParams, value: number) {
+    SynthApi.write_to_fx_queue(0, 10, param, value);
+  }
+
+  /** Demande l'accès au micro et l'envoie au moteur comme entrée audio */
+  async enable_audio_input(): Promise<boolean> {
+    return SynthApi.soundEngine.enable_audio_input();
+  }
+
+  /** Tempo de l'hôte (BPM) pour les effets synchronisés */
+  set_tempo(bpm: number) {
+    SynthApi.write_to_fx_queue(0, 4, 0, bpm);
+  }
+
+  edit_eq_band(id: number, band: number, param: EqBandParams, value: number) {
+    SynthApi.write_to_fx_queue(id, 2, band * 5 + param, value);
+  }
+
+  set_eq_output(id: number, gain_db: number) {
+    SynthApi.write_to_fx_queue(id, 2, EQ_OUTPUT_PARAM, gain_db);
+  }
+
+  edit_shifter_voice(id: number, voice: number, param: ShifterVoiceParams, value: number) {
+    SynthApi.write_to_fx_queue(id, 2, voice * 4 + param, value);
+  }
+
+  /** Réponse en amplitude (dB) d'un égaliseur calculée par le moteur, null sans réponse */
+  async get_magnitude_response(id: number): Promise<MagnitudeResponse | null> {
+    const sequence = Atomics.load(SynthApi.fx_response_header, 0);
+    SynthApi.write_to_fx_queue(id, 3, 0, 0);
+
+    const start = performance.now();
+    while (Atomics.load(SynthApi.fx_response_header, 0) === sequence) {
+      if (performance.now() - start > FX_RESPONSE_TIMEOUT_MS) return null;
+      await SynthApi.wait(SAMPLE_UPLOAD_POLL_MS);
+    }
+    if (Atomics.load(SynthApi.fx_response_header, 1) !== id) return null;
+
+    const ratio = FX_RESPONSE_MAX_FREQUENCY / FX_RESPONSE_MIN_FREQUENCY;
+    const frequencies = Float32Array.from(
+      { length: FX_RESPONSE_POINTS },
+      (_, i) => FX_RESPONSE_MIN_FREQUENCY * Math.pow(ratio, i / (FX_RESPONSE_POINTS - 1))
+    );
+    return { frequencies, magnitudes: Float32Array.from(SynthApi.fx_response_array) };
+  }
+
+  /** Réduction de gain (dB) d'un compresseur, limiteur ou gate sur le dernier bloc */
+  get_gain_reduction(id: number): number | null {
+    const offset = 1 + (id % FX_METER_CAPACITY) * FX_METER_SIZE;
+    // l'entrée a pu être réutilisée par un autre effet
+    if (Atomics.load(SynthApi.fx_meter_array, offset) !== id) return null;
+    return Atomics.load(SynthApi.fx_meter_array, offset + 1) / 1000;
+  }
+
+  /** Réduction de gain (dB) du limiteur de sortie, après la chaîne master */
+  get_master_gain_reduction(): number {
+    return Atomics.load(SynthApi.fx_meter_array, 0) / 1000;
+  }
+
+  private static init_sample_upload() {
+    SynthApi.sample_upload_buffer = new SharedArrayBuffer(
+      SAMPLE_UPLOAD_BUFFER_LENGTH * Int32Array.BYTES_PER_ELEMENT
+    );
+    SynthApi.sample_upload_array = new Int32Array(SynthApi.sample_upload_buffer);
+
+    SynthApi.sample_transfer_buffer = new SharedArrayBuffer(
+      SAMPLE_TRANSFER_SLOTS * SAMPLE_TRANSFER_SLOT_SIZE * Float32Array.BYTES_PER_ELEMENT
     );
   }
 
@@ -307,37 +900,138 @@ export class SynthApi {
       }
     );
 
-    SynthApi.sample_processor_worker.postMessage({
-      type: "init",
-      sample_buffer: SynthApi.sample_buffer,
-    });
+    SynthApi.sample_processor_worker.postMessage({ type: "init" });
     SynthApi.sample_processor_worker.onmessage = (e: MessageEvent) => {
       if (!e) return;
       if (e.data.type === "log") {
         console.log(e.data.message);
       } else if (e.data.type === "sampler update") {
-        this.notify_sample_event(e.data.event as SampleEvent);
+        SynthApi.upload_sample(
+          e.data.event as SampleEvent,
+          e.data.samples as Float32Array,
+          UploadFormat.PCM
+        );
       }
     };
   }
 
-  private static get sample_event_view(): Uint32Array {
-    return new Uint32Array(SynthApi.sampler_event_buffer);
+  private static wait(ms: number) {
+    return new Promise((resolve) => setTimeout(resolve, ms));
+  }
+
+  private static async acquire_transfer_slot(): Promise<number> {
+    const states = SynthApi.sample_upload_array;
+    for (;;) {
+      for (let slot = 0; slot < SAMPLE_TRANSFER_SLOTS; slot++) {
+        if (Atomics.compareExchange(states, SAMPLE_UPLOAD_SLOTS_START + slot, 0, 1) === 0) {
+          return slot;
+        }
+      }
+      await SynthApi.wait(SAMPLE_UPLOAD_POLL_MS);
+    }
+  }
+
+  private static async write_to_upload_queue(fields: number[]) {
+    const control = SynthApi.sample_upload_array;
+    for (;;) {
+      const write_pos = Atomics.load(control, 0);
+      const read_pos = Atomics.load(control, 1);
+      const next_write_pos = (write_pos + 1) % SAMPLE_UPLOAD_QUEUE_CAPACITY;
+
+      if (next_write_pos !== read_pos) {
+        control.set(fields, SAMPLE_UPLOAD_QUEUE_START + write_pos * SAMPLE_UPLOAD_EVENT_SIZE);
+        Atomics.store(control, 0, next_write_pos);
+        return;
+      }
+      await SynthApi.wait(SAMPLE_UPLOAD_POLL_MS);
+    }
   }
 
-  private static init_sample_event() {
-    SynthApi.sampler_event_buffer = new SharedArrayBuffer(SAMPLE_EVENT_SIZE);
+  public static get_upload_state(upload_id: number): UploadState | null {
+    const offset =
+      SAMPLE_UPLOAD_STATUS_START +
+      (upload_id % SAMPLE_UPLOAD_STATUS_CAPACITY) * SAMPLE_UPLOAD_STATUS_SIZE;
+    const control = SynthApi.sample_upload_array;
+
+    // l'entrée a pu être réutilisée par un upload plus récent
+    if (Atomics.load(control, offset) !== upload_id) return null;
+
+    return {
+      status: Atomics.load(control, offset + 1) as UploadStatus,
+      received: Atomics.load(control, offset + 2),
+    };
   }
 
-  public static notify_sample_event(event: SampleEvent) {
-    const evt = SynthApi.sample_event_view;
-    SynthApi.sample_event_index++;
-    evt[0] = SynthApi.sample_event_index;
-    evt[1] = event.sampler_id;
-    evt[2] = event.sample_id;
-    evt[3] = event.length;
-    evt[4] = event.channels;
-    evt[5] = event.hq;
+  /**
+   * Envoie un sample au moteur par chunks d'un slot de transfert : des f32 pour le PCM,
+   * des octets pour un fichier. Résout avec le statut final renvoyé par le thread audio.
+   */
+  public static async upload_sample(
+    event: SampleEvent,
+    values: Float32Array | Uint8Array | null,
+    format: UploadFormat
+  ): Promise<UploadStatus> {
+    const upload_id = ++SynthApi.upload_index;
+    const total_length = values ? values.length : 0;
+    const chunk_size =
+      format === UploadFormat.PCM
+        ? SAMPLE_TRANSFER_SLOT_SIZE
+        : SAMPLE_TRANSFER_SLOT_SIZE * Float32Array.BYTES_PER_ELEMENT;
+
+    const status_offset =
+      SAMPLE_UPLOAD_STATUS_START +
+      (upload_id % SAMPLE_UPLOAD_STATUS_CAPACITY) * SAMPLE_UPLOAD_STATUS_SIZE;
+    Atomics.store(SynthApi.sample_upload_array, status_offset, upload_id);
+    Atomics.store(SynthApi.sample_upload_array, status_offset + 1, UploadStatus.QUEUED);
+    Atomics.store(SynthApi.sample_upload_array, status_offset + 2, 0);
+
+    const header = (offset: number, length: number, slot: number) => [
+      upload_id,
+      event.sampler_id,
+      event.sample_id,
+      total_length,
+      offset,
+      length,
+      slot,
+      event.channels,
+      event.hq,
+      format,
+      event.interpolation === undefined ? 0 : event.interpolation + 1,
+    ];
+
+    if (!values || total_length === 0) {
+      await SynthApi.write_to_upload_queue(header(0, 0, -1));
+    }
+
+    for (let offset = 0; values && offset < total_length; offset += chunk_size) {
+      const end = Math.min(offset + chunk_size, total_length);
+      const slot = await SynthApi.acquire_transfer_slot();
+      const slot_offset = slot * SAMPLE_TRANSFER_SLOT_SIZE * Float32Array.BYTES_PER_ELEMENT;
+
+      if (values instanceof Float32Array) {
+        new Float32Array(SynthApi.sample_transfer_buffer, slot_offset, end - offset).set(
+          values.subarray(offset, end)
+        );
+      } else {
+        new Uint8Array(SynthApi.sample_transfer_buffer, slot_offset, end - offset).set(
+          values.subarray(offset, end)
+        );
+      }
+
+      await SynthApi.write_to_upload_queue(header(offset, end - offset, slot));
+    }
+
+    for (;;) {
+      const state = SynthApi.get_upload_state(upload_id);
+      if (!state) return UploadStatus.QUEUED;
+      if (state.status >= UploadStatus.DONE || state.status < 0) {
+        if (state.status < 0) {
+          console.error(`[UPLOAD ${upload_id}] erreur ${UploadStatus[state.status]}`);
+        }
+        return state.status;
+      }
+      await SynthApi.wait(SAMPLE_UPLOAD_POLL_MS);
+    }
   }
 
   public async import_sample(
@@ -355,7 +1049,8 @@ export class SynthApi {
 
     const array_buffer = await file.arrayBuffer();
     const audio_ctx = new AudioContext();
-    const audio_buffer = await audio_ctx.decodeAudioData(array_buffer);
+    // decodeAudioData détache le buffer qu'on lui passe : on garde l'original pour l'upload
+    const audio_buffer = await audio_ctx.decodeAudioData(array_buffer.slice(0));
 
     const channels: Float32Array[] = [];
     for (let i = 0; i < audio_buffer.numberOfChannels; i++) {
@@ -374,36 +1069,27 @@ export class SynthApi {
 
       this.loaded_samples.push(new_sample);
     } else {
-      let total_length = channels[0].length;
-      if (channels.length >= 2) {
-        total_length += channels[1].length;
-      }
-
-      if (total_length > MAX_SAMPLE_LENGTH) {
-        console.warn("Sample trop long pour être inséré dans le buffer !");
-        return;
-      }
-      const buffer_view = new Float32Array(SynthApi.sample_buffer, 0, total_length);
-
-      buffer_view.set(channels[0], 0);
-      if (channels.length >= 2) {
-        buffer_view.set(channels[1], channels[0].length);
-      }
-
       const new_sample_id = this.get_new_sample_id();
 
-      SynthApi.notify_sample_event({
-        sampler_id: sampler_id,
-        sample_id: new_sample_id,
-        length: total_length,
-        channels: channels.length,
-        hq: 0,
-      });
+      // le fichier est décodé par le moteur (boucles, root key, plages de l'instrument)
+      const status = await SynthApi.upload_sample(
+        {
+          sampler_id: sampler_id,
+          sample_id: new_sample_id,
+          length: array_buffer.byteLength,
+          channels: 0,
+          hq: 0,
+        },
+        new Uint8Array(array_buffer),
+        UploadFormat.WAV
+      );
+
+      if (status < UploadStatus.DONE) return;
 
       const new_sample: SampleData = {
         duration_seconds: audio_buffer.duration,
         high_quality: false,
-        sample_id: this.get_new_sample_id(),
+        sample_id: new_sample_id,
         title: file.name,
       };
 
@@ -416,6 +1102,154 @@ export class SynthApi {
     }
   }
 
+  /**
+   * Importe un instrument SFZ : `files` contient le .sfz et les samples qu'il référence
+   * (sélection de dossier ou multiple). L'instrument est enregistré comme un sample.
+   */
+  public async import_instrument(
+    files: File[],
+    sampler_id: number
+  ): Promise<SampleDataWithChannels | void> {
+    const sfz = files.find((f) => f.name.toLowerCase().endsWith(".sfz"));
+    if (!sfz) return;
+
+    console.log("[IMPORT INSTRUMENT] processing...");
+
+    // [nombre de fichiers] puis pour chacun [taille du nom][nom][taille][octets], en little endian
+    const encoder = new TextEncoder();
+    const entries = await Promise.all(
+      files.map(async (f) => ({
+        name: encoder.encode(f.webkitRelativePath || f.name),
+        data: new Uint8Array(await f.arrayBuffer()),
+      }))
+    );
+    const size = entries.reduce((acc, e) => acc + 8 + e.name.length + e.data.length, 4);
+    const bundle = new Uint8Array(size);
+    const view = new DataView(bundle.buffer);
+
+    view.setUint32(0, entries.length, true);
+    let offset = 4;
+    for (const entry of entries) {
+      view.setUint32(offset, entry.name.length, true);
+      bundle.set(entry.name, offset + 4);
+      offset += 4 + entry.name.length;
+      view.setUint32(offset, entry.data.length, true);
+      bundle.set(entry.data, offset + 4);
+      offset += 4 + entry.data.length;
+    }
+
+    const new_sample_id = this.get_new_sample_id();
+    const status = await SynthApi.upload_sample(
+      {
+        sampler_id: sampler_id,
+        sample_id: new_sample_id,
+        length: bundle.length,
+        channels: 0,
+        hq: 0,
+      },
+      bundle,
+      UploadFormat.SFZ
+    );
+
+    if (status < UploadStatus.DONE) return;
+
+    const new_instrument: SampleData = {
+      duration_seconds: 0,
+      high_quality: false,
+      sample_id: new_sample_id,
+      title: sfz.name,
+    };
+
+    this.loaded_samples.push(new_instrument);
+
+    return { ...new_instrument, channels: [] };
+  }
+
+  /**
+   * Importe une banque SF2. Le sampler joue son premier preset ; les autres se
+   * sélectionnent avec `select_preset` ou un program change MIDI.
+   */
+  public async import_soundfont(
+    file: File,
+    sampler_id: number
+  ): Promise<SampleDataWithChannels | void> {
+    console.log("[IMPORT SOUNDFONT] processing...");
+
+    const bytes = new Uint8Array(await file.arrayBuffer());
+    const new_sample_id = this.get_new_sample_id();
+    const status = await SynthApi.upload_sample(
+      {
+        sampler_id: sampler_id,
+        sample_id: new_sample_id,
+        length: bytes.length,
+        channels: 0,
+        hq: 0,
+      },
+      bytes,
+      UploadFormat.SF2
+    );
+
+    if (status < UploadStatus.DONE) return;
+
+    const new_bank: SampleData = {
+      duration_seconds: 0,
+      high_quality: false,
+      sample_id: new_sample_id,
+      title: file.name,
+    };
+
+    this.loaded_samples.push(new_bank);
+
+    return { ...new_bank, channels: [] };
+  }
+
+  /**
+   * Charge un WAV comme réponse impulsionnelle pour l'effet de convolution : le sample
+   * est stocké par le moteur sans être assigné à un sampler.
+   */
+  public async import_impulse_response(file: File): Promise<SampleData | void> {
+    return this.import_unassigned_sample(file);
+  }
+
+  /** Charge un WAV utilisé comme modulateur du vocodeur */
+  public async import_modulator(file: File): Promise<SampleData | void> {
+    return this.import_unassigned_sample(file);
+  }
+
+  private async import_unassigned_sample(file: File): Promise<SampleData | void> {
+    if (file.type !== "audio/wav") {
+      console.log("invalid format for ", file.name);
+      return;
+    }
+
+    const bytes = new Uint8Array(await file.arrayBuffer());
+    const new_sample_id = this.get_new_sample_id();
+    const status = await SynthApi.upload_sample(
+      {
+        sampler_id: NO_SAMPLER,
+        sample_id: new_sample_id,
+        length: bytes.length,
+        channels: 0,
+        hq: 0,
+      },
+      bytes,
+      UploadFormat.WAV
+    );
+
+    if (status < UploadStatus.DONE) return;
+
+    const sample: SampleData = {
+      duration_seconds: 0,
+      high_quality: false,
+      sample_id: new_sample_id,
+      title: file.name,
+    };
+
+    this.loaded_samples.push(sample);
+
+    return sample;
+  }
+
   public async set_existing_sample(id: number, sampler_id: number) {
     const sample = this.loaded_samples.find((e) => e.sample_id === id);
     if (!sample) {
@@ -430,7 +1264,7 @@ export class SynthApi {
       length: 0,
     };
 
-    SynthApi.notify_sample_event(event);
+    await SynthApi.upload_sample(event, null, UploadFormat.PCM);
   }
 
   private handleHqSample(
@@ -440,24 +1274,21 @@ export class SynthApi {
     sample_id: number
   ) {
     if (channels[1] && audio_buffer.duration < 5) {
-      const interleaved = new Float32Array(channels[0].length + channels[1].length);
-      interleaved.set(channels[0], 0);
-      interleaved.set(channels[1], channels[0].length);
-
+      // chaque canal est rééchantillonné séparément par le worker
       SynthApi.sample_processor_worker.postMessage({
-        samples: interleaved,
+        channels: [channels[0], channels[1]],
         sampleRate: audio_buffer.sampleRate,
         event: {
           sampler_id: sampler_id,
           sample_id: sample_id,
-          length: interleaved.length,
+          length: channels[0].length * 2,
           channels: 2,
           hq: 1,
         },
       });
     } else if (audio_buffer.duration < 10) {
       SynthApi.sample_processor_worker.postMessage({
-        samples: channels[0],
+        channels: [channels[0]],
         sampleRate: audio_buffer.sampleRate,
         event: {
           sampler_id: sampler_id,
@@ -0,0 +1,7 @@
+export type AutoPan = {
+  id: number;
+  rate: number;
+  width: number;
+  waveform: number;
+  phase_offset: number;
+};
@@ -0,0 +1,10 @@
+export type Chorus = {
+  id: number;
+  rate: number;
+  depth: number;
+  base_delay: number;
+  voices: number;
+  spread: number;
+  dry: number;
+  wet: number;
+};
@@ -0,0 +1,12 @@
+export type Compressor = {
+  id: number;
+  threshold: number;
+  ratio: number;
+  knee: number;
+  attack: number;
+  release: number;
+  makeup: number;
+  link: number;
+  dry: number;
+  wet: number;
+};
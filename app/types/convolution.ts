@@ -0,0 +1,9 @@
+export type Convolution = {
+  id: number;
+  ir_sample: number | null;
+  trim_start: number;
+  length: number;
+  pre_delay: number;
+  dry: number;
+  wet: number;
+};
@@ -0,0 +1,12 @@
+export type Distortion = {
+  id: number;
+  curve: number;
+  drive: number;
+  output: number;
+  tone: number;
+  oversampling: number;
+  bits: number;
+  downsample: number;
+  dry: number;
+  wet: number;
+};
@@ -0,0 +1,8 @@
+export type Ducker = {
+  id: number;
+  threshold: number;
+  duck: number; // profondeur
+  attack: number;
+  hold: number;
+  release: number;
+};
@@ -6,4 +6,8 @@ export type Echo = {
   l_delay_offset: number;
   dry: number;
   wet: number;
+  ping_pong: boolean;
+  low_cut: number;
+  high_cut: number;
+  saturation: number;
 };
@@ -0,0 +1,15 @@
+import type { FilterType } from "~/sound/synth_api_service";
+
+export type EqBand = {
+  type: FilterType;
+  frequency: number;
+  gain: number;
+  q: number;
+  enabled: boolean;
+};
+
+export type Equalizer = {
+  id: number;
+  bands: EqBand[];
+  output: number;
+};
@@ -0,0 +1,10 @@
+export type Flanger = {
+  id: number;
+  rate: number;
+  depth: number;
+  manual: number;
+  feedback: number;
+  spread: number;
+  dry: number;
+  wet: number;
+};
@@ -0,0 +1,10 @@
+export type Gate = {
+  id: number;
+  threshold: number;
+  ratio: number;
+  knee: number;
+  attack: number;
+  release: number;
+  range: number;
+  link: number;
+};
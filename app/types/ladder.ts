@@ -0,0 +1,9 @@
+export type Ladder = {
+  id: number;
+  cutoff: number;
+  resonance: number;
+  drive: number;
+  poles: number;
+  dry: number;
+  wet: number;
+};
@@ -0,0 +1,8 @@
+export type Limiter = {
+  id: number;
+  ceiling: number;
+  release: number;
+  lookahead: number;
+  input_gain: number;
+  link: number;
+};
@@ -0,0 +1,11 @@
+export type Phaser = {
+  id: number;
+  rate: number;
+  depth: number;
+  center: number;
+  feedback: number;
+  stages: number;
+  spread: number;
+  dry: number;
+  wet: number;
+};
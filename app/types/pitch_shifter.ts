@@ -0,0 +1,14 @@
+export type ShifterVoice = {
+  semitones: number;
+  cents: number;
+  level: number;
+  pan: number;
+};
+
+export type PitchShifter = {
+  id: number;
+  harmony: ShifterVoice[];
+  window: number;
+  dry: number;
+  wet: number;
+};
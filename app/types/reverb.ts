@@ -0,0 +1,11 @@
+export type Reverb = {
+  id: number;
+  size: number;
+  decay: number;
+  pre_delay: number;
+  damping: number;
+  diffusion: number;
+  width: number;
+  dry: number;
+  wet: number;
+};
@@ -0,0 +1,7 @@
+export type RingModulator = {
+  id: number;
+  carrier: number;
+  depth: number;
+  waveform: number;
+  phase_offset: number;
+};
@@ -1,4 +1,4 @@
-import type { SampleData } from "~/sound/synth_api_service";
+import type { Interpolation, SampleData } from "~/sound/synth_api_service";
 
 export type Adsr = {
   attack: number;
@@ -8,6 +8,11 @@ export type Adsr = {
   release: number;
 };
 
+export type SamplerSend = {
+  level: number;
+  pre_fader: boolean;
+};
+
 export type Sampler = {
   id: number;
   sample_id: number;
@@ -15,6 +20,8 @@ export type Sampler = {
   pan: number;
   gain: number;
   shift: number;
+  interpolation: Interpolation;
+  sends: SamplerSend[]; // un par bus auxiliaire
 };
 
 export type SampleDataWithChannels = SampleData & { channels: Float32Array<ArrayBufferLike>[] };
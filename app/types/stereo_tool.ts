@@ -0,0 +1,10 @@
+export type StereoTool = {
+  id: number;
+  width: number;
+  haas: number;
+  bass_mono: number;
+  swap: boolean;
+  invert_left: boolean;
+  invert_right: boolean;
+  mono: boolean;
+};
@@ -0,0 +1,10 @@
+export type Svf = {
+  id: number;
+  cutoff: number;
+  resonance: number;
+  low: number;
+  band: number;
+  high: number;
+  dry: number;
+  wet: number;
+};
@@ -0,0 +1,7 @@
+export type Tremolo = {
+  id: number;
+  rate: number;
+  intensity: number;
+  waveform: number;
+  phase_offset: number;
+};
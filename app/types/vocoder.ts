@@ -0,0 +1,15 @@
+export type Vocoder = {
+  id: number;
+  modulator: number; // ModulatorSource
+  modulator_sample: number | null;
+  band_count: number;
+  spacing: number; // BandSpacing
+  low: number;
+  high: number;
+  attack: number;
+  release: number;
+  noise: number;
+  output: number;
+  dry: number;
+  wet: number;
+};